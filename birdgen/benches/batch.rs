@@ -0,0 +1,54 @@
+// Three birds (what the app builds on every re-roll), one after the other vs. as a batch
+// Run with `cargo bench -p birdgen --features parallel` to see the speedup
+use birdgen::{
+    BirdGenInputs, BirdMeshSettings, GOOD_BIRDS, generate_bird_body_csg_mesh_with,
+    generate_bird_csg_batch, generate_bird_head_csg_mesh_with, parallel_generation_enabled,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+fn good_birds(count: usize) -> Vec<BirdGenInputs> {
+    GOOD_BIRDS
+        .iter()
+        .take(count)
+        .map(|seed| {
+            let mut bird = BirdGenInputs::default();
+            bird.update_from_seed_string(seed.to_string())
+                .expect("good birds should have good seeds");
+            bird
+        })
+        .collect()
+}
+
+fn three_birds(c: &mut Criterion) {
+    let birds = good_birds(3);
+    let settings = BirdMeshSettings::default();
+    let mut group = c.benchmark_group("three birds");
+    // each iteration is a lot of CSG, keep the sample count down
+    group.sample_size(10);
+    group.bench_function("one at a time", |b| {
+        b.iter(|| {
+            black_box(&birds)
+                .iter()
+                .map(|bird| {
+                    (
+                        generate_bird_head_csg_mesh_with(bird, &settings),
+                        generate_bird_body_csg_mesh_with(bird, &settings),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    let batch_name = if parallel_generation_enabled() {
+        "batch (parallel)"
+    } else {
+        "batch (sequential fallback)"
+    };
+    group.bench_function(batch_name, |b| {
+        b.iter(|| generate_bird_csg_batch(black_box(&birds), &settings))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, three_birds);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+// Numbers for each step of bird generation, for every good bird plus some seeded random ones,
+// at each precision. Each stage's inputs get built in the setup and criterion times just that step
+// Run with `cargo bench -p birdgen --bench pipeline` (BIRDGEN_BENCH_TRIANGLES=1 to print the
+// triangle counts after each stage too)
+use birdgen::{
+    BirdGenInputs, BirdMeshSettings, ExportOptions, GOOD_BIRDS, GenerationPart, PipelineStage,
+    Precision, StageInput, profile_bird_generation, profile_bird_stage_inputs,
+};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use rand::{SeedableRng, rngs::StdRng};
+
+const RANDOM_BIRDS: usize = 8;
+// fixed so the random birds are the same every run
+const RANDOM_SEED: u64 = 0xB1_2D5;
+
+const HEAD_STAGES: [PipelineStage; 5] = [
+    PipelineStage::Primitives,
+    PipelineStage::Unions,
+    PipelineStage::Hulls,
+    PipelineStage::Renormalize,
+    PipelineStage::Subdivide,
+];
+const BODY_STAGES: [PipelineStage; 5] = [
+    PipelineStage::Primitives,
+    PipelineStage::Unions,
+    PipelineStage::Hulls,
+    PipelineStage::Renormalize,
+    PipelineStage::BaseCut,
+];
+
+fn bench_birds() -> Vec<(String, BirdGenInputs)> {
+    let mut birds: Vec<(String, BirdGenInputs)> = GOOD_BIRDS
+        .iter()
+        .enumerate()
+        .map(|(i, seed)| {
+            let mut bird = BirdGenInputs::default();
+            bird.update_from_seed_string(seed.to_string())
+                .expect("good birds should have good seeds");
+            (format!("good-{i}"), bird)
+        })
+        .collect();
+    let mut rng = StdRng::seed_from_u64(RANDOM_SEED);
+    for i in 0..RANDOM_BIRDS {
+        let mut bird = BirdGenInputs::default();
+        bird.randomize_values_with_rng(&mut rng);
+        birds.push((format!("random-{i}"), bird));
+    }
+    birds
+}
+
+// only with BIRDGEN_BENCH_TRIANGLES set, otherwise it's a wall of text before every run
+fn print_triangle_counts(birds: &[(String, BirdGenInputs)], settings: &BirdMeshSettings) {
+    if std::env::var_os("BIRDGEN_BENCH_TRIANGLES").is_none() {
+        return;
+    }
+    println!(
+        "triangles after each stage ({:?} precision)",
+        settings.precision
+    );
+    for (name, bird) in birds {
+        let (_, profile) = profile_bird_generation(bird, settings);
+        for (part, stages) in [
+            (GenerationPart::Head, HEAD_STAGES),
+            (GenerationPart::Body, BODY_STAGES),
+        ] {
+            let counts: Vec<String> = stages
+                .iter()
+                .filter_map(|stage| {
+                    let triangles = profile.triangles_after(part, *stage)?;
+                    Some(format!("{} {triangles}", stage.name()))
+                })
+                .collect();
+            println!("  {name} {part:?}: {}", counts.join(", "));
+        }
+    }
+}
+
+fn pipeline_stages(c: &mut Criterion) {
+    let birds = bench_birds();
+    for precision in Precision::ALL {
+        let settings = BirdMeshSettings {
+            precision,
+            ..Default::default()
+        };
+        print_triangle_counts(&birds, &settings);
+        let mut group = c.benchmark_group(format!("{precision:?}").to_lowercase());
+        // every sample is at least one whole bird, keep the sample count down
+        group.sample_size(10);
+        for (part, stages) in [
+            (GenerationPart::Head, HEAD_STAGES),
+            (GenerationPart::Body, BODY_STAGES),
+        ] {
+            for stage in stages {
+                let name = format!("{part:?}/{}", stage.name()).to_lowercase();
+                if stage == PipelineStage::Primitives {
+                    // straight from the inputs, nothing to build ahead of time, so these come out
+                    // of the profiler's clock instead (cycling through the birds)
+                    group.bench_function(name, |b| {
+                        let mut next_bird = birds.iter().cycle();
+                        b.iter_custom(|iters| {
+                            (0..iters)
+                                .filter_map(|_| next_bird.next())
+                                .map(|(_, bird)| {
+                                    let (_, profile) = profile_bird_generation(bird, &settings);
+                                    profile.total_time(part, stage)
+                                })
+                                .sum()
+                        })
+                    });
+                    continue;
+                }
+                // every time the stage ran while building a bird, one bird per iteration
+                // (the setup builds the whole bird to get them, off the clock)
+                let mut next_bird = birds.iter().cycle();
+                group.bench_function(name, |b| {
+                    b.iter_batched(
+                        || {
+                            let (_, bird) = next_bird.next().expect("there's always another bird");
+                            profile_bird_stage_inputs(bird, &settings)
+                                .inputs
+                                .into_iter()
+                                .filter(|(p, s, _)| *p == part && *s == stage)
+                                .map(|(_, _, input)| input)
+                                .collect::<Vec<StageInput>>()
+                        },
+                        |inputs| inputs.into_iter().map(StageInput::run).collect::<Vec<_>>(),
+                        BatchSize::PerIteration,
+                    )
+                });
+            }
+        }
+        let export_options = ExportOptions {
+            mesh: settings,
+            ..Default::default()
+        };
+        let mut next_bird = birds.iter().cycle();
+        group.bench_function("get_stl", |b| {
+            b.iter_batched(
+                || next_bird.next().expect("there's always another bird").1,
+                |bird| bird.get_stl_with(&export_options),
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, pipeline_stages);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+// Building lots of birds at once
+// With the `parallel` feature on native targets every bird (and each bird's head and body)
+// gets built on rayon's thread pool, on wasm (or without the feature) it's one after the other
+use crate::{
+    AttachedBird, BirdGenInputs, BirdMeshSettings, BirdMeshes, CSGMesh,
+    generate_bird_body_csg_mesh_with, generate_bird_head_csg_mesh_with,
+};
+
+#[cfg(all(feature = "parallel", not(target_family = "wasm")))]
+use rayon::prelude::*;
+
+// Head and body for one bird, still in the generation frame
+pub struct BirdCSGMeshes {
+    pub head: CSGMesh,
+    pub body: CSGMesh,
+}
+
+impl BirdCSGMeshes {
+    // Exactly the bird that was asked for, `settings.neck_fix` doesn't come into it
+    // (clamping changes the inputs, so that's `AttachedBird::generate`)
+    pub fn generate(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Self {
+        let (head, body) = join(
+            || generate_bird_head_csg_mesh_with(input, settings),
+            || generate_bird_body_csg_mesh_with(input, settings),
+        );
+        BirdCSGMeshes { head, body }
+    }
+}
+
+// (neck fixed if the settings say so, each with the inputs it actually got built from)
+pub fn generate_bird_csg_batch(
+    inputs: &[BirdGenInputs],
+    settings: &BirdMeshSettings,
+) -> Vec<AttachedBird> {
+    map_birds(inputs, |input| AttachedBird::generate(input, settings))
+}
+
+// Same thing but display-ready bevy meshes
+pub fn generate_bird_batch(
+    inputs: &[BirdGenInputs],
+    settings: &BirdMeshSettings,
+) -> Vec<(BirdGenInputs, BirdMeshes)> {
+    map_birds(inputs, |input| {
+        let bird = AttachedBird::generate(input, settings);
+        (bird.inputs, BirdMeshes::from_csg(&bird.meshes))
+    })
+}
+
+// true if batches actually run in parallel on this build
+pub fn parallel_generation_enabled() -> bool {
+    cfg!(all(feature = "parallel", not(target_family = "wasm")))
+}
+
+#[cfg(all(feature = "parallel", not(target_family = "wasm")))]
+pub(crate) fn map_birds<I: Sync, T: Send>(
+    inputs: &[I],
+    build: impl Fn(&I) -> T + Send + Sync,
+) -> Vec<T> {
+    inputs.par_iter().map(build).collect()
+}
+
+#[cfg(not(all(feature = "parallel", not(target_family = "wasm"))))]
+pub(crate) fn map_birds<I: Sync, T: Send>(
+    inputs: &[I],
+    build: impl Fn(&I) -> T + Send + Sync,
+) -> Vec<T> {
+    inputs.iter().map(build).collect()
+}
+
+#[cfg(all(feature = "parallel", not(target_family = "wasm")))]
+pub(crate) fn join<A: Send, B: Send>(
+    a: impl FnOnce() -> A + Send,
+    b: impl FnOnce() -> B + Send,
+) -> (A, B) {
+    rayon::join(a, b)
+}
+
+#[cfg(not(all(feature = "parallel", not(target_family = "wasm"))))]
+pub(crate) fn join<A: Send, B: Send>(
+    a: impl FnOnce() -> A + Send,
+    b: impl FnOnce() -> B + Send,
+) -> (A, B) {
+    (a(), b())
+}
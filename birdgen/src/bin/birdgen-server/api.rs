@@ -0,0 +1,497 @@
+// The routes, kept away from the socket: a method, url and body go in, a reply comes out
+// Anything that builds a bird checks the request against the server's limits first, so one request
+// can't tie a thread up for minutes
+use birdgen::{
+    AttachedBird, BirdCSGMeshes, BirdExport, BirdGenInputs, BirdMeshSettings, BodyMode,
+    BreedingOptions, ExportOptions, FilamentOptions, GOOD_BIRDS, NeckFix, Precision,
+    PrintabilityOptions, RenderOptions, breed_birds, check_printability,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+// seeds are ~60 characters, anything much longer isn't one
+const MAX_SEED_LENGTH: usize = 256;
+const MESH_FORMATS: [&str; 4] = ["stl", "3mf", "obj", "png"];
+
+pub(crate) struct Limits {
+    pub(crate) max_precision: Precision,
+    pub(crate) max_scale: f64,
+    pub(crate) max_png_size: u32,
+    pub(crate) max_children: usize,
+}
+
+pub(crate) struct Reply {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: Vec<u8>,
+    // for meshes and images, so saving the response gets a sensible name
+    pub(crate) filename: Option<String>,
+}
+
+impl Reply {
+    fn json(value: &Value) -> Self {
+        Reply {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_vec_pretty(value).unwrap_or_default(),
+            filename: None,
+        }
+    }
+
+    fn file(content_type: &'static str, body: Vec<u8>, filename: String) -> Self {
+        Reply {
+            status: 200,
+            content_type,
+            body,
+            filename: Some(filename),
+        }
+    }
+
+    pub(crate) fn error(status: u16, message: &str) -> Self {
+        Reply {
+            status,
+            ..Reply::json(&json!({ "error": message }))
+        }
+    }
+}
+
+// status and message for whatever went wrong
+type Failed = (u16, String);
+
+fn bad_request(message: impl Into<String>) -> Failed {
+    (400, message.into())
+}
+
+pub(crate) fn handle(limits: &Limits, method: &str, url: &str, body: &[u8]) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = Query::parse(query);
+    let path = path.trim_end_matches('/');
+    let result = match (method, path) {
+        ("GET", "") => Ok(Reply::json(&index())),
+        ("GET", "/random") => random(limits, &query),
+        ("GET", "/good") => good(limits, &query),
+        ("POST", "/breed") => breed(limits, &query, body),
+        ("GET", path) if path.starts_with("/bird/") => bird(limits, &path[6..], &query),
+        (_, "" | "/random" | "/good" | "/breed") => {
+            Err((405, format!("{method} isn't allowed on {path}")))
+        }
+        _ if path.starts_with("/bird/") => Err((405, format!("{method} isn't allowed on {path}"))),
+        _ => Err((404, format!("nothing at {path}"))),
+    };
+    result.unwrap_or_else(|(status, message)| Reply::error(status, &message))
+}
+
+fn index() -> Value {
+    json!({
+        "name": "birdgen",
+        "version": env!("CARGO_PKG_VERSION"),
+        "routes": [
+            "GET /bird/{seed} (or .json, .stl, .3mf, .obj, .png)",
+            "GET /random",
+            "GET /good",
+            "POST /breed",
+        ],
+    })
+}
+
+// `?a=1&b=2`, no percent decoding (seeds, numbers and colours don't need any)
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        Query(
+            query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (key.to_string(), value.to_string())
+                })
+                .collect(),
+        )
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn parse_value<T: FromStr>(&self, key: &str) -> Result<Option<T>, Failed> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| bad_request(format!("{key}={value} isn't valid")))
+            })
+            .transpose()
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.get(key)
+            .is_some_and(|value| matches!(value, "" | "1" | "true" | "yes"))
+    }
+}
+
+// ?precision=low|medium|high&smooth&fix_neck=extend|clamp
+fn settings(limits: &Limits, query: &Query) -> Result<BirdMeshSettings, Failed> {
+    let rank = |precision: Precision| Precision::ALL.iter().position(|p| *p == precision);
+    let precision = match query.get("precision") {
+        None => {
+            // (medium, unless the server won't go that high)
+            if rank(Precision::Medium) > rank(limits.max_precision) {
+                limits.max_precision
+            } else {
+                Precision::Medium
+            }
+        }
+        Some(name) => {
+            let precision = Precision::ALL
+                .into_iter()
+                .find(|precision| format!("{precision:?}").eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    bad_request(format!("precision={name} isn't low, medium or high"))
+                })?;
+            if rank(precision) > rank(limits.max_precision) {
+                return Err(bad_request(format!(
+                    "precision={name} is over this server's limit ({:?})",
+                    limits.max_precision
+                )));
+            }
+            precision
+        }
+    };
+    let neck_fix = match query.get("fix_neck") {
+        None => None,
+        Some("extend") => Some(NeckFix::ExtendNeck),
+        Some("clamp") => Some(NeckFix::ClampOffsets),
+        Some(other) => {
+            return Err(bad_request(format!(
+                "fix_neck={other} isn't extend or clamp"
+            )));
+        }
+    };
+    Ok(BirdMeshSettings {
+        body_mode: if query.flag("smooth") {
+            BodyMode::smooth()
+        } else {
+            BodyMode::Hull
+        },
+        precision,
+        neck_fix,
+    })
+}
+
+// The mesh settings the request asked for, to go on the file links so they build the same bird
+fn mesh_query(query: &Query) -> String {
+    let pairs: Vec<String> = ["precision", "smooth", "fix_neck"]
+        .into_iter()
+        .filter_map(|key| match query.get(key)? {
+            "" => Some(key.to_string()),
+            value => Some(format!("{key}={value}")),
+        })
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", pairs.join("&"))
+    }
+}
+
+fn parse_seed(seed: &str) -> Result<BirdGenInputs, Failed> {
+    if seed.len() > MAX_SEED_LENGTH {
+        return Err(bad_request("that's too long to be a seed"));
+    }
+    // (the parser skips anything it doesn't recognise, which would turn junk into the default bird)
+    let seed = seed.trim();
+    if !matches!(seed.split('.').next(), Some("m" | "h" | "b" | "t" | "c")) {
+        return Err(bad_request(format!(
+            "{seed} doesn't look like a seed (they start m., h., b., t. or c.)"
+        )));
+    }
+    let mut bird = BirdGenInputs::default();
+    bird.update_from_seed_string(seed.to_string())
+        .map_err(|e| bad_request(format!("bad seed: {e}")))?;
+    Ok(bird)
+}
+
+// Everything there is to know about a bird without downloading it, `links` goes on the end of
+// every file link (see mesh_query)
+fn bird_json(bird: &BirdGenInputs, meshes: &BirdCSGMeshes, links: &str) -> Value {
+    let seed = bird.get_bird_seed_string();
+    let metrics = meshes.metrics(&FilamentOptions::default());
+    let printability = check_printability(bird, meshes, &PrintabilityOptions::default());
+    let (min, max) = (metrics.bounds.min, metrics.bounds.max);
+    json!({
+        "seed": seed,
+        "metrics": {
+            "volume_mm3": metrics.volume,
+            "surface_area_mm2": metrics.surface_area,
+            "bounds": {
+                "min": [min.x, min.y, min.z],
+                "max": [max.x, max.y, max.z],
+            },
+            "filament": {
+                "material_volume_mm3": metrics.filament.material_volume,
+                "mass_g": metrics.filament.mass_grams,
+                "length_mm": metrics.filament.length_mm,
+            },
+        },
+        "printability": {
+            "printable": printability.is_printable(),
+            "shells": printability.shells,
+            "head_attached": printability.head_attached,
+            "stability_margin_mm": printability.stability_margin,
+            "overhang_fraction": printability.overhang_fraction,
+            "warnings": printability
+                .problems
+                .iter()
+                .map(|problem| problem.describe())
+                .collect::<Vec<_>>(),
+        },
+        "files": MESH_FORMATS
+            .iter()
+            .map(|format| {
+                let link = format!("/bird/{seed}.{format}{links}");
+                (format.to_string(), json!(link))
+            })
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+// Builds the bird first, with whatever neck fix was asked for (clamping moves the head, so the
+// seed and file links have to be for the bird that actually got built, not the one in the request)
+// Only whole numbers go in, or the seed handed back would build a slightly different bird
+fn built_bird_json(bird: &BirdGenInputs, settings: &BirdMeshSettings, links: &str) -> Value {
+    let built = AttachedBird::generate(&bird.quantised(), settings);
+    bird_json(&built.inputs, &built.meshes, links)
+}
+
+// GET /bird/{seed}.{stl,3mf,obj,png}, or the metadata without an extension (or with .json)
+fn bird(limits: &Limits, name: &str, query: &Query) -> Result<Reply, Failed> {
+    let (seed, format) = match name.rsplit_once('.') {
+        Some((seed, format)) if MESH_FORMATS.contains(&format) || format == "json" => {
+            (seed, format)
+        }
+        _ => (name, "json"),
+    };
+    let bird = parse_seed(seed)?;
+    let settings = settings(limits, query)?;
+    if format == "json" {
+        return Ok(Reply::json(&built_bird_json(
+            &bird,
+            &settings,
+            &mesh_query(query),
+        )));
+    }
+
+    let scale = query.parse_value::<f64>("scale")?.unwrap_or(1.0);
+    if !(scale > 0.0 && scale <= limits.max_scale) {
+        return Err(bad_request(format!(
+            "scale={scale} should be over 0 and at most {}",
+            limits.max_scale
+        )));
+    }
+    let render = match format {
+        "png" => Some(render_options(limits, query)?),
+        _ => None,
+    };
+    let export = BirdExport::new(
+        &bird,
+        &ExportOptions {
+            mesh: settings,
+            ..Default::default()
+        },
+    )
+    .map_err(|e| (500, e))?
+    .scaled(scale);
+    let name = format!("coolbird-{}", export.inputs.get_bird_seed_string());
+    let filename = format!("{name}.{format}");
+    Ok(match format {
+        "stl" => Reply::file("model/stl", export.to_stl(&name), filename),
+        "3mf" => Reply::file(
+            "model/3mf",
+            export.to_3mf(&name).map_err(|e| (500, e))?,
+            filename,
+        ),
+        "obj" => Reply::file("model/obj", export.to_obj(&name), filename),
+        _ => {
+            let png = export
+                .render(&render.unwrap_or_default())
+                .to_png()
+                .map_err(|e| (500, e))?;
+            Reply::file("image/png", png, filename)
+        }
+    })
+}
+
+// ?size=512&color=f7b32b&transparent
+fn render_options(limits: &Limits, query: &Query) -> Result<RenderOptions, Failed> {
+    let size = query.parse_value::<u32>("size")?.unwrap_or(512);
+    if size == 0 || size > limits.max_png_size {
+        return Err(bad_request(format!(
+            "size={size} should be 1 to {}",
+            limits.max_png_size
+        )));
+    }
+    let defaults = RenderOptions::default();
+    let color = match query.get("color") {
+        None => defaults.color,
+        Some(hex) => {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => [r, g, b],
+                _ => return Err(bad_request(format!("color={hex} isn't rrggbb hex"))),
+            }
+        }
+    };
+    let [r, g, b, _] = defaults.background;
+    Ok(RenderOptions {
+        width: size,
+        height: size,
+        color,
+        background: [r, g, b, if query.flag("transparent") { 0 } else { 255 }],
+        ..defaults
+    })
+}
+
+fn rng_seed(query: &Query) -> Result<u64, Failed> {
+    Ok(query
+        .parse_value::<u64>("rng_seed")?
+        .unwrap_or_else(rand::random))
+}
+
+// GET /random?rng_seed=7
+fn random(limits: &Limits, query: &Query) -> Result<Reply, Failed> {
+    let rng_seed = rng_seed(query)?;
+    let settings = settings(limits, query)?;
+    let mut bird = BirdGenInputs::default();
+    bird.randomize_values_with_rng(&mut StdRng::seed_from_u64(rng_seed));
+    let mut reply = built_bird_json(&bird, &settings, &mesh_query(query));
+    reply["rng_seed"] = json!(rng_seed);
+    Ok(Reply::json(&reply))
+}
+
+// GET /good?index=3, or ?rng_seed=7 to pick one
+fn good(limits: &Limits, query: &Query) -> Result<Reply, Failed> {
+    let settings = settings(limits, query)?;
+    let (bird, rng_seed) = match query.parse_value::<usize>("index")? {
+        Some(index) => {
+            let seed = GOOD_BIRDS.get(index).ok_or_else(|| {
+                bad_request(format!(
+                    "index={index} is past the last good bird ({})",
+                    GOOD_BIRDS.len() - 1
+                ))
+            })?;
+            (parse_seed(seed)?, None)
+        }
+        None => {
+            let rng_seed = rng_seed(query)?;
+            let bird =
+                BirdGenInputs::get_a_good_bird_with_rng(&mut StdRng::seed_from_u64(rng_seed));
+            (bird, Some(rng_seed))
+        }
+    };
+    let mut reply = built_bird_json(&bird, &settings, &mesh_query(query));
+    reply["rng_seed"] = json!(rng_seed);
+    Ok(Reply::json(&reply))
+}
+
+// POST /breed {"parents": [seed] or [seed, seed], "rng_seed": 7, "count": 4, "printable": true}
+// One parent gets a mate bred towards it like the app does, two get a child of their own
+fn breed(limits: &Limits, query: &Query, body: &[u8]) -> Result<Reply, Failed> {
+    let request: Value =
+        serde_json::from_slice(body).map_err(|e| bad_request(format!("body isn't JSON: {e}")))?;
+    let parents = request["parents"]
+        .as_array()
+        .filter(|parents| (1..=2).contains(&parents.len()))
+        .ok_or_else(|| bad_request("parents should be a list of one or two seeds"))?
+        .iter()
+        .map(|parent| {
+            parent
+                .as_str()
+                .ok_or_else(|| bad_request("parents should be seed strings"))
+                .and_then(parse_seed)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let rng_seed = match &request["rng_seed"] {
+        Value::Null => rand::random(),
+        value => value
+            .as_u64()
+            .ok_or_else(|| bad_request("rng_seed should be a whole number"))?,
+    };
+    let count = match &request["count"] {
+        Value::Null => 1,
+        value => value
+            .as_u64()
+            .ok_or_else(|| bad_request("count should be a whole number"))?
+            as usize,
+    };
+    if count == 0 || count > limits.max_children {
+        return Err(bad_request(format!(
+            "count={count} should be 1 to {}",
+            limits.max_children
+        )));
+    }
+    let printable = request["printable"].as_bool().unwrap_or(false);
+    let settings = settings(limits, query)?;
+    let links = mesh_query(query);
+
+    // every child gets its own rng seed, same as a batch
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    let child_seeds: Vec<u64> = (0..count).map(|_| rng.random()).collect();
+    let children: Vec<Value> = match parents[..] {
+        [parent] => {
+            let options = BreedingOptions {
+                printability: printable.then(PrintabilityOptions::default),
+                ..Default::default()
+            };
+            breed_birds(&parent, &child_seeds, &options, &settings)
+                .into_iter()
+                .zip(&child_seeds)
+                .map(|(child, child_seed)| {
+                    let mut json = bird_json(&child.inputs, &child.meshes, &links);
+                    json["rng_seed"] = json!(child_seed);
+                    json["mate"] = json!(child.mate.get_bird_seed_string());
+                    json["attempts"] = json!(child.attempts);
+                    json
+                })
+                .collect()
+        }
+        _ => child_seeds
+            .iter()
+            .map(|child_seed| {
+                let mut rng = StdRng::seed_from_u64(*child_seed);
+                let mut json = if printable {
+                    let child = parents[0].get_printable_child_with_rng(
+                        &parents[1],
+                        &settings,
+                        &PrintabilityOptions::default(),
+                        &mut rng,
+                    );
+                    let mut json = bird_json(&child.inputs, &child.meshes, &links);
+                    json["attempts"] = json!(child.attempts);
+                    json
+                } else {
+                    let child = parents[0].get_child_with_rng(&parents[1], &mut rng);
+                    built_bird_json(&child, &settings, &links)
+                };
+                json["rng_seed"] = json!(child_seed);
+                json
+            })
+            .collect(),
+    };
+    Ok(Reply::json(&json!({
+        "parents": parents
+            .iter()
+            .map(|parent| parent.get_bird_seed_string())
+            .collect::<Vec<_>>(),
+        "rng_seed": rng_seed,
+        "children": children,
+    })))
+}
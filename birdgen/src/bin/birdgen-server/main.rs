@@ -0,0 +1,175 @@
+// Birds over HTTP, for tools that want them on demand without linking Rust
+// e.g. `birdgen-server --port 8080`, then `curl localhost:8080/bird/<seed>.stl -o bird.stl`
+use birdgen::Precision;
+use clap::{Parser, ValueEnum};
+use std::{
+    io::{self, Read, Write},
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Arc,
+    thread,
+    time::Instant,
+};
+use tiny_http::{Header, Request, Response, Server};
+
+mod api;
+use api::{Limits, Reply, handle};
+
+// request bodies are a few seeds at most
+const MAX_BODY: usize = 16 * 1024;
+
+#[derive(Parser)]
+#[command(
+    name = "birdgen-server",
+    version,
+    about = "Serve cool birds over HTTP: GET /bird/{seed}.stl|.3mf|.obj|.png, POST /breed, GET /random, GET /good"
+)]
+struct Args {
+    #[arg(
+        long,
+        default_value = "127.0.0.1",
+        help = "Address to listen on (0.0.0.0 for everyone)"
+    )]
+    host: String,
+    #[arg(
+        short,
+        long,
+        default_value_t = 8080,
+        help = "Port to listen on (0 picks a free one)"
+    )]
+    port: u16,
+    #[arg(long, help = "Requests handled at once [default: one per core]")]
+    threads: Option<usize>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "medium",
+        help = "Finest precision anyone can ask for"
+    )]
+    max_precision: PrecisionArg,
+    #[arg(
+        long,
+        default_value_t = 10.0,
+        help = "Biggest scale anyone can ask for"
+    )]
+    max_scale: f64,
+    #[arg(
+        long,
+        default_value_t = 1024,
+        help = "Biggest PNG anyone can ask for (px)"
+    )]
+    max_png_size: u32,
+    #[arg(
+        long,
+        default_value_t = 8,
+        help = "Most children one /breed can ask for"
+    )]
+    max_children: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PrecisionArg {
+    Low,
+    Medium,
+    High,
+}
+
+impl Args {
+    fn limits(&self) -> Limits {
+        Limits {
+            max_precision: match self.max_precision {
+                PrecisionArg::Low => Precision::Low,
+                PrecisionArg::Medium => Precision::Medium,
+                PrecisionArg::High => Precision::High,
+            },
+            max_scale: self.max_scale,
+            max_png_size: self.max_png_size,
+            max_children: self.max_children.max(1),
+        }
+    }
+}
+
+fn main() {
+    if let Err(e) = serve(&Args::parse()) {
+        eprintln!("birdgen-server: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn serve(args: &Args) -> Result<(), String> {
+    let server = Server::http((args.host.as_str(), args.port))
+        .map_err(|e| format!("couldn't listen on {}:{}: {e}", args.host, args.port))?;
+    let address = server
+        .server_addr()
+        .to_ip()
+        .ok_or("not listening on an IP address")?;
+    // (first line on stdout, so whatever started us can find the port when it asked for 0)
+    println!("listening on http://{address}");
+    let _ = io::stdout().flush();
+
+    let server = Arc::new(server);
+    let limits = Arc::new(args.limits());
+    let threads = args
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, |cores| cores.get()))
+        .max(1);
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let (server, limits) = (server.clone(), limits.clone());
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    respond(&limits, request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+fn respond(limits: &Limits, mut request: Request) {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let mut body = vec![];
+    let reply = if request
+        .body_length()
+        .is_some_and(|length| length > MAX_BODY)
+    {
+        Reply::error(413, "request body is too big")
+    } else if let Err(e) = request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)
+    {
+        Reply::error(400, &format!("couldn't read the request body: {e}"))
+    } else if body.len() > MAX_BODY {
+        Reply::error(413, "request body is too big")
+    } else {
+        // a bird that trips up the CSG shouldn't take the thread down with it
+        catch_unwind(AssertUnwindSafe(|| handle(limits, &method, &url, &body)))
+            .unwrap_or_else(|_| Reply::error(500, "building that bird fell over"))
+    };
+    eprintln!(
+        "{method} {url} {} ({} bytes, {:.0?})",
+        reply.status,
+        reply.body.len(),
+        started.elapsed()
+    );
+
+    let mut response = Response::from_data(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header("Content-Type", reply.content_type));
+    if let Some(filename) = &reply.filename {
+        response = response.with_header(header(
+            "Content-Disposition",
+            &format!("inline; filename=\"{filename}\""),
+        ));
+    }
+    let _ = request.respond(response);
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("headers are ascii")
+}
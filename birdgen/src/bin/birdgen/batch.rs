@@ -0,0 +1,367 @@
+// `birdgen batch`: breed a whole drop of birds off one parent into a folder
+// Every bird gets a mesh, a .seed file and a row in the manifest so the lot can be sorted in a spreadsheet
+use crate::{FilamentArgs, MeshArgs, MeshFormat, RenderArgs, parse_png_size, read_seed_file};
+use birdgen::{
+    BirdExport, BirdGenInputTypes, BirdGenInputs, BirdMetrics, BredBird, BreedingOptions,
+    PrintabilityOptions, PrintabilityReport, breed_birds, check_printability,
+};
+use clap::{Args, ValueEnum};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde_json::json;
+use std::{fs, path::PathBuf};
+
+// how many birds are bred (and held in memory) at once
+const CHUNK_SIZE: usize = 16;
+
+#[derive(Args)]
+pub(crate) struct BatchArgs {
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 100,
+        help = "How many birds to breed"
+    )]
+    count: usize,
+    #[arg(long, help = "Parent bird seed [default: one of the known good birds]")]
+    parent: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "parent",
+        help = "Read the parent seed from a file"
+    )]
+    parent_file: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Seed for the whole batch, so the same birds come out every time"
+    )]
+    rng_seed: Option<u64>,
+    #[arg(
+        short,
+        long,
+        default_value = "birds",
+        help = "Folder to write the birds into"
+    )]
+    out: PathBuf,
+    #[arg(long, help = "Only keep children that pass the printability checks")]
+    printable: bool,
+    #[arg(
+        long,
+        default_value_t = 6,
+        help = "How many tries each printable child gets"
+    )]
+    attempts: usize,
+    #[arg(
+        long,
+        default_value_t = 0.12,
+        help = "Least each mate gets pulled towards the parent (0-1)"
+    )]
+    min_mate_lerp: f32,
+    #[arg(
+        long,
+        default_value_t = 0.82,
+        help = "Most each mate gets pulled towards the parent (0-1)"
+    )]
+    max_mate_lerp: f32,
+    #[arg(long, value_enum, default_value = "both")]
+    manifest: ManifestFormat,
+    #[arg(long, help = "Render a PNG thumbnail of every bird too")]
+    thumbnails: bool,
+    #[arg(
+        long,
+        default_value_t = 256,
+        value_parser = parse_png_size,
+        help = "Thumbnail width and height (px, up to 8192)"
+    )]
+    png_size: u32,
+    #[command(flatten)]
+    mesh: MeshArgs,
+    #[command(flatten)]
+    filament: FilamentArgs,
+    #[command(flatten)]
+    render: RenderArgs,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ManifestFormat {
+    Csv,
+    Json,
+    Both,
+}
+
+// Everything the manifest needs about one bird, the meshes are already on disk by now
+struct BatchBird {
+    index: usize,
+    rng_seed: u64,
+    inputs: BirdGenInputs,
+    mesh_file: String,
+    seed_file: String,
+    thumbnail: Option<String>,
+    metrics: BirdMetrics,
+    printability: PrintabilityReport,
+    attempts: usize,
+}
+
+impl BatchBird {
+    fn warnings(&self) -> Vec<String> {
+        self.printability
+            .problems
+            .iter()
+            .map(|problem| problem.describe())
+            .collect()
+    }
+}
+
+pub(crate) fn run_batch(args: &BatchArgs) -> Result<(), String> {
+    let rng_seed = args.rng_seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    let parent = pick_parent(args, &mut rng)?;
+    let parent_seed = parent.get_bird_seed_string();
+    let format = args.mesh.format.unwrap_or(MeshFormat::Stl);
+    let settings = args.mesh.settings();
+    let printability = PrintabilityOptions {
+        max_attempts: args.attempts.max(1),
+        ..args.mesh.printability()
+    };
+    let options = BreedingOptions {
+        min_mate_lerp: args.min_mate_lerp.clamp(0.0, 1.0),
+        max_mate_lerp: args.max_mate_lerp.clamp(0.0, 1.0),
+        printability: args.printable.then_some(printability),
+    };
+    let child_seeds: Vec<u64> = (0..args.count).map(|_| rng.random()).collect();
+
+    fs::create_dir_all(&args.out)
+        .map_err(|e| format!("couldn't create {}: {e}", args.out.display()))?;
+    eprintln!(
+        "breeding {} birds off {parent_seed} (rng seed {rng_seed}) into {}",
+        args.count,
+        args.out.display()
+    );
+
+    let mut birds = Vec::with_capacity(args.count);
+    for (chunk_index, chunk) in child_seeds.chunks(CHUNK_SIZE).enumerate() {
+        let bred = breed_birds(&parent, chunk, &options, &settings);
+        for (offset, (bird, child_seed)) in bred.into_iter().zip(chunk).enumerate() {
+            let index = chunk_index * CHUNK_SIZE + offset + 1;
+            birds.push(write_bird(
+                args,
+                format,
+                &printability,
+                index,
+                *child_seed,
+                bird,
+            )?);
+        }
+        eprintln!("  {}/{}", birds.len(), args.count);
+    }
+
+    let settings_json = json!({
+        "format": format.extension(),
+        "precision": format!("{:?}", settings.precision).to_lowercase(),
+        "smooth": args.mesh.build.smooth,
+        "scale": args.mesh.scale,
+        "neck_fix": settings.neck_fix.map(|fix| format!("{fix:?}")),
+        "printable": args.printable,
+        "min_mate_lerp": options.min_mate_lerp,
+        "max_mate_lerp": options.max_mate_lerp,
+    });
+    if matches!(args.manifest, ManifestFormat::Csv | ManifestFormat::Both) {
+        let path = args.out.join("manifest.csv");
+        fs::write(&path, manifest_csv(&birds))
+            .map_err(|e| format!("couldn't write {}: {e}", path.display()))?;
+    }
+    if matches!(args.manifest, ManifestFormat::Json | ManifestFormat::Both) {
+        let path = args.out.join("manifest.json");
+        let manifest = json!({
+            "parent": parent_seed,
+            "rng_seed": rng_seed,
+            "settings": settings_json,
+            "birds": birds.iter().map(bird_json).collect::<Vec<_>>(),
+        });
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(&manifest).unwrap_or_default(),
+        )
+        .map_err(|e| format!("couldn't write {}: {e}", path.display()))?;
+    }
+    let printable = birds
+        .iter()
+        .filter(|bird| bird.printability.is_printable())
+        .count();
+    eprintln!(
+        "done, {printable}/{} printable, wrote {}",
+        birds.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+fn pick_parent(args: &BatchArgs, rng: &mut StdRng) -> Result<BirdGenInputs, String> {
+    let seed = match (&args.parent, &args.parent_file) {
+        (Some(seed), _) => seed.clone(),
+        (None, Some(path)) => read_seed_file(path)?,
+        (None, None) => return Ok(BirdGenInputs::get_a_good_bird_with_rng(rng)),
+    };
+    let mut parent = BirdGenInputs::default();
+    parent.update_from_seed_string(seed.trim().to_string())?;
+    Ok(parent)
+}
+
+fn write_bird(
+    args: &BatchArgs,
+    format: MeshFormat,
+    printability: &PrintabilityOptions,
+    index: usize,
+    rng_seed: u64,
+    bird: BredBird,
+) -> Result<BatchBird, String> {
+    // printable batches already checked every child, otherwise check here so the manifest has it anyway
+    let report = match bird.printability {
+        Some(report) => report,
+        None => check_printability(&bird.inputs, &bird.meshes, printability),
+    };
+    // (breeding hands back the inputs it built, so a clamped neck's seed is the one that matches the mesh)
+    let export = BirdExport::from_meshes(bird.inputs, bird.meshes).scaled(args.mesh.scale);
+    let metrics = export.metrics(&args.filament.options());
+    let seed = export.inputs.get_bird_seed_string();
+
+    let name = format!("bird-{index:04}");
+    let mesh_file = format!("{name}.{}", format.extension());
+    let seed_file = format!("{name}.seed");
+    let mesh_path = args.out.join(&mesh_file);
+    fs::write(&mesh_path, format.write(&export, &name)?)
+        .map_err(|e| format!("couldn't write {}: {e}", mesh_path.display()))?;
+    let seed_path = args.out.join(&seed_file);
+    fs::write(
+        &seed_path,
+        format!("# {name}, rng seed {rng_seed}\n{seed}\n"),
+    )
+    .map_err(|e| format!("couldn't write {}: {e}", seed_path.display()))?;
+    let thumbnail = if args.thumbnails {
+        let thumbnail = format!("{name}.png");
+        let png_path = args.out.join(&thumbnail);
+        fs::write(
+            &png_path,
+            export
+                .render(&args.render.options(args.png_size))
+                .to_png()?,
+        )
+        .map_err(|e| format!("couldn't write {}: {e}", png_path.display()))?;
+        Some(thumbnail)
+    } else {
+        None
+    };
+
+    Ok(BatchBird {
+        index,
+        rng_seed,
+        inputs: export.inputs,
+        mesh_file,
+        seed_file,
+        thumbnail,
+        metrics,
+        printability: report,
+        attempts: bird.attempts,
+    })
+}
+
+fn manifest_csv(birds: &[BatchBird]) -> String {
+    let mut header: Vec<&str> = vec![
+        "index",
+        "mesh",
+        "seed_file",
+        "thumbnail",
+        "seed",
+        "rng_seed",
+    ];
+    header.extend(BirdGenInputTypes::ALL.iter().map(|input| input.name()));
+    header.extend([
+        "volume_mm3",
+        "surface_area_mm2",
+        "size_x_mm",
+        "size_y_mm",
+        "size_z_mm",
+        "mass_g",
+        "filament_m",
+        "printable",
+        "attempts",
+        "warnings",
+    ]);
+    let mut lines = vec![header.join(",")];
+    for bird in birds {
+        let metrics = &bird.metrics;
+        let size = metrics.bounds.size();
+        let mut row = vec![
+            bird.index.to_string(),
+            bird.mesh_file.clone(),
+            bird.seed_file.clone(),
+            bird.thumbnail.clone().unwrap_or_default(),
+            bird.inputs.get_bird_seed_string(),
+            bird.rng_seed.to_string(),
+        ];
+        row.extend(
+            BirdGenInputTypes::ALL
+                .iter()
+                .map(|input| format!("{:.2}", bird.inputs.get_input_value_for_type(input))),
+        );
+        row.extend([
+            format!("{:.1}", metrics.volume),
+            format!("{:.1}", metrics.surface_area),
+            format!("{:.1}", size.x),
+            format!("{:.1}", size.y),
+            format!("{:.1}", size.z),
+            format!("{:.1}", metrics.filament.mass_grams),
+            format!("{:.2}", metrics.filament.length_mm / 1000.0),
+            bird.printability.is_printable().to_string(),
+            bird.attempts.to_string(),
+            // (the only field that can have commas or quotes in it)
+            format!("\"{}\"", bird.warnings().join("; ").replace('"', "\"\"")),
+        ]);
+        lines.push(row.join(","));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn bird_json(bird: &BatchBird) -> serde_json::Value {
+    let metrics = &bird.metrics;
+    let (min, max) = (metrics.bounds.min, metrics.bounds.max);
+    let inputs: serde_json::Map<String, serde_json::Value> = BirdGenInputTypes::ALL
+        .iter()
+        .map(|input| {
+            (
+                input.name().to_string(),
+                json!(bird.inputs.get_input_value_for_type(input)),
+            )
+        })
+        .collect();
+    json!({
+        "index": bird.index,
+        "mesh": bird.mesh_file,
+        "seed_file": bird.seed_file,
+        "thumbnail": bird.thumbnail,
+        "seed": bird.inputs.get_bird_seed_string(),
+        "rng_seed": bird.rng_seed,
+        "inputs": inputs,
+        "metrics": {
+            "volume_mm3": metrics.volume,
+            "surface_area_mm2": metrics.surface_area,
+            "bounds": {
+                "min": [min.x, min.y, min.z],
+                "max": [max.x, max.y, max.z],
+            },
+            "filament": {
+                "material_volume_mm3": metrics.filament.material_volume,
+                "mass_g": metrics.filament.mass_grams,
+                "length_mm": metrics.filament.length_mm,
+            },
+        },
+        "printability": {
+            "printable": bird.printability.is_printable(),
+            "shells": bird.printability.shells,
+            "head_attached": bird.printability.head_attached,
+            "stability_margin_mm": bird.printability.stability_margin,
+            "overhang_fraction": bird.printability.overhang_fraction,
+        },
+        "attempts": bird.attempts,
+        "warnings": bird.warnings(),
+    })
+}
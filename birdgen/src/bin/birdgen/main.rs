@@ -0,0 +1,963 @@
+// Headless bird generation: seed in, mesh out, no window (or GPU) involved
+// e.g. `birdgen --good --rng-seed 7 -o bird.3mf --json`
+use bevy::math::DVec2;
+use birdgen::{
+    BirdCSGMeshes, BirdExport, BirdGenInputs, BirdMeshSettings, BirdMetrics, BirdParts, BodyMode,
+    BrickOptions, ExportOptions, FilamentOptions, GOOD_BIRDS, HangerOptions, HollowOptions,
+    HollowReport, NeckFix, NeckReport, PapercraftOptions, Precision, PrintabilityOptions,
+    PrintabilityReport, RenderOptions, SilhouetteOptions, SliceOptions, SplitBird, SplitOptions,
+    SplitPlane, View, VoxelMaterial, VoxelOptions, check_printability,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use serde_json::json;
+
+mod batch;
+mod sweep;
+use batch::{BatchArgs, run_batch};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use sweep::{SweepArgs, run_sweep};
+
+// biggest PNG (or contact sheet cell) anyone can ask for, each pixel's rendered a few times over
+// so much past this and it's out of memory long before it's done
+const MAX_PNG_SIZE: u32 = 8192;
+
+#[derive(Parser)]
+#[command(
+    name = "birdgen",
+    version,
+    about = "Generate cool birds from the command line",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    bird: BirdArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Breed a batch of birds into a folder, with a CSV/JSON manifest")]
+    Batch(BatchArgs),
+    #[command(about = "Sweep one or two inputs across their ranges into a contact sheet")]
+    Sweep(SweepArgs),
+}
+
+#[derive(Args)]
+struct BirdArgs {
+    #[arg(
+        help = "Bird seed string, e.g. m.22.67.4.190.h.26.24.17.-7.26.33.36.b.43.21.55.13.35.t.36.15.-15.15.82.c.92"
+    )]
+    seed: Option<String>,
+    #[arg(
+        long,
+        help = "Read the seed from a file (first line that isn't blank or a # comment)"
+    )]
+    file: Option<PathBuf>,
+    #[arg(long, help = "Completely random bird")]
+    random: bool,
+    #[arg(long, help = "One of the known good birds")]
+    good: bool,
+    #[arg(
+        long,
+        help = "Seed for --random/--good, so the same bird comes out every time"
+    )]
+    rng_seed: Option<u64>,
+    #[arg(
+        short,
+        long,
+        help = "Where to write the mesh ('-' for stdout) [default: coolbird-<seed>.<format>]"
+    )]
+    output: Option<PathBuf>,
+    #[arg(long, help = "Only print the report, don't write a mesh")]
+    no_mesh: bool,
+    #[arg(long, help = "Print the report as JSON")]
+    json: bool,
+    #[arg(long, help = "Also render the bird to a PNG")]
+    png: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = 512,
+        value_parser = parse_png_size,
+        help = "PNG width and height (px, up to 8192)"
+    )]
+    png_size: u32,
+    #[arg(long, help = "Also write the bird's outline to an SVG, at real size")]
+    svg: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "side",
+        help = "Which way the SVG looks at the bird"
+    )]
+    view: ViewArg,
+    #[arg(long, help = "Add a line to the SVG where the head overlaps the body")]
+    detail_lines: bool,
+    #[command(flatten)]
+    mesh: MeshArgs,
+    #[command(flatten)]
+    export: ExportArgs,
+    #[command(flatten)]
+    filament: FilamentArgs,
+    #[command(flatten)]
+    render: RenderArgs,
+    #[command(flatten)]
+    slices: SliceArgs,
+    #[command(flatten)]
+    papercraft: PapercraftArgs,
+    #[command(flatten)]
+    voxels: VoxelArgs,
+    #[command(flatten)]
+    bricks: BrickArgs,
+}
+
+// how the mesh gets written, shared by single birds and batches
+#[derive(Args)]
+struct MeshArgs {
+    #[arg(
+        short,
+        long,
+        value_enum,
+        help = "Mesh format [default: from the output extension, or stl]"
+    )]
+    format: Option<MeshFormat>,
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        help = "Scale factor (1.0 is the bird's natural size in mm)"
+    )]
+    scale: f64,
+    #[command(flatten)]
+    build: BuildArgs,
+}
+
+// how the bird gets built, for anything that generates birds
+#[derive(Args)]
+struct BuildArgs {
+    #[arg(long, value_enum, default_value = "medium")]
+    precision: PrecisionArg,
+    #[arg(long, help = "Smooth blended body instead of hulls")]
+    smooth: bool,
+    #[arg(long, value_enum, help = "Stick floating heads back on")]
+    fix_neck: Option<NeckFixArg>,
+}
+
+impl BuildArgs {
+    fn settings(&self) -> BirdMeshSettings {
+        BirdMeshSettings {
+            body_mode: if self.smooth {
+                BodyMode::smooth()
+            } else {
+                BodyMode::Hull
+            },
+            precision: match self.precision {
+                PrecisionArg::Low => Precision::Low,
+                PrecisionArg::Medium => Precision::Medium,
+                PrecisionArg::High => Precision::High,
+            },
+            neck_fix: self.fix_neck.map(|fix| match fix {
+                NeckFixArg::Extend => NeckFix::ExtendNeck,
+                NeckFixArg::Clamp => NeckFix::ClampOffsets,
+            }),
+        }
+    }
+}
+
+impl MeshArgs {
+    fn settings(&self) -> BirdMeshSettings {
+        self.build.settings()
+    }
+
+    // checks run at natural size, so hold features (and the base margin) to whatever they'll be after scaling
+    fn printability(&self) -> PrintabilityOptions {
+        let defaults = PrintabilityOptions::default();
+        let unscaled = |mm: f32| mm / self.scale as f32;
+        PrintabilityOptions {
+            min_feature_size: defaults.min_feature_size.map(unscaled),
+            min_stability_margin: defaults.min_stability_margin.map(unscaled),
+            ..defaults
+        }
+    }
+}
+
+// extras bolted onto a single bird's mesh
+#[derive(Args)]
+struct ExportArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "Add a loop (or a hole) to hang the bird from"
+    )]
+    hanger: Option<HangerArg>,
+    #[arg(
+        long,
+        default_value_t = 4.0,
+        help = "Inner diameter of the loop, or diameter of the tail hole (mm)"
+    )]
+    hanger_hole: f32,
+    #[arg(long, help = "Hollow out the body for resin printing")]
+    hollow: bool,
+    #[arg(
+        long,
+        default_value_t = 2.0,
+        help = "Wall thickness to leave when hollowing (mm, at natural size)"
+    )]
+    wall: f32,
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Drain holes through the floor of a hollow bird"
+    )]
+    drain_holes: usize,
+    #[arg(
+        long,
+        value_enum,
+        help = "Cut the bird in half to print flat, with pegs to join the halves (stl writes a file per piece, 3mf one file with every piece)"
+    )]
+    split: Option<SplitArg>,
+}
+
+impl ExportArgs {
+    fn options(&self, mesh: BirdMeshSettings) -> ExportOptions {
+        ExportOptions {
+            mesh,
+            hanger: self.hanger.map(|hanger| HangerOptions {
+                hole_diameter: self.hanger_hole,
+                ..match hanger {
+                    HangerArg::HeadLoop => HangerOptions::head_loop(),
+                    HangerArg::BackLoop => HangerOptions::back_loop(),
+                    HangerArg::TailHole => HangerOptions::tail_hole(),
+                }
+            }),
+            hollow: self.hollow.then(|| HollowOptions {
+                wall_thickness: self.wall,
+                drain_holes: self.drain_holes,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn split_options(&self) -> Option<SplitOptions> {
+        self.split.map(|split| SplitOptions {
+            plane: match split {
+                SplitArg::Sagittal => SplitPlane::Sagittal,
+                SplitArg::Coronal => SplitPlane::Coronal,
+                SplitArg::Transverse => SplitPlane::Transverse,
+            },
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Args)]
+struct FilamentArgs {
+    #[arg(long, default_value_t = 1.24, help = "Filament density (g/cm^3)")]
+    density: f32,
+    #[arg(long, default_value_t = 1.75, help = "Filament diameter (mm)")]
+    diameter: f32,
+    #[arg(long, default_value_t = 20.0, help = "Infill percentage")]
+    infill: f32,
+}
+
+// the look of rendered PNGs (how big they are is up to whoever's rendering)
+#[derive(Args)]
+struct RenderArgs {
+    #[arg(
+        long,
+        value_parser = parse_color,
+        default_value = "#f7b32b",
+        help = "Bird colour for PNGs and voxels"
+    )]
+    color: [u8; 3],
+    #[arg(long, help = "Transparent PNG background")]
+    transparent: bool,
+}
+
+impl RenderArgs {
+    fn options(&self, size: u32) -> RenderOptions {
+        let defaults = RenderOptions::default();
+        let [r, g, b, _] = defaults.background;
+        RenderOptions {
+            width: size,
+            height: size,
+            color: self.color,
+            background: [r, g, b, if self.transparent { 0 } else { 255 }],
+            ..defaults
+        }
+    }
+}
+
+// stacked slices for building the bird out of sheets, cut at whatever --scale makes it
+#[derive(Args)]
+struct SliceArgs {
+    #[arg(
+        long,
+        help = "Slice the bird into layers, writing layers.svg and sheet-N.svg into this folder"
+    )]
+    slices: Option<PathBuf>,
+    #[arg(long, default_value_t = 3.0, help = "Sheet material thickness (mm)")]
+    thickness: f64,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "top",
+        help = "Which way the layers face"
+    )]
+    slice_view: ViewArg,
+    #[arg(
+        long,
+        value_parser = parse_sheet,
+        default_value = "600x400",
+        help = "Size of the sheets to lay the layers out on, WxH (mm)"
+    )]
+    sheet: (f64, f64),
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Dowel holes to line the layers up with"
+    )]
+    holes: usize,
+    #[arg(long, default_value_t = 3.0, help = "Dowel hole diameter (mm)")]
+    hole_diameter: f64,
+}
+
+impl SliceArgs {
+    fn options(&self) -> SliceOptions {
+        SliceOptions {
+            view: self.slice_view.view(),
+            thickness: self.thickness,
+            registration_holes: self.holes,
+            hole_diameter: self.hole_diameter,
+            ..Default::default()
+        }
+    }
+}
+
+// a low poly paper model, printed at whatever --scale makes it
+#[derive(Args)]
+struct PapercraftArgs {
+    #[arg(
+        long,
+        help = "Unfold a low poly bird into a printable net, as a PDF (.pdf) or an SVG per page"
+    )]
+    papercraft: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = 240,
+        help = "Roughly how many faces the paper bird gets"
+    )]
+    paper_faces: usize,
+    #[arg(
+        long,
+        value_parser = parse_sheet,
+        default_value = "210x297",
+        help = "Paper size, WxH (mm)"
+    )]
+    paper_size: (f64, f64),
+}
+
+impl PapercraftArgs {
+    fn options(&self) -> PapercraftOptions {
+        let (width, height) = self.paper_size;
+        PapercraftOptions {
+            faces: self.paper_faces,
+            page_size: DVec2::new(width, height),
+            ..Default::default()
+        }
+    }
+}
+
+// a blocky bird for MagicaVoxel or Minecraft, the same number of voxels whatever the --scale
+#[derive(Args)]
+struct VoxelArgs {
+    #[arg(
+        long,
+        help = "Voxelise the bird, as MagicaVoxel (.vox) or a Minecraft schematic (.schem)"
+    )]
+    voxels: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = 64,
+        help = "Voxels along the bird's longest side"
+    )]
+    voxel_size: usize,
+}
+
+impl VoxelArgs {
+    fn options(&self, color: [u8; 3]) -> VoxelOptions {
+        VoxelOptions {
+            size: self.voxel_size,
+            body_color: color,
+            ..Default::default()
+        }
+    }
+}
+
+// a brick built bird, one stud per voxel (so --scale doesn't come into it either)
+#[derive(Args)]
+struct BrickArgs {
+    #[arg(
+        long,
+        help = "Build the bird out of bricks, as an LDraw model (.ldr) plus a -parts.csv parts list"
+    )]
+    bricks: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value_t = 24,
+        help = "Studs along the brick bird's longest side"
+    )]
+    studs: usize,
+    #[arg(long, default_value_t = 8, help = "Longest brick to use (studs)")]
+    max_brick: usize,
+    #[arg(long, help = "Only 1xN bricks, no 2xN")]
+    one_wide: bool,
+}
+
+impl BrickArgs {
+    fn options(&self) -> BrickOptions {
+        BrickOptions {
+            max_length: self.max_brick,
+            two_wide: !self.one_wide,
+        }
+    }
+}
+
+// "600x400"
+fn parse_sheet(sheet: &str) -> Result<(f64, f64), String> {
+    let size = sheet.split_once(['x', 'X']).and_then(|(width, height)| {
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+    match size {
+        Some((width, height)) if width > 0.0 && height > 0.0 => Ok((width, height)),
+        _ => Err(format!("{sheet} should be WxH in mm, e.g. 600x400")),
+    }
+}
+
+fn parse_png_size(size: &str) -> Result<u32, String> {
+    match size.trim().parse() {
+        Ok(size) if (1..=MAX_PNG_SIZE).contains(&size) => Ok(size),
+        _ => Err(format!("{size} should be 1 to {MAX_PNG_SIZE} px")),
+    }
+}
+
+// "#rrggbb" (the # is optional)
+fn parse_color(color: &str) -> Result<[u8; 3], String> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(format!("{color} isn't a #rrggbb colour"));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| format!("{color} isn't a #rrggbb colour"))
+    };
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+impl FilamentArgs {
+    fn options(&self) -> FilamentOptions {
+        FilamentOptions {
+            density: self.density,
+            diameter: self.diameter,
+            infill: self.infill,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MeshFormat {
+    Stl,
+    #[value(name = "3mf")]
+    ThreeMf,
+    Obj,
+}
+
+impl MeshFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            MeshFormat::Stl => "stl",
+            MeshFormat::ThreeMf => "3mf",
+            MeshFormat::Obj => "obj",
+        }
+    }
+
+    fn write(&self, export: &BirdExport, name: &str) -> Result<Vec<u8>, String> {
+        Ok(match self {
+            MeshFormat::Stl => export.to_stl(name),
+            MeshFormat::ThreeMf => export.to_3mf(name)?,
+            MeshFormat::Obj => export.to_obj(name),
+        })
+    }
+
+    // all the pieces in one go, for stdout or a 3mf
+    fn write_split(&self, split: &SplitBird, name: &str) -> Result<Vec<u8>, String> {
+        match self {
+            MeshFormat::Stl => Ok(split.to_stl(name)),
+            MeshFormat::ThreeMf => split.to_3mf(name),
+            MeshFormat::Obj => Err("split birds can only be written as stl or 3mf".to_string()),
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        MeshFormat::value_variants()
+            .iter()
+            .find(|format| format.extension() == extension)
+            .copied()
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PrecisionArg {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NeckFixArg {
+    Extend,
+    Clamp,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HangerArg {
+    HeadLoop,
+    BackLoop,
+    TailHole,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SplitArg {
+    #[value(help = "Left and right halves")]
+    Sagittal,
+    #[value(help = "Front and back halves")]
+    Coronal,
+    #[value(help = "Top and bottom halves")]
+    Transverse,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ViewArg {
+    Side,
+    Front,
+    Top,
+}
+
+impl ViewArg {
+    fn view(&self) -> View {
+        match self {
+            ViewArg::Side => View::Side,
+            ViewArg::Front => View::Front,
+            ViewArg::Top => View::Top,
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Some(Command::Batch(batch)) => run_batch(batch),
+        Some(Command::Sweep(sweep)) => run_sweep(sweep),
+        None => run(&cli.bird),
+    };
+    if let Err(e) = result {
+        eprintln!("birdgen: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: &BirdArgs) -> Result<(), String> {
+    let rng_seed = cli.rng_seed.unwrap_or_else(rand::random);
+    let settings = cli.mesh.settings();
+    let export = BirdExport::new(&pick_bird(cli, rng_seed)?, &cli.export.options(settings))?;
+    // (a clamped neck moves the head, so the seed has to come from the bird that got built)
+    let bird = export.inputs;
+    let seed = bird.get_bird_seed_string();
+
+    let printability = check_printability(
+        &bird,
+        &BirdCSGMeshes {
+            head: export.head.clone(),
+            body: export.body.clone(),
+        },
+        &cli.mesh.printability(),
+    );
+
+    let export = export.scaled(cli.mesh.scale);
+    let metrics = export.metrics(&cli.filament.options());
+    // split after scaling, so the pegs and their clearance are real mm
+    let split = match cli.export.split_options() {
+        Some(options) => {
+            let split = export.split(&options)?;
+            eprintln!(
+                "split in two with {} pegs ({:.1}mm, {:.1}mm clearance)",
+                split.pegs.len(),
+                options.peg_diameter,
+                options.clearance
+            );
+            Some(split)
+        }
+        None => None,
+    };
+
+    let format = cli
+        .mesh
+        .format
+        .or_else(|| cli.output.as_deref().and_then(MeshFormat::from_path))
+        .unwrap_or(MeshFormat::Stl);
+    let output = cli
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("coolbird-{seed}.{}", format.extension())));
+    let to_stdout = output.as_os_str() == "-";
+    let mut pieces = vec![];
+    let written = if cli.no_mesh {
+        None
+    } else if let Some(split) = split
+        .as_ref()
+        .filter(|_| matches!(format, MeshFormat::Stl) && !to_stdout)
+    {
+        // an stl per piece next to where the whole bird would have gone (bird-a.stl, bird-b.stl, bird-pegs.stl)
+        let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+        for (file_name, stl) in split.to_stl_files(&stem) {
+            let path = output.with_file_name(file_name);
+            write_extra(&path, &stl)?;
+            pieces.push(path);
+        }
+        None
+    } else {
+        let name = format!("coolbird-{seed}");
+        let bytes = match &split {
+            Some(split) => format.write_split(split, &name)?,
+            None => format.write(&export, &name)?,
+        };
+        if to_stdout {
+            io::stdout()
+                .write_all(&bytes)
+                .map_err(|e| format!("couldn't write the mesh to stdout: {e}"))?;
+        } else {
+            fs::write(&output, bytes)
+                .map_err(|e| format!("couldn't write {}: {e}", output.display()))?;
+        }
+        Some(output)
+    };
+    let mut extras: Vec<(&'static str, &Path)> = pieces
+        .iter()
+        .map(|path| ("piece", path.as_path()))
+        .collect();
+    if let Some(path) = &cli.png {
+        let png = export.render(&cli.render.options(cli.png_size)).to_png()?;
+        write_extra(path, &png)?;
+        extras.push(("png", path.as_path()));
+    }
+    if let Some(path) = &cli.svg {
+        let silhouette = export.silhouette(&SilhouetteOptions {
+            view: cli.view.view(),
+            detail_lines: cli.detail_lines,
+            ..Default::default()
+        });
+        write_extra(
+            path,
+            silhouette.to_svg(&format!("coolbird-{seed}")).as_bytes(),
+        )?;
+        extras.push(("svg", path.as_path()));
+    }
+    if let Some(dir) = &cli.slices.slices {
+        let sliced = export.slice(&cli.slices.options());
+        let name = format!("coolbird-{seed}");
+        let (width, height) = cli.slices.sheet;
+        let sheets = sliced.to_sheets_svg(&name, DVec2::new(width, height), 3.0)?;
+        fs::create_dir_all(dir).map_err(|e| format!("couldn't create {}: {e}", dir.display()))?;
+        write_extra(
+            &dir.join("layers.svg"),
+            sliced.to_layers_svg(&name).as_bytes(),
+        )?;
+        for (i, sheet) in sheets.iter().enumerate() {
+            write_extra(&dir.join(format!("sheet-{}.svg", i + 1)), sheet.as_bytes())?;
+        }
+        eprintln!(
+            "{} layers ({:.0}mm tall) on {} sheets",
+            sliced.layers.len(),
+            sliced.height(),
+            sheets.len()
+        );
+        extras.push(("slices", dir.as_path()));
+    }
+    if let Some(path) = &cli.papercraft.papercraft {
+        let net = export.papercraft(&cli.papercraft.options())?;
+        let name = format!("coolbird-{seed}");
+        let is_pdf = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        if is_pdf {
+            write_extra(path, &net.to_pdf(&name))?;
+        } else {
+            let pages = net.to_svg_pages(&name);
+            // (bird.svg, or bird-1.svg, bird-2.svg... when it takes more than one page)
+            for (i, page) in pages.iter().enumerate() {
+                let page_path = if pages.len() == 1 {
+                    path.clone()
+                } else {
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    path.with_file_name(format!("{stem}-{}.svg", i + 1))
+                };
+                write_extra(&page_path, page.as_bytes())?;
+            }
+        }
+        eprintln!(
+            "{} + {} faces in {} pieces on {} pages, {} edges to glue",
+            net.head_faces,
+            net.body_faces,
+            net.pieces.len(),
+            net.pages,
+            net.glue_edges
+        );
+        extras.push(("papercraft", path.as_path()));
+    }
+    if let Some(path) = &cli.voxels.voxels {
+        let voxels =
+            BirdParts::new(&bird, &settings).voxelise(&cli.voxels.options(cli.render.color));
+        let is_schem = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("schem"));
+        if is_schem {
+            write_extra(path, &voxels.to_schem()?)?;
+        } else {
+            write_extra(path, &voxels.to_vox())?;
+        }
+        let [x, y, z] = voxels.size;
+        eprintln!(
+            "{x}x{y}x{z} voxels ({:.1}mm each): {} body, {} beak, {} eye",
+            voxels.voxel_size * cli.mesh.scale,
+            voxels.count(VoxelMaterial::Body),
+            voxels.count(VoxelMaterial::Beak),
+            voxels.count(VoxelMaterial::Eyes)
+        );
+        extras.push(("voxels", path.as_path()));
+    }
+    let parts_path;
+    if let Some(path) = &cli.bricks.bricks {
+        let voxels = BirdParts::new(&bird, &settings).voxelise(&VoxelOptions {
+            size: cli.bricks.studs,
+            body_color: cli.render.color,
+            ..Default::default()
+        });
+        let model = voxels.to_bricks(&cli.bricks.options());
+        write_extra(path, model.to_ldr(&format!("coolbird-{seed}")).as_bytes())?;
+        // (bird.ldr's parts list goes in bird-parts.csv)
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        parts_path = path.with_file_name(format!("{stem}-parts.csv"));
+        write_extra(&parts_path, model.to_parts_csv().as_bytes())?;
+        let [x, y, z] = model.size_mm();
+        eprintln!(
+            "{} bricks in {} layers, {x:.0}x{y:.0}x{z:.0}mm built:",
+            model.bricks.len(),
+            model.layers
+        );
+        for counted in model.parts_list() {
+            eprintln!(
+                "{:>5} x {} {}",
+                counted.count,
+                counted.color.name,
+                counted.part.name()
+            );
+        }
+        extras.push(("bricks", path.as_path()));
+        extras.push(("parts", parts_path.as_path()));
+    }
+
+    let report = Report {
+        seed: &seed,
+        rng_seed: (cli.random || cli.good).then_some(rng_seed),
+        metrics: &metrics,
+        printability: &printability,
+        neck: export.neck_report.as_ref(),
+        hollow: export.hollow_report.as_ref(),
+        written: written.as_deref(),
+        extras,
+    };
+    let text = if cli.json {
+        report.to_json()
+    } else {
+        report.to_text()
+    };
+    // keep stdout clean when the mesh is going there
+    if to_stdout && written.is_some() {
+        eprintln!("{text}");
+    } else {
+        println!("{text}");
+    }
+    Ok(())
+}
+
+fn pick_bird(cli: &BirdArgs, rng_seed: u64) -> Result<BirdGenInputs, String> {
+    let sources =
+        cli.seed.is_some() as u8 + cli.file.is_some() as u8 + cli.random as u8 + cli.good as u8;
+    if sources != 1 {
+        return Err("pick exactly one of a seed, --file, --random or --good".to_string());
+    }
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    let mut bird = BirdGenInputs::default();
+    if cli.random {
+        bird.randomize_values_with_rng(&mut rng);
+        // round trip through the seed string, so the printed seed gives back exactly this bird
+        let seed = bird.get_bird_seed_string();
+        bird.update_from_seed_string(seed)?;
+        return Ok(bird);
+    }
+    let seed = if cli.good {
+        GOOD_BIRDS
+            .choose(&mut rng)
+            .expect("there's always a good bird")
+            .to_string()
+    } else if let Some(path) = &cli.file {
+        read_seed_file(path)?
+    } else {
+        cli.seed.clone().unwrap_or_default()
+    };
+    bird.update_from_seed_string(seed.trim().to_string())?;
+    Ok(bird)
+}
+
+fn write_extra(path: &Path, contents: &[u8]) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("couldn't write {}: {e}", path.display()))
+}
+
+fn read_seed_file(path: &Path) -> Result<String, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("no seed in {}", path.display()))
+}
+
+struct Report<'a> {
+    seed: &'a str,
+    rng_seed: Option<u64>,
+    metrics: &'a BirdMetrics,
+    printability: &'a PrintabilityReport,
+    neck: Option<&'a NeckReport>,
+    hollow: Option<&'a HollowReport>,
+    written: Option<&'a Path>,
+    // anything else written alongside the mesh, e.g. ("png", path)
+    extras: Vec<(&'static str, &'a Path)>,
+}
+
+impl Report<'_> {
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .printability
+            .problems
+            .iter()
+            .map(|problem| problem.describe())
+            .collect();
+        if self.neck.is_some_and(|neck| neck.still_floating) {
+            warnings.push("head is still floating after the neck fix".to_string());
+        }
+        warnings
+    }
+
+    fn neck_changes(&self) -> Vec<String> {
+        self.neck
+            .map(|neck| {
+                neck.changes
+                    .iter()
+                    .map(|change| change.describe())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![format!("seed      {}", self.seed)];
+        if let Some(rng_seed) = self.rng_seed {
+            lines.push(format!("rng seed  {rng_seed}"));
+        }
+        let metrics = self.metrics;
+        let size = metrics.bounds.size();
+        lines.push(format!(
+            "size      {:.1} x {:.1} x {:.1} mm",
+            size.x, size.y, size.z
+        ));
+        lines.push(format!("volume    {:.1} mm3", metrics.volume));
+        lines.push(format!("area      {:.1} mm2", metrics.surface_area));
+        lines.push(format!(
+            "filament  {:.1} g, {:.2} m",
+            metrics.filament.mass_grams,
+            metrics.filament.length_mm / 1000.0
+        ));
+        for change in self.neck_changes() {
+            lines.push(format!("neck      {change}"));
+        }
+        if let Some(hollow) = self.hollow {
+            lines.push(format!(
+                "hollow    {:.1} mm walls (asked for {:.1}), {} drain holes, {:.1} mm3 of resin saved",
+                hollow.achieved_wall_thickness,
+                hollow.requested_wall_thickness,
+                hollow.drain_holes,
+                hollow.resin_saved
+            ));
+        }
+        let extras = self.extras.iter().map(|(_, path)| *path);
+        for path in self.written.into_iter().chain(extras) {
+            lines.push(format!("wrote     {}", path.display()));
+        }
+        for warning in self.warnings() {
+            lines.push(format!("warning   {warning}"));
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> String {
+        let metrics = self.metrics;
+        let (min, max) = (metrics.bounds.min, metrics.bounds.max);
+        let mut report = json!({
+            "seed": self.seed,
+            "rng_seed": self.rng_seed,
+            "metrics": {
+                "volume_mm3": metrics.volume,
+                "surface_area_mm2": metrics.surface_area,
+                "bounds": {
+                    "min": [min.x, min.y, min.z],
+                    "max": [max.x, max.y, max.z],
+                },
+                "filament": {
+                    "material_volume_mm3": metrics.filament.material_volume,
+                    "mass_g": metrics.filament.mass_grams,
+                    "length_mm": metrics.filament.length_mm,
+                },
+            },
+            "printability": {
+                "printable": self.printability.is_printable(),
+                "shells": self.printability.shells,
+                "head_attached": self.printability.head_attached,
+                "stability_margin_mm": self.printability.stability_margin,
+                "overhang_fraction": self.printability.overhang_fraction,
+            },
+            "neck_changes": self.neck_changes(),
+            "hollow": self.hollow.map(|hollow| json!({
+                "requested_wall_mm": hollow.requested_wall_thickness,
+                "achieved_wall_mm": hollow.achieved_wall_thickness,
+                "drain_holes": hollow.drain_holes,
+                "solid_volume_mm3": hollow.solid_volume,
+                "hollow_volume_mm3": hollow.hollow_volume,
+                "resin_saved_mm3": hollow.resin_saved,
+            })),
+            "output": self.written.map(|path| path.display().to_string()),
+            "warnings": self.warnings(),
+        });
+        for (key, path) in &self.extras {
+            report[*key] = json!(path.display().to_string());
+        }
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+}
@@ -0,0 +1,149 @@
+// `birdgen sweep`: walk one or two inputs across their ranges on a fixed bird, to see what they actually do
+// e.g. `birdgen sweep beak_length --by head_size:15:35 --steps 5 -o beaks.png --seeds beaks.txt`
+use crate::{BuildArgs, RenderArgs, parse_png_size, read_seed_file};
+use birdgen::{BirdGenInputTypes, BirdGenInputs, ContactSheetOptions, NeckFix, Sweep, SweepAxis};
+use clap::Args;
+use std::{fs, path::PathBuf};
+
+#[derive(Args)]
+pub(crate) struct SweepArgs {
+    #[arg(
+        value_parser = parse_axis,
+        help = "Input to sweep across the sheet, e.g. beak_length or beak_length:0:30"
+    )]
+    input: AxisArg,
+    #[arg(long, value_parser = parse_axis, help = "Second input to sweep down the sheet")]
+    by: Option<AxisArg>,
+    #[arg(long, default_value_t = 6, help = "Steps along each input")]
+    steps: usize,
+    #[arg(long, help = "Bird to hold still [default: the app's default bird]")]
+    base: Option<String>,
+    #[arg(long, conflicts_with = "base", help = "Read the base seed from a file")]
+    base_file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "Where to write the contact sheet [default: sweep-<input>.png]"
+    )]
+    output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Also write every cell's seed to this file ('-' for stdout)"
+    )]
+    seeds: Option<PathBuf>,
+    #[arg(long, help = "Only write the seeds, skip rendering the sheet")]
+    no_sheet: bool,
+    #[arg(
+        long,
+        default_value_t = 192,
+        value_parser = parse_png_size,
+        help = "Size of each bird on the sheet (px, up to 8192)"
+    )]
+    cell_size: u32,
+    #[arg(
+        long,
+        help = "Fit each bird to its cell instead of keeping them all at one scale"
+    )]
+    independent_scale: bool,
+    #[command(flatten)]
+    build: BuildArgs,
+    #[command(flatten)]
+    render: RenderArgs,
+}
+
+// an input, and optionally the part of its range to sweep
+#[derive(Clone, Copy)]
+struct AxisArg {
+    input: BirdGenInputTypes,
+    range: Option<(f32, f32)>,
+}
+
+impl AxisArg {
+    fn axis(&self, steps: usize) -> SweepAxis {
+        let full = SweepAxis::full(self.input, steps);
+        match self.range {
+            Some((from, to)) => SweepAxis { from, to, ..full },
+            None => full,
+        }
+    }
+}
+
+// "name" or "name:from:to"
+fn parse_axis(arg: &str) -> Result<AxisArg, String> {
+    let mut parts = arg.split(':');
+    let name = parts.next().unwrap_or_default();
+    let input = BirdGenInputTypes::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = BirdGenInputTypes::ALL
+            .iter()
+            .map(|input| input.name())
+            .collect();
+        format!("no input called {name}, try one of {}", names.join(", "))
+    })?;
+    let bounds: Vec<f32> = parts
+        .map(|part| part.parse().map_err(|_| format!("{part} isn't a number")))
+        .collect::<Result<_, _>>()?;
+    let range = match bounds[..] {
+        [] => None,
+        [from, to] => Some((from, to)),
+        _ => return Err(format!("{arg} should be name or name:from:to")),
+    };
+    Ok(AxisArg { input, range })
+}
+
+pub(crate) fn run_sweep(args: &SweepArgs) -> Result<(), String> {
+    // clamping pulls the head inputs back in, so the sheet wouldn't show what it's labelled with
+    if args.build.settings().neck_fix == Some(NeckFix::ClampOffsets) {
+        return Err("--fix-neck clamp would undo the sweep, use --fix-neck extend".to_string());
+    }
+    let base_seed = match (&args.base, &args.base_file) {
+        (Some(seed), _) => Some(seed.clone()),
+        (None, Some(path)) => Some(read_seed_file(path)?),
+        (None, None) => None,
+    };
+    let mut base = BirdGenInputs::default();
+    if let Some(seed) = base_seed {
+        base.update_from_seed_string(seed.trim().to_string())?;
+    }
+    let sweep = Sweep {
+        base,
+        columns: args.input.axis(args.steps),
+        rows: args.by.map(|by| by.axis(args.steps)),
+    };
+    eprintln!(
+        "sweeping {} ({} birds)",
+        sweep.describe(),
+        sweep.cells().len()
+    );
+
+    if let Some(path) = &args.seeds {
+        let seeds = sweep.seed_list();
+        if path.as_os_str() == "-" {
+            print!("{seeds}");
+        } else {
+            fs::write(path, seeds)
+                .map_err(|e| format!("couldn't write {}: {e}", path.display()))?;
+            eprintln!("wrote {}", path.display());
+        }
+    }
+    if args.no_sheet {
+        return Ok(());
+    }
+
+    let output = args.output.clone().unwrap_or_else(|| {
+        let mut name = format!("sweep-{}", sweep.columns.input.name());
+        if let Some(rows) = &sweep.rows {
+            name += &format!("-{}", rows.input.name());
+        }
+        PathBuf::from(format!("{name}.png"))
+    });
+    let sheet = sweep.contact_sheet(&ContactSheetOptions {
+        cell_size: args.cell_size,
+        settings: args.build.settings(),
+        render: args.render.options(args.cell_size),
+        same_scale: !args.independent_scale,
+    });
+    fs::write(&output, sheet.to_png()?)
+        .map_err(|e| format!("couldn't write {}: {e}", output.display()))?;
+    eprintln!("wrote {}", output.display());
+    Ok(())
+}
@@ -1,9 +1,18 @@
+use crate::{
+    ExportOptions,
+    GenerationPart::{Body, Head},
+    NeckFix,
+    PipelineStage::*,
+    StageInput, StageProfile,
+    smooth_body::SmoothBody,
+};
 use bevy::{ecs::resource::Resource, mesh::Mesh};
 use csgrs::{csg::CSG, mesh::plane::Plane};
 use rand::seq::IndexedRandom;
-type CSGMesh = csgrs::mesh::Mesh<()>;
+use std::ops::RangeInclusive;
+pub type CSGMesh = csgrs::mesh::Mesh<()>;
 
-const GOOD_BIRDS: &'static [&'static str] = &[
+pub const GOOD_BIRDS: &'static [&'static str] = &[
     "m.22.67.4.190.h.26.24.17.-7.26.33.36.b.43.21.55.13.35.t.36.15.-15.15.82.c.92",
     "m.44.67.16.131.h.20.1.3.10.26.30.37.b.57.21.55.13.35.t.31.15.-15.-6.82.c.94",
     "m.14.57.15.10.h.22.32.7.6.32.27.-22.b.60.40.100.40.25.t.50.47.14.72.197.c.100",
@@ -88,6 +97,7 @@ pub struct RecentBirds {
     pub right: BirdGenInputs,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BirdGenInputTypes {
     BeakLength,
     BeakSize,
@@ -113,6 +123,95 @@ pub enum BirdGenInputTypes {
     BaseFlat,
 }
 
+impl BirdGenInputTypes {
+    pub const ALL: [BirdGenInputTypes; 22] = [
+        BirdGenInputTypes::BeakLength,
+        BirdGenInputTypes::BeakSize,
+        BirdGenInputTypes::BeakWidth,
+        BirdGenInputTypes::BeakRoundness,
+        BirdGenInputTypes::HeadSize,
+        BirdGenInputTypes::HeadToBelly,
+        BirdGenInputTypes::EyeSize,
+        BirdGenInputTypes::HeadLateralOffset,
+        BirdGenInputTypes::HeadLevel,
+        BirdGenInputTypes::HeadYaw,
+        BirdGenInputTypes::HeadPitch,
+        BirdGenInputTypes::BellyLength,
+        BirdGenInputTypes::BellySize,
+        BirdGenInputTypes::BellyFat,
+        BirdGenInputTypes::BellyToBottom,
+        BirdGenInputTypes::BottomSize,
+        BirdGenInputTypes::TailLength,
+        BirdGenInputTypes::TailWidth,
+        BirdGenInputTypes::TailYaw,
+        BirdGenInputTypes::TailPitch,
+        BirdGenInputTypes::TailRoundness,
+        BirdGenInputTypes::BaseFlat,
+    ];
+
+    // snake_case, same as the field names
+    pub fn name(&self) -> &'static str {
+        match self {
+            BirdGenInputTypes::BeakLength => "beak_length",
+            BirdGenInputTypes::BeakSize => "beak_size",
+            BirdGenInputTypes::BeakWidth => "beak_width",
+            BirdGenInputTypes::BeakRoundness => "beak_roundness",
+            BirdGenInputTypes::HeadSize => "head_size",
+            BirdGenInputTypes::HeadToBelly => "head_to_belly",
+            BirdGenInputTypes::EyeSize => "eye_size",
+            BirdGenInputTypes::HeadLateralOffset => "head_lateral_offset",
+            BirdGenInputTypes::HeadLevel => "head_level",
+            BirdGenInputTypes::HeadYaw => "head_yaw",
+            BirdGenInputTypes::HeadPitch => "head_pitch",
+            BirdGenInputTypes::BellyLength => "belly_length",
+            BirdGenInputTypes::BellySize => "belly_size",
+            BirdGenInputTypes::BellyFat => "belly_fat",
+            BirdGenInputTypes::BellyToBottom => "belly_to_bottom",
+            BirdGenInputTypes::BottomSize => "bottom_size",
+            BirdGenInputTypes::TailLength => "tail_length",
+            BirdGenInputTypes::TailWidth => "tail_width",
+            BirdGenInputTypes::TailYaw => "tail_yaw",
+            BirdGenInputTypes::TailPitch => "tail_pitch",
+            BirdGenInputTypes::TailRoundness => "tail_roundness",
+            BirdGenInputTypes::BaseFlat => "base_flat",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BirdGenInputTypes> {
+        BirdGenInputTypes::ALL
+            .into_iter()
+            .find(|input_type| input_type.name() == name)
+    }
+
+    // the values each input is meant to take (same as the ranges `randomize_values` picks from)
+    pub fn range(&self) -> RangeInclusive<f32> {
+        match self {
+            BirdGenInputTypes::BeakLength => 0.0..=50.0,
+            BirdGenInputTypes::BeakSize => 20.0..=100.0,
+            BirdGenInputTypes::BeakWidth => 0.0..=25.0,
+            BirdGenInputTypes::BeakRoundness => 10.0..=200.0,
+            BirdGenInputTypes::HeadSize => 10.0..=40.0,
+            BirdGenInputTypes::HeadToBelly => -20.0..=50.0,
+            BirdGenInputTypes::EyeSize => 0.0..=20.0,
+            BirdGenInputTypes::HeadLateralOffset => -15.0..=15.0,
+            BirdGenInputTypes::HeadLevel => 0.0..=80.0,
+            BirdGenInputTypes::HeadYaw => -45.0..=45.0,
+            BirdGenInputTypes::HeadPitch => -80.0..=45.0,
+            BirdGenInputTypes::BellyLength => 10.0..=100.0,
+            BirdGenInputTypes::BellySize => 20.0..=60.0,
+            BirdGenInputTypes::BellyFat => 50.0..=150.0,
+            BirdGenInputTypes::BellyToBottom => 1.0..=50.0,
+            BirdGenInputTypes::BottomSize => 5.0..=50.0,
+            BirdGenInputTypes::TailLength => 0.0..=100.0,
+            BirdGenInputTypes::TailWidth => 1.0..=50.0,
+            BirdGenInputTypes::TailYaw => -45.0..=45.0,
+            BirdGenInputTypes::TailPitch => -45.0..=90.0,
+            BirdGenInputTypes::TailRoundness => 10.0..=200.0,
+            BirdGenInputTypes::BaseFlat => -100.0..=100.0,
+        }
+    }
+}
+
 impl Default for BirdGenInputs {
     fn default() -> Self {
         BirdGenInputs {
@@ -198,9 +297,11 @@ impl BirdGenInputs {
     }
 
     pub fn randomize_values(&mut self) {
-        use rand::Rng;
-        let mut rng = rand::rng();
+        self.randomize_values_with_rng(&mut rand::rng());
+    }
 
+    // same as `randomize_values`, but with a caller provided (e.g. seeded) rng
+    pub fn randomize_values_with_rng(&mut self, rng: &mut impl rand::Rng) {
         self.beak_length = rng.random_range(0.0..=50.0);
         self.beak_size = rng.random_range(20.0..=100.0);
         self.beak_width = rng.random_range(0.0..=25.0);
@@ -280,35 +381,18 @@ impl BirdGenInputs {
     }
 
     pub fn get_child_with(&self, mate: &BirdGenInputs) -> BirdGenInputs {
-        use rand::Rng;
-        let mut rng = rand::rng();
+        self.get_child_with_rng(mate, &mut rand::rng())
+    }
+
+    // same as `get_child_with`, but with a caller provided (e.g. seeded) rng
+    pub fn get_child_with_rng(
+        &self,
+        mate: &BirdGenInputs,
+        rng: &mut impl rand::Rng,
+    ) -> BirdGenInputs {
         let mut child = mate.clone();
-        let all_bird_input_types = vec![
-            BirdGenInputTypes::BeakLength,
-            BirdGenInputTypes::BeakSize,
-            BirdGenInputTypes::BeakWidth,
-            BirdGenInputTypes::BeakRoundness,
-            BirdGenInputTypes::HeadSize,
-            BirdGenInputTypes::HeadToBelly,
-            BirdGenInputTypes::EyeSize,
-            BirdGenInputTypes::HeadLateralOffset,
-            BirdGenInputTypes::HeadLevel,
-            BirdGenInputTypes::HeadYaw,
-            BirdGenInputTypes::HeadPitch,
-            BirdGenInputTypes::BellyLength,
-            BirdGenInputTypes::BellySize,
-            BirdGenInputTypes::BellyFat,
-            BirdGenInputTypes::BellyToBottom,
-            BirdGenInputTypes::BottomSize,
-            BirdGenInputTypes::TailLength,
-            BirdGenInputTypes::TailWidth,
-            BirdGenInputTypes::TailYaw,
-            BirdGenInputTypes::TailPitch,
-            BirdGenInputTypes::TailRoundness,
-            BirdGenInputTypes::BaseFlat,
-        ];
         // roll the dice for each 'trait'
-        for bird_input_type in all_bird_input_types {
+        for bird_input_type in BirdGenInputTypes::ALL {
             // chance to prefer either parent's
             // some variance to try an represent more dominant traits? idk lol
             let parent_favor_per_trait = rng.random_range(0.4..=0.6);
@@ -370,6 +454,16 @@ impl BirdGenInputs {
         format!("{mouth_str}.{head_str}.{belly_str}.{tail_str}.{cutoff_str}")
     }
 
+    // The bird its seed string gives back: seeds only hold whole numbers, cut towards zero
+    pub fn quantised(&self) -> BirdGenInputs {
+        let mut bird = *self;
+        for input_type in BirdGenInputTypes::ALL {
+            let value = self.get_input_value_for_type(&input_type);
+            bird.set_input_value_for_type(&input_type, value.trunc());
+        }
+        bird
+    }
+
     pub fn update_from_seed_string(&mut self, seed: String) -> Result<(), String> {
         let parts: Vec<&str> = seed.split('.').collect();
 
@@ -459,39 +553,22 @@ impl BirdGenInputs {
     }
 
     pub fn get_stl(&self) -> Result<Vec<u8>, std::io::Error> {
-        // rotate STL's - idk man but when I uploaded to shapeways it thought the bird was on it's side, switching that up here
-        let head_csg_mesh = generate_bird_head_csg_mesh(self).rotate(-90., 0., 0.);
-        let body_csg_mesh = generate_bird_body_csg_mesh(self).rotate(-90., 0., 0.);
-        let body_stl_str = body_csg_mesh
-            .to_stl_ascii(format!("coolbird-{}", self.get_bird_seed_string()).as_str());
-        let head_stl_str = head_csg_mesh.to_stl_ascii("head");
-        // grab triangles from head and add to body, manually editing the string of the STL
-        // (does feel a bit hacky - but it does maintain head and body triangles better)
-        // much bigger than the binary format stl tho
-        {
-            let mut result = body_stl_str.clone();
-
-            // Remove the "endsolid" line from body
-            if let Some(pos) = result.rfind("endsolid") {
-                result.truncate(pos);
-            }
-
-            // Extract facets from head (between "solid" line and "endsolid" line)
-            let facets_start = head_stl_str.find("facet").unwrap_or(head_stl_str.len());
-            let facets_end = head_stl_str.rfind("endsolid").unwrap_or(head_stl_str.len());
-            let head_facets = &head_stl_str[facets_start..facets_end];
-
-            // Combine: body (without endsolid) + head facets + endsolid
-            result.push_str(head_facets);
-            result.push_str("endsolid bird\n");
+        self.get_stl_with(&ExportOptions::default())
+    }
 
-            Ok(result.as_bytes().to_vec())
-        }
+    // Height (z, generation frame) the flat base gets cut at, None if there's no cut
+    // The cut box's top ends up a belly's height above `cut_height` in `cut_base`
+    pub fn base_height(&self) -> Option<f32> {
+        (self.base_flat > -100.0).then(|| self.belly_size * (-0.5 + self.base_flat / 200.0))
     }
+
     pub fn get_a_good_bird() -> Self {
-        let mut rng = rand::rng();
+        BirdGenInputs::get_a_good_bird_with_rng(&mut rand::rng())
+    }
+
+    pub fn get_a_good_bird_with_rng(rng: &mut impl rand::Rng) -> Self {
         // select one of our strings of good birds
-        let good_bird_str = *(GOOD_BIRDS.choose(&mut rng).unwrap());
+        let good_bird_str = *(GOOD_BIRDS.choose(rng).unwrap());
         let mut output = BirdGenInputs::default();
         output
             .update_from_seed_string(good_bird_str.to_string())
@@ -500,9 +577,13 @@ impl BirdGenInputs {
     }
 
     pub fn get_semi_random_bird() -> Self {
+        BirdGenInputs::get_semi_random_bird_with_rng(&mut rand::rng())
+    }
+
+    pub fn get_semi_random_bird_with_rng(rng: &mut impl rand::Rng) -> Self {
         let mut random_bird = BirdGenInputs::default();
-        random_bird.randomize_values();
-        let mut output = BirdGenInputs::get_a_good_bird();
+        random_bird.randomize_values_with_rng(rng);
+        let mut output = BirdGenInputs::get_a_good_bird_with_rng(rng);
         output.bird_lerp(&random_bird, 0.01);
         output
     }
@@ -516,10 +597,72 @@ fn lerp(v0: f32, v1: f32, t: f32) -> f32 {
 // There is probably a benefit to tuning the segment/stack count per geometry
 const RESOLUTION_PSUEDO_UNIT: usize = 20;
 
-const SPHERE_SEGMENTS: usize = RESOLUTION_PSUEDO_UNIT;
-const SPHERE_STACKS: usize = RESOLUTION_PSUEDO_UNIT * 2;
+// used for the holes/pegs cut into exports, the bird itself goes by `Precision`
+pub(crate) const SPHERE_SEGMENTS: usize = RESOLUTION_PSUEDO_UNIT;
+
+pub(crate) const NONZERO_THICKNESS: f64 = 0.1; // used in place of 0 when we want parts of the bird to approach an edge
+
+// How the body gets built
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BodyMode {
+    // neck, chest, bottom and tail all hulled together (faceted but sturdy)
+    #[default]
+    Hull,
+    // the same pieces blended together as a signed distance field,
+    // `blend_radius` is how far the smoothing reaches at the joins
+    Smooth {
+        blend_radius: f32,
+    },
+}
 
-const NONZERO_THICKNESS: f64 = 0.1; // used in place of 0 when we want parts of the bird to approach an edge
+impl BodyMode {
+    pub fn smooth() -> Self {
+        BodyMode::Smooth { blend_radius: 6.0 }
+    }
+}
+
+// Mesh resolution, same idea as the original script's low/med/hi setting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Precision {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Precision {
+    pub const ALL: [Precision; 3] = [Precision::Low, Precision::Medium, Precision::High];
+
+    fn resolution(&self) -> usize {
+        match self {
+            Precision::Low => RESOLUTION_PSUEDO_UNIT / 2,
+            Precision::Medium => RESOLUTION_PSUEDO_UNIT,
+            Precision::High => RESOLUTION_PSUEDO_UNIT * 2,
+        }
+    }
+
+    pub(crate) fn sphere_segments(&self) -> usize {
+        self.resolution()
+    }
+
+    pub(crate) fn sphere_stacks(&self) -> usize {
+        self.resolution() * 2
+    }
+
+    // how much finer (or coarser) than medium we are
+    pub(crate) fn detail_scale(&self) -> f64 {
+        self.resolution() as f64 / RESOLUTION_PSUEDO_UNIT as f64
+    }
+}
+
+// Knobs for mesh generation that aren't part of the bird itself (so don't go in the seed)
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BirdMeshSettings {
+    pub body_mode: BodyMode,
+    pub precision: Precision,
+    // stick floating heads back on (off by default, so birds look the way their seed says)
+    pub neck_fix: Option<NeckFix>,
+}
 
 // Currently making separate head and body meshes,
 // Can't get a nice result when doing a union between the head and body
@@ -527,179 +670,296 @@ const NONZERO_THICKNESS: f64 = 0.1; // used in place of 0 when we want parts of
 // For now tho we'll just spawn two different meshes in Bevy, even though that would make printing it in 3d a bit harder.
 // We'll see!
 
-fn generate_bird_head_csg_mesh(input: &BirdGenInputs) -> CSGMesh {
-    // skull base for head
-    let skull: CSGMesh = CSGMesh::sphere(
+pub fn generate_bird_head_csg_mesh(input: &BirdGenInputs) -> CSGMesh {
+    generate_bird_head_csg_mesh_with(input, &BirdMeshSettings::default())
+}
+
+pub fn generate_bird_head_csg_mesh_with(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> CSGMesh {
+    generate_bird_head_csg_mesh_profiled(input, settings, &mut StageProfile::disabled())
+}
+
+pub(crate) fn generate_bird_head_csg_mesh_profiled(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+    profile: &mut StageProfile,
+) -> CSGMesh {
+    // guess what, head is the beak now
+    let mut head = build_beak(input, settings.precision, profile);
+    let eyes = profile.time(Head, Primitives, || {
+        generate_eye_csg_meshes(input, settings.precision)
+    });
+    for eye in eyes {
+        head = profile.union(Head, &head, &eye);
+        // important to do after unions to make sure the mesh looks nice
+        // (i think lol)
+        profile.renormalize(Head, &mut head);
+    }
+
+    let mut head_in_place = place_head(&head, input);
+    profile.renormalize(Head, &mut head_in_place);
+    profile.subdivide(Head, &mut head_in_place);
+    head_in_place
+}
+
+// skull base for head
+pub(crate) fn generate_skull_csg_mesh(input: &BirdGenInputs, precision: Precision) -> CSGMesh {
+    CSGMesh::sphere(
         input.head_size as f64 / 2.0,
-        2 * SPHERE_SEGMENTS,
-        SPHERE_STACKS,
-        None,
-    );
-    // beak
-    let mut beak_skeleton: CSGMesh = CSGMesh::cylinder(
-        if input.beak_width > 0.0 {
-            input.beak_width as f64
-        } else {
-            NONZERO_THICKNESS
-        },
-        NONZERO_THICKNESS,
-        SPHERE_SEGMENTS / 4, // way less resolution since we're conna covnex hull it
+        2 * precision.sphere_segments(),
+        precision.sphere_stacks(),
         None,
     )
-    .scale(input.beak_roundness as f64 / 100.0, 1.0, 1.0)
-    .translate(
-        (-input.beak_length - input.head_size / 2.0) as f64,
-        0.0,
-        0.0,
-    )
-    .rotate(0.0, 15.0, 0.0)
-    .union(&skull.clone());
-    beak_skeleton.renormalize();
-    let mut beak = beak_skeleton.convex_hull().scale(
-        1.0,
-        input.beak_size as f64 / 100.0,
-        input.beak_size as f64 / 100.0,
-    );
-    beak.renormalize();
-    // guess what, head is the beak now
-    let mut head = beak;
-
-    // eyes
-    if input.eye_size > 0.0 {
-        for y in [-1.0, 1.0] {
-            let eye: CSGMesh = CSGMesh::sphere(
-                input.eye_size as f64 / 2.0,
-                // half resolution sphere compared to skull
-                SPHERE_SEGMENTS / 2 + 2,
-                SPHERE_STACKS / 2 + 2,
+}
+
+// beak hulled onto the skull, still centered on the origin
+pub(crate) fn generate_beak_csg_mesh(input: &BirdGenInputs, precision: Precision) -> CSGMesh {
+    build_beak(input, precision, &mut StageProfile::disabled())
+}
+
+fn build_beak(input: &BirdGenInputs, precision: Precision, profile: &mut StageProfile) -> CSGMesh {
+    let [beak_tip, skull] = profile.time(Head, Primitives, || {
+        [
+            CSGMesh::cylinder(
+                if input.beak_width > 0.0 {
+                    input.beak_width as f64
+                } else {
+                    NONZERO_THICKNESS
+                },
+                NONZERO_THICKNESS,
+                precision.sphere_segments() / 4, // way less resolution since we're conna covnex hull it
                 None,
             )
-            .scale(1.0, 1.0, 0.5)
+            .scale(input.beak_roundness as f64 / 100.0, 1.0, 1.0)
             .translate(
+                (-input.beak_length - input.head_size / 2.0) as f64,
                 0.0,
                 0.0,
-                (input.head_size / 2.0 - input.eye_size / 8.0) as f64,
             )
-            .rotate(50.0, -40.0, 0.0);
-            // .scale(1.0, y, 1.0);
-            if y == -1.0 {
-                // flip one eye across y plane
-                let plane_y = Plane::from_normal([0.0, 1.0, 0.0].into(), 0.0);
-                head = head.union(&eye.mirror(plane_y));
-            } else {
-                head = head.union(&eye);
-            }
-            // important to do after unions to make sure the mesh looks nice
-            // (i think lol)
-            head.renormalize();
-        }
+            .rotate(0.0, 15.0, 0.0),
+            generate_skull_csg_mesh(input, precision),
+        ]
+    });
+    let mut beak_skeleton = profile.union(Head, &beak_tip, &skull);
+    profile.renormalize(Head, &mut beak_skeleton);
+    let mut beak = profile.hull(Head, &beak_skeleton).scale(
+        1.0,
+        input.beak_size as f64 / 100.0,
+        input.beak_size as f64 / 100.0,
+    );
+    profile.renormalize(Head, &mut beak);
+    beak
+}
+
+// both eyes (none if eye size is 0), still centered on the origin
+pub(crate) fn generate_eye_csg_meshes(input: &BirdGenInputs, precision: Precision) -> Vec<CSGMesh> {
+    if input.eye_size <= 0.0 {
+        return vec![];
     }
+    let eye: CSGMesh = CSGMesh::sphere(
+        input.eye_size as f64 / 2.0,
+        // half resolution sphere compared to skull
+        precision.sphere_segments() / 2 + 2,
+        precision.sphere_stacks() / 2 + 2,
+        None,
+    )
+    .scale(1.0, 1.0, 0.5)
+    .translate(
+        0.0,
+        0.0,
+        (input.head_size / 2.0 - input.eye_size / 8.0) as f64,
+    )
+    .rotate(50.0, -40.0, 0.0);
+    // flip one eye across y plane
+    let plane_y = Plane::from_normal([0.0, 1.0, 0.0].into(), 0.0);
+    vec![eye.mirror(plane_y), eye]
+}
 
-    let mut head_in_place = head
-        .rotate(0.0, input.head_pitch as f64, input.head_yaw as f64)
+// move a head (or a bit of one) from the origin onto the neck
+pub(crate) fn place_head(head: &CSGMesh, input: &BirdGenInputs) -> CSGMesh {
+    head.rotate(0.0, input.head_pitch as f64, input.head_yaw as f64)
         .translate(
             0.0,
             input.head_lateral_offset as f64,
             input.head_level as f64,
         )
-        .scale(1.1, 1.1, 1.1);
-    head_in_place.renormalize();
-    head_in_place.subdivide_triangles(std::num::NonZero::<u32>::new(1).unwrap());
-    head_in_place
+        .scale(1.1, 1.1, 1.1)
 }
 
 pub fn generate_bird_head_mesh(input: &BirdGenInputs) -> Mesh {
-    let head_in_place = generate_bird_head_csg_mesh(input);
-    // add the x axis rotation to account for y up world we're rocking with in bevy
-    head_in_place.rotate(-90.0, 180.0, 0.0).to_bevy_mesh()
+    generate_bird_head_mesh_with(input, &BirdMeshSettings::default())
+}
+
+pub fn generate_bird_head_mesh_with(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Mesh {
+    to_display_mesh(&generate_bird_head_csg_mesh_with(input, settings))
 }
 
 pub fn generate_bird_body_csg_mesh(input: &BirdGenInputs) -> CSGMesh {
-    let neck = CSGMesh::sphere(
-        input.head_size as f64 / 2.0,
-        SPHERE_SEGMENTS / 2 + 1,
-        SPHERE_STACKS / 2 + 1,
-        None,
-    )
-    .translate(
-        0.0,
-        input.head_lateral_offset as f64,
-        input.head_level as f64,
-    );
-    let chest = CSGMesh::sphere(
-        input.belly_size as f64 / 2.0,
-        SPHERE_SEGMENTS + 2,
-        SPHERE_STACKS + 2,
-        None,
-    )
-    .scale(
-        (input.belly_length / input.belly_size) as f64,
-        input.belly_fat as f64 / 100.0,
-        1.0,
-    )
-    .translate(input.head_to_belly as f64, 0.0, 0.0);
-    let mut body = neck.union(&chest).convex_hull();
-    let bottom = CSGMesh::sphere(
-        input.bottom_size as f64 / 2.0,
-        SPHERE_SEGMENTS + 1,
-        SPHERE_STACKS + 1,
-        None,
-    )
-    .translate(
-        (input.head_to_belly + input.belly_to_bottom) as f64,
-        0.0,
-        0.0,
-    );
-    let body_plus_bottom = body.union(&bottom).convex_hull();
-    body = body_plus_bottom;
-    let tail = CSGMesh::cylinder(
-        input.tail_width as f64,
-        NONZERO_THICKNESS,
-        SPHERE_SEGMENTS + 1,
-        None,
-    )
-    .scale(input.tail_roundness as f64 / 100.0, 1.0, 1.0)
-    .translate(input.tail_length as f64, 0.0, 0.0)
-    .rotate(0.0, -input.tail_pitch as f64, input.tail_yaw as f64)
-    .translate(
-        (input.head_to_belly + input.belly_to_bottom) as f64,
-        0.0,
-        0.0,
-    );
-    let body_plus_tail = body.union(&tail).convex_hull();
-    body = body_plus_tail;
-    body.renormalize();
-
-    if input.base_flat > -100.0 {
-        let total_len =
-            input.beak_length + input.head_to_belly + input.belly_to_bottom + input.tail_length;
-
-        // Calculate the cut height (in OpenSCAD's z-axis, which becomes Bevy's y-axis after rotation)
-        let cut_height = (input.belly_size * (-1.5 + input.base_flat / 200.0)) as f64;
-
-        // Create a large cube to subtract from the bottom
-        // Little hacky with my positioning but idc
-        let cut_box = CSGMesh::cuboid(
-            (total_len * 4.0) as f64,
-            (total_len * 4.0) as f64,
-            input.belly_size as f64 + (total_len * 4.0) as f64,
+    generate_bird_body_csg_mesh_with(input, &BirdMeshSettings::default())
+}
+
+pub fn generate_bird_body_csg_mesh_with(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> CSGMesh {
+    generate_bird_body_csg_mesh_profiled(input, settings, &mut StageProfile::disabled())
+}
+
+pub(crate) fn generate_bird_body_csg_mesh_profiled(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+    profile: &mut StageProfile,
+) -> CSGMesh {
+    let body = match settings.body_mode {
+        BodyMode::Hull => generate_hull_body(input, settings.precision, profile),
+        BodyMode::Smooth { blend_radius } => profile.time(Body, Polygonise, || {
+            SmoothBody::new(input, blend_radius).polygonise(settings.precision)
+        }),
+    };
+    cut_base(body, input, profile)
+}
+
+// the body without the tail, for when the tail needs to be its own part
+pub(crate) fn generate_bird_torso_csg_mesh(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> CSGMesh {
+    let mut profile = StageProfile::disabled();
+    let torso = match settings.body_mode {
+        BodyMode::Hull => {
+            let mut torso = generate_hull_torso(input, settings.precision, &mut profile);
+            torso.renormalize();
+            torso
+        }
+        BodyMode::Smooth { blend_radius } => SmoothBody::new(input, blend_radius)
+            .without_tail()
+            .polygonise(settings.precision),
+    };
+    cut_base(torso, input, &mut profile)
+}
+
+fn generate_hull_body(
+    input: &BirdGenInputs,
+    precision: Precision,
+    profile: &mut StageProfile,
+) -> CSGMesh {
+    let mut body = generate_hull_torso(input, precision, profile);
+    let tail = profile.time(Body, Primitives, || {
+        CSGMesh::cylinder(
+            input.tail_width as f64,
+            NONZERO_THICKNESS,
+            precision.sphere_segments() + 1,
             None,
         )
+        .scale(input.tail_roundness as f64 / 100.0, 1.0, 1.0)
+        .translate(input.tail_length as f64, 0.0, 0.0)
+        .rotate(0.0, -input.tail_pitch as f64, input.tail_yaw as f64)
         .translate(
-            -total_len as f64 * 2.0,
-            -total_len as f64 * 2.0,
-            cut_height - total_len as f64 * 4.0,
-        );
+            (input.head_to_belly + input.belly_to_bottom) as f64,
+            0.0,
+            0.0,
+        )
+    });
+    let body_plus_tail = profile.union(Body, &body, &tail);
+    body = profile.hull(Body, &body_plus_tail);
+    profile.renormalize(Body, &mut body);
+    body
+}
+
+fn generate_hull_torso(
+    input: &BirdGenInputs,
+    precision: Precision,
+    profile: &mut StageProfile,
+) -> CSGMesh {
+    let [neck, chest, bottom] = profile.time(Body, Primitives, || {
+        [
+            CSGMesh::sphere(
+                input.head_size as f64 / 2.0,
+                precision.sphere_segments() / 2 + 1,
+                precision.sphere_stacks() / 2 + 1,
+                None,
+            )
+            .translate(
+                0.0,
+                input.head_lateral_offset as f64,
+                input.head_level as f64,
+            ),
+            CSGMesh::sphere(
+                input.belly_size as f64 / 2.0,
+                precision.sphere_segments() + 2,
+                precision.sphere_stacks() + 2,
+                None,
+            )
+            .scale(
+                (input.belly_length / input.belly_size) as f64,
+                input.belly_fat as f64 / 100.0,
+                1.0,
+            )
+            .translate(input.head_to_belly as f64, 0.0, 0.0),
+            CSGMesh::sphere(
+                input.bottom_size as f64 / 2.0,
+                precision.sphere_segments() + 1,
+                precision.sphere_stacks() + 1,
+                None,
+            )
+            .translate(
+                (input.head_to_belly + input.belly_to_bottom) as f64,
+                0.0,
+                0.0,
+            ),
+        ]
+    });
+    let neck_plus_chest = profile.union(Body, &neck, &chest);
+    let body = profile.hull(Body, &neck_plus_chest);
+    let body_plus_bottom = profile.union(Body, &body, &bottom);
+    profile.hull(Body, &body_plus_bottom)
+}
 
-        body = body.difference(&cut_box);
+fn cut_base(body: CSGMesh, input: &BirdGenInputs, profile: &mut StageProfile) -> CSGMesh {
+    if input.base_flat <= -100.0 {
+        return body;
     }
-    body
+    profile.keep_input(Body, BaseCut, || {
+        StageInput::BaseCut(body.clone(), base_cut_box(input))
+    });
+    profile.time(Body, BaseCut, || body.difference(&base_cut_box(input)))
+}
+
+fn base_cut_box(input: &BirdGenInputs) -> CSGMesh {
+    let total_len =
+        input.beak_length + input.head_to_belly + input.belly_to_bottom + input.tail_length;
+
+    // Calculate the cut height (in OpenSCAD's z-axis, which becomes Bevy's y-axis after rotation)
+    let cut_height = (input.belly_size * (-1.5 + input.base_flat / 200.0)) as f64;
+
+    // Create a large cube to subtract from the bottom
+    // Little hacky with my positioning but idc
+    CSGMesh::cuboid(
+        (total_len * 4.0) as f64,
+        (total_len * 4.0) as f64,
+        input.belly_size as f64 + (total_len * 4.0) as f64,
+        None,
+    )
+    .translate(
+        -total_len as f64 * 2.0,
+        -total_len as f64 * 2.0,
+        cut_height - total_len as f64 * 4.0,
+    )
 }
 
 pub fn generate_bird_body_mesh(input: &BirdGenInputs) -> Mesh {
-    let body = generate_bird_body_csg_mesh(input);
+    generate_bird_body_mesh_with(input, &BirdMeshSettings::default())
+}
+
+pub fn generate_bird_body_mesh_with(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Mesh {
+    to_display_mesh(&generate_bird_body_csg_mesh_with(input, settings))
+}
+
+pub(crate) fn to_display_mesh(mesh: &CSGMesh) -> Mesh {
     // add the x axis rotation to account for y up world we're rocking with in bevy
-    body.rotate(-90.0, 180.0, 0.0).to_bevy_mesh()
+    mesh.rotate(-90.0, 180.0, 0.0).to_bevy_mesh()
 }
 
 /* From https://www.thingiverse.com/thing:139945/files
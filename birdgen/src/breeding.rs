@@ -0,0 +1,88 @@
+// Breeding children off a parent the same way the app does, minus the app
+// Every child gets its own rng seed, so a batch comes out the same no matter how it's split up (or parallelised)
+use crate::{
+    AttachedBird, BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, PrintabilityOptions,
+    PrintabilityReport, batch::map_birds,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+#[derive(Clone, Copy, Debug)]
+pub struct BreedingOptions {
+    // how far each mate gets pulled towards the parent before breeding (0 is a semi random bird, 1 is the parent)
+    pub min_mate_lerp: f32,
+    pub max_mate_lerp: f32,
+    // only keep children that'll print
+    pub printability: Option<PrintabilityOptions>,
+}
+
+impl Default for BreedingOptions {
+    fn default() -> Self {
+        BreedingOptions {
+            min_mate_lerp: 0.12,
+            max_mate_lerp: 0.82,
+            printability: None,
+        }
+    }
+}
+
+pub struct BredBird {
+    // the child that got built, after any neck fix
+    pub inputs: BirdGenInputs,
+    pub mate: BirdGenInputs,
+    pub meshes: BirdCSGMeshes,
+    // only with `printability` set
+    pub printability: Option<PrintabilityReport>,
+    pub attempts: usize,
+}
+
+impl BirdGenInputs {
+    pub fn breed_with_rng(
+        &self,
+        options: &BreedingOptions,
+        settings: &BirdMeshSettings,
+        rng: &mut impl Rng,
+    ) -> BredBird {
+        let mut mate = BirdGenInputs::get_semi_random_bird_with_rng(rng);
+        let (low, high) = (
+            options.min_mate_lerp.min(options.max_mate_lerp),
+            options.max_mate_lerp.max(options.min_mate_lerp),
+        );
+        mate.bird_lerp(self, rng.random_range(low..=high));
+        match &options.printability {
+            Some(printability) => {
+                let child = self.get_printable_child_with_rng(&mate, settings, printability, rng);
+                BredBird {
+                    inputs: child.inputs,
+                    mate,
+                    meshes: child.meshes,
+                    printability: Some(child.report),
+                    attempts: child.attempts,
+                }
+            }
+            None => {
+                // (quantised, so the child's seed builds the same mesh)
+                let child = self.get_child_with_rng(&mate, rng).quantised();
+                let child = AttachedBird::generate(&child, settings);
+                BredBird {
+                    inputs: child.inputs,
+                    mate,
+                    meshes: child.meshes,
+                    printability: None,
+                    attempts: 1,
+                }
+            }
+        }
+    }
+}
+
+// One child per rng seed (in parallel with the `parallel` feature)
+pub fn breed_birds(
+    parent: &BirdGenInputs,
+    rng_seeds: &[u64],
+    options: &BreedingOptions,
+    settings: &BirdMeshSettings,
+) -> Vec<BredBird> {
+    map_birds(rng_seeds, |rng_seed| {
+        parent.breed_with_rng(options, settings, &mut StdRng::seed_from_u64(*rng_seed))
+    })
+}
@@ -0,0 +1,373 @@
+// Brick built birds: a voxel bird turned into layers of standard 1xN and 2xN bricks, with a parts list
+// and an LDraw .ldr that LeoCAD, Studio, LDView and friends can open
+// Every voxel is one stud, and bricks are taller than they are wide (9.6mm to 8mm), so each layer takes
+// every 1.2th row of voxels. Layers get filled greedily, biggest brick first, running the other way
+// to the layer underneath and straddling as many of its bricks as they can so the bird holds together
+use crate::{BirdVoxels, VoxelMaterial};
+use std::fmt::Write;
+
+// Footprint in studs, length along the brick
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BrickPart {
+    pub width: usize,
+    pub length: usize,
+    // LDraw part file
+    pub ldraw: &'static str,
+}
+
+impl BrickPart {
+    pub const STANDARD: [BrickPart; 11] = [
+        BrickPart::new(1, 1, "3005.dat"),
+        BrickPart::new(1, 2, "3004.dat"),
+        BrickPart::new(1, 3, "3622.dat"),
+        BrickPart::new(1, 4, "3010.dat"),
+        BrickPart::new(1, 6, "3009.dat"),
+        BrickPart::new(1, 8, "3008.dat"),
+        BrickPart::new(2, 2, "3003.dat"),
+        BrickPart::new(2, 3, "3002.dat"),
+        BrickPart::new(2, 4, "3001.dat"),
+        BrickPart::new(2, 6, "2456.dat"),
+        BrickPart::new(2, 8, "3007.dat"),
+    ];
+
+    const fn new(width: usize, length: usize, ldraw: &'static str) -> Self {
+        BrickPart {
+            width,
+            length,
+            ldraw,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("Brick {} x {}", self.width, self.length)
+    }
+
+    pub fn studs(&self) -> usize {
+        self.width * self.length
+    }
+}
+
+// One of LDraw's colours, which is what brick tools (and the parts list) go by
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LDrawColor {
+    pub code: u32,
+    pub name: &'static str,
+    pub rgb: [u8; 3],
+}
+
+// the everyday solid colours
+const LDRAW_COLORS: [LDrawColor; 18] = [
+    LDrawColor::new(0, "Black", [27, 42, 52]),
+    LDrawColor::new(1, "Blue", [30, 90, 168]),
+    LDrawColor::new(2, "Green", [0, 133, 43]),
+    LDrawColor::new(4, "Red", [180, 0, 0]),
+    LDrawColor::new(5, "Dark Pink", [211, 53, 157]),
+    LDrawColor::new(10, "Bright Green", [88, 171, 65]),
+    LDrawColor::new(14, "Yellow", [250, 200, 10]),
+    LDrawColor::new(15, "White", [244, 244, 244]),
+    LDrawColor::new(19, "Tan", [228, 205, 158]),
+    LDrawColor::new(25, "Orange", [214, 121, 35]),
+    LDrawColor::new(28, "Dark Tan", [170, 125, 85]),
+    LDrawColor::new(70, "Reddish Brown", [95, 49, 9]),
+    LDrawColor::new(71, "Light Bluish Grey", [150, 150, 150]),
+    LDrawColor::new(72, "Dark Bluish Grey", [100, 100, 100]),
+    LDrawColor::new(73, "Medium Blue", [115, 150, 200]),
+    LDrawColor::new(191, "Bright Light Orange", [252, 172, 0]),
+    LDrawColor::new(226, "Bright Light Yellow", [255, 236, 108]),
+    LDrawColor::new(320, "Dark Red", [114, 0, 18]),
+];
+
+impl LDrawColor {
+    const fn new(code: u32, name: &'static str, rgb: [u8; 3]) -> Self {
+        LDrawColor { code, name, rgb }
+    }
+
+    pub fn closest(rgb: [u8; 3]) -> Self {
+        *LDRAW_COLORS
+            .iter()
+            .min_by_key(|color| {
+                (0..3)
+                    .map(|i| (rgb[i] as i32 - color.rgb[i] as i32).pow(2))
+                    .sum::<i32>()
+            })
+            .expect("there are colours")
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BrickOptions {
+    // longest brick to use (from 1, 2, 3, 4, 6 and 8)
+    pub max_length: usize,
+    // 2xN bricks as well as 1xN
+    pub two_wide: bool,
+}
+
+impl Default for BrickOptions {
+    fn default() -> Self {
+        BrickOptions {
+            max_length: 8,
+            two_wide: true,
+        }
+    }
+}
+
+// `x` and `z` are the stud it starts at (its smallest corner), layer 0 is the bottom
+#[derive(Clone, Copy, Debug)]
+pub struct Brick {
+    pub part: BrickPart,
+    pub material: VoxelMaterial,
+    pub layer: usize,
+    pub x: usize,
+    pub z: usize,
+    // otherwise its length runs along x
+    pub along_z: bool,
+}
+
+impl Brick {
+    // studs along x and z
+    pub fn footprint(&self) -> [usize; 2] {
+        if self.along_z {
+            [self.part.width, self.part.length]
+        } else {
+            [self.part.length, self.part.width]
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BrickCount {
+    pub part: BrickPart,
+    pub color: LDrawColor,
+    pub count: usize,
+}
+
+// Same orientation as the voxels: x along the bird, z across, layers going up
+#[derive(Clone, Debug)]
+pub struct BrickModel {
+    // studs along x and z
+    pub studs: [usize; 2],
+    pub layers: usize,
+    // bottom layer first
+    pub bricks: Vec<Brick>,
+    // by material, like the voxels' colours
+    pub colors: [LDrawColor; 3],
+}
+
+const STUD_MM: f64 = 8.0;
+const BRICK_HEIGHT_MM: f64 = 9.6;
+// LDraw units: 20 a stud, 24 a brick
+const LDU_STUD: i64 = 20;
+const LDU_BRICK: i64 = 24;
+
+impl BrickModel {
+    pub fn color(&self, material: VoxelMaterial) -> LDrawColor {
+        self.colors[material.index() as usize - 1]
+    }
+
+    // how big it comes out built, x y z (mm)
+    pub fn size_mm(&self) -> [f64; 3] {
+        [
+            self.studs[0] as f64 * STUD_MM,
+            self.layers as f64 * BRICK_HEIGHT_MM,
+            self.studs[1] as f64 * STUD_MM,
+        ]
+    }
+
+    // Every different brick in every colour, most common colour first, then biggest bricks first
+    pub fn parts_list(&self) -> Vec<BrickCount> {
+        let mut list: Vec<BrickCount> = vec![];
+        for brick in &self.bricks {
+            let color = self.color(brick.material);
+            match list
+                .iter_mut()
+                .find(|counted| counted.part == brick.part && counted.color == color)
+            {
+                Some(counted) => counted.count += 1,
+                None => list.push(BrickCount {
+                    part: brick.part,
+                    color,
+                    count: 1,
+                }),
+            }
+        }
+        let color_total = |color: LDrawColor| {
+            list.iter()
+                .filter(|counted| counted.color == color)
+                .map(|counted| counted.count)
+                .sum::<usize>()
+        };
+        let totals: Vec<usize> = list
+            .iter()
+            .map(|counted| color_total(counted.color))
+            .collect();
+        let mut list: Vec<(usize, BrickCount)> = totals.into_iter().zip(list).collect();
+        list.sort_by(|(a_total, a), (b_total, b)| {
+            b_total
+                .cmp(a_total)
+                .then(a.color.code.cmp(&b.color.code))
+                .then(b.part.studs().cmp(&a.part.studs()))
+                .then(b.part.width.cmp(&a.part.width))
+        });
+        list.into_iter().map(|(_, counted)| counted).collect()
+    }
+
+    pub fn to_parts_csv(&self) -> String {
+        let mut csv = String::from("part,ldraw_part,colour,ldraw_colour,count\n");
+        for counted in self.parts_list() {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                counted.part.name(),
+                counted.part.ldraw.trim_end_matches(".dat"),
+                counted.color.name,
+                counted.color.code,
+                counted.count
+            );
+        }
+        csv
+    }
+
+    // A layer per build step, centred on the origin (LDraw's y points down, so it's flipped over x)
+    pub fn to_ldr(&self, name: &str) -> String {
+        let mut ldr = String::new();
+        let _ = writeln!(ldr, "0 {name}");
+        let _ = writeln!(ldr, "0 Name: {name}.ldr");
+        let _ = writeln!(ldr, "0 Author: birdgen");
+        let _ = writeln!(ldr, "0 !LDRAW_ORG Unofficial_Model");
+        let _ = writeln!(
+            ldr,
+            "0 // {} bricks in {} layers, one layer per step",
+            self.bricks.len(),
+            self.layers
+        );
+        let [width, depth] = self.studs.map(|studs| studs as i64);
+        for layer in 0..self.layers {
+            let _ = writeln!(ldr, "0 // layer {}", layer + 1);
+            for brick in self.bricks.iter().filter(|brick| brick.layer == layer) {
+                let [size_x, size_z] = brick.footprint().map(|studs| studs as i64);
+                // parts sit with their top face on the origin, length along x
+                let x = (2 * brick.x as i64 + size_x - width) * LDU_STUD / 2;
+                let y = -(layer as i64 + 1) * LDU_BRICK;
+                let z = -(2 * brick.z as i64 + size_z - depth) * LDU_STUD / 2;
+                let rotation = if brick.along_z {
+                    "0 0 1 0 1 0 -1 0 0"
+                } else {
+                    "1 0 0 0 1 0 0 0 1"
+                };
+                let _ = writeln!(
+                    ldr,
+                    "1 {} {x} {y} {z} {rotation} {}",
+                    self.color(brick.material).code,
+                    brick.part.ldraw
+                );
+            }
+            let _ = writeln!(ldr, "0 STEP");
+        }
+        ldr
+    }
+}
+
+impl BirdVoxels {
+    pub fn to_bricks(&self, options: &BrickOptions) -> BrickModel {
+        build_bricks(self, options)
+    }
+}
+
+pub fn build_bricks(voxels: &BirdVoxels, options: &BrickOptions) -> BrickModel {
+    let [size_x, height, size_z] = voxels.size;
+    let colors = VoxelMaterial::ALL.map(|material| LDrawColor::closest(voxels.color(material)));
+    let layers = if height == 0 {
+        0
+    } else {
+        ((height as f64 * STUD_MM / BRICK_HEIGHT_MM).round() as usize).max(1)
+    };
+    let mut parts: Vec<BrickPart> = BrickPart::STANDARD
+        .into_iter()
+        .filter(|part| part.length <= options.max_length.max(1))
+        .filter(|part| options.two_wide || part.width == 1)
+        .collect();
+    parts.sort_by_key(|part| std::cmp::Reverse((part.studs(), part.width)));
+
+    let mut bricks: Vec<Brick> = vec![];
+    // which brick covers each stud of the layer below
+    let mut below: Vec<Option<usize>> = vec![None; size_x * size_z];
+    for layer in 0..layers {
+        let y = (((layer as f64 + 0.5) * BRICK_HEIGHT_MM / STUD_MM) as usize).min(height - 1);
+        let material = |x: usize, z: usize| voxels.get(x, y, z);
+        let mut owner: Vec<Option<usize>> = vec![None; size_x * size_z];
+        // odd layers run the other way, so joints don't line up from one layer to the next
+        let crosswise = layer % 2 == 1;
+        let cells: Vec<(usize, usize)> = if crosswise {
+            (0..size_x)
+                .flat_map(|x| (0..size_z).map(move |z| (x, z)))
+                .collect()
+        } else {
+            (0..size_z)
+                .flat_map(|z| (0..size_x).map(move |x| (x, z)))
+                .collect()
+        };
+        for (x, z) in cells {
+            let voxel = material(x, z);
+            if voxel == 0 || owner[z * size_x + x].is_some() {
+                continue;
+            }
+            // everything before this stud in scan order is taken, so it's the brick's corner
+            let mut best: Option<((usize, usize, bool), BrickPart, bool)> = None;
+            for part in &parts {
+                for along_z in [false, true] {
+                    if along_z && part.width == part.length {
+                        continue;
+                    }
+                    let [fx, fz] = if along_z {
+                        [part.width, part.length]
+                    } else {
+                        [part.length, part.width]
+                    };
+                    if x + fx > size_x || z + fz > size_z {
+                        continue;
+                    }
+                    let studs = || (z..z + fz).flat_map(|z| (x..x + fx).map(move |x| (x, z)));
+                    if !studs()
+                        .all(|(x, z)| material(x, z) == voxel && owner[z * size_x + x].is_none())
+                    {
+                        continue;
+                    }
+                    let mut straddled: Vec<usize> =
+                        studs().filter_map(|(x, z)| below[z * size_x + x]).collect();
+                    straddled.sort_unstable();
+                    straddled.dedup();
+                    let score = (part.studs(), straddled.len(), along_z == crosswise);
+                    if best.is_none_or(|(best, _, _)| score > best) {
+                        best = Some((score, *part, along_z));
+                    }
+                }
+            }
+            // (a 1x1 always fits)
+            let Some((_, part, along_z)) = best else {
+                continue;
+            };
+            let brick = Brick {
+                part,
+                material: VoxelMaterial::ALL[voxel as usize - 1],
+                layer,
+                x,
+                z,
+                along_z,
+            };
+            let [fx, fz] = brick.footprint();
+            for z in z..z + fz {
+                for x in x..x + fx {
+                    owner[z * size_x + x] = Some(bricks.len());
+                }
+            }
+            bricks.push(brick);
+        }
+        below = owner;
+    }
+    BrickModel {
+        studs: [size_x, size_z],
+        layers,
+        bricks,
+        colors,
+    }
+}
@@ -0,0 +1,156 @@
+// Holding on to meshes we've already built, so picking a bird we've seen before is instant
+use crate::{
+    BirdCSGMeshes, BirdGenInputTypes, BirdGenInputs, BirdMeshSettings, BirdMetrics, BodyMode,
+    FilamentOptions, NeckFix, Precision, bird::to_display_mesh,
+};
+use bevy::{ecs::resource::Resource, mesh::Mesh};
+use std::collections::VecDeque;
+
+// inputs closer together than this count as the same bird
+const INPUT_QUANTUM: f32 = 0.01;
+const DEFAULT_CAPACITY: usize = 32;
+
+// Display-ready head and body for one bird
+// (measured on the way through, with default filament, so the app doesn't need the CSG again)
+#[derive(Clone)]
+pub struct BirdMeshes {
+    pub head: Mesh,
+    pub body: Mesh,
+    pub metrics: BirdMetrics,
+}
+
+impl BirdMeshes {
+    // no neck fixing, same as `BirdCSGMeshes::generate`
+    pub fn generate(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Self {
+        BirdMeshes::from_csg(&BirdCSGMeshes::generate(input, settings))
+    }
+
+    pub fn from_csg(csg: &BirdCSGMeshes) -> Self {
+        BirdMeshes {
+            head: to_display_mesh(&csg.head),
+            body: to_display_mesh(&csg.body),
+            metrics: csg.metrics(&FilamentOptions::default()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeshCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+// Least recently used cache of bird meshes, the most recently used entry lives at the back
+#[derive(Resource)]
+pub struct MeshCache {
+    capacity: usize,
+    entries: VecDeque<(MeshCacheKey, BirdMeshes)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for MeshCache {
+    fn default() -> Self {
+        MeshCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl MeshCache {
+    pub fn new(capacity: usize) -> Self {
+        MeshCache {
+            capacity: capacity.max(1),
+            entries: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(
+        &mut self,
+        input: &BirdGenInputs,
+        settings: &BirdMeshSettings,
+    ) -> Option<BirdMeshes> {
+        let key = MeshCacheKey::new(input, settings);
+        let Some(index) = self.entries.iter().position(|(k, _)| *k == key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        let entry = self.entries.remove(index)?;
+        let meshes = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(meshes)
+    }
+
+    pub fn insert(
+        &mut self,
+        input: &BirdGenInputs,
+        settings: &BirdMeshSettings,
+        meshes: BirdMeshes,
+    ) {
+        let key = MeshCacheKey::new(input, settings);
+        self.entries.retain(|(k, _)| *k != key);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, meshes));
+    }
+
+    pub fn get_or_generate(
+        &mut self,
+        input: &BirdGenInputs,
+        settings: &BirdMeshSettings,
+    ) -> BirdMeshes {
+        if let Some(meshes) = self.get(input, settings) {
+            return meshes;
+        }
+        let meshes = BirdMeshes::generate(input, settings);
+        self.insert(input, settings, meshes.clone());
+        meshes
+    }
+
+    pub fn stats(&self) -> MeshCacheStats {
+        MeshCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// Every input (snapped to a grid so float noise doesn't matter) plus the mesh settings
+// Birds with equal keys get the same meshes, so this is what the cache compares, not a hash of it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MeshCacheKey {
+    inputs: [i64; BirdGenInputTypes::ALL.len()],
+    // the smooth body's blend radius, none for hulls
+    blend_radius: Option<i64>,
+    precision: Precision,
+    neck_fix: Option<NeckFix>,
+}
+
+impl MeshCacheKey {
+    pub fn new(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Self {
+        MeshCacheKey {
+            inputs: BirdGenInputTypes::ALL
+                .map(|input_type| quantise(input.get_input_value_for_type(&input_type))),
+            blend_radius: match settings.body_mode {
+                BodyMode::Hull => None,
+                BodyMode::Smooth { blend_radius } => Some(quantise(blend_radius)),
+            },
+            precision: settings.precision,
+            neck_fix: settings.neck_fix,
+        }
+    }
+}
+
+fn quantise(value: f32) -> i64 {
+    (value / INPUT_QUANTUM).round() as i64
+}
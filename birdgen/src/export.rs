@@ -0,0 +1,159 @@
+// Turning a bird into something you can send to a printer
+use crate::{
+    AttachedBird, BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, CSGMesh, NeckReport,
+    geometry::mesh_triangles,
+    hollow::{HollowOptions, HollowReport, plan_hollowing},
+    ornament::{HangerOptions, add_hanger},
+    parts::write_3mf,
+};
+use csgrs::csg::CSG;
+use std::{collections::HashMap, fmt::Write as _};
+
+// Extras to bolt onto the bird when exporting, all off by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExportOptions {
+    pub mesh: BirdMeshSettings,
+    pub hanger: Option<HangerOptions>,
+    // hollow out the body (head stays solid, it's small)
+    pub hollow: Option<HollowOptions>,
+}
+
+// Head and body meshes with any export options applied, still in the generation frame
+pub struct BirdExport {
+    // the bird these meshes are of, after any neck fix (seeds and file names want this one)
+    pub inputs: BirdGenInputs,
+    pub head: CSGMesh,
+    pub body: CSGMesh,
+    pub hollow_report: Option<HollowReport>,
+    // what it took to keep the head on (only with `mesh.neck_fix` set)
+    pub neck_report: Option<NeckReport>,
+}
+
+impl BirdExport {
+    pub fn new(input: &BirdGenInputs, options: &ExportOptions) -> Result<Self, String> {
+        let AttachedBird {
+            inputs,
+            meshes: BirdCSGMeshes { mut head, mut body },
+            report,
+        } = AttachedBird::generate(input, &options.mesh);
+        let neck_report = options.mesh.neck_fix.map(|_| report);
+        // plan the cavity off the plain body, so a loop on the back doesn't end up with a loop-shaped hole under it
+        let hollowing = match &options.hollow {
+            Some(hollow) => Some(plan_hollowing(&body, hollow)?),
+            None => None,
+        };
+        if let Some(hanger) = &options.hanger {
+            (head, body) = add_hanger(head, body, &inputs, hanger)?;
+        }
+        let mut hollow_report = None;
+        if let Some(hollowing) = hollowing {
+            let (hollowed, report) = hollowing.apply(body);
+            body = hollowed;
+            hollow_report = Some(report);
+        }
+        Ok(BirdExport {
+            inputs,
+            head,
+            body,
+            hollow_report,
+            neck_report,
+        })
+    }
+
+    // already built meshes (of `inputs`), exported as they are
+    pub fn from_meshes(inputs: BirdGenInputs, meshes: BirdCSGMeshes) -> Self {
+        BirdExport {
+            inputs,
+            head: meshes.head,
+            body: meshes.body,
+            hollow_report: None,
+            neck_report: None,
+        }
+    }
+
+    pub fn to_stl(&self, name: &str) -> Vec<u8> {
+        combine_stl(&self.body, &self.head, name).into_bytes()
+    }
+
+    // body and head as two objects
+    pub fn to_3mf(&self, name: &str) -> Result<Vec<u8>, String> {
+        write_3mf(name, &[("body", &self.body), ("head", &self.head)])
+    }
+
+    // Wavefront OBJ, y up like the STL
+    pub fn to_obj(&self, name: &str) -> Vec<u8> {
+        let mut obj = format!("# {name}\n");
+        // OBJ indices count up across the whole file, starting from 1
+        let mut vertex_count = 0;
+        for (object_name, mesh) in [("body", &self.body), ("head", &self.head)] {
+            let _ = writeln!(obj, "o {object_name}");
+            let mut vertex_ids: HashMap<[u64; 3], usize> = HashMap::new();
+            let mut faces = String::new();
+            for triangle in mesh_triangles(&to_export_orientation(mesh)) {
+                let [a, b, c] = triangle.map(|v| {
+                    let key = [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+                    *vertex_ids.entry(key).or_insert_with(|| {
+                        let _ = writeln!(obj, "v {} {} {}", v.x, v.y, v.z);
+                        vertex_count += 1;
+                        vertex_count
+                    })
+                });
+                if a == b || b == c || a == c {
+                    continue;
+                }
+                let _ = writeln!(faces, "f {a} {b} {c}");
+            }
+            obj.push_str(&faces);
+        }
+        obj.into_bytes()
+    }
+
+    // everything scaled about the origin (1.0 is the bird's natural size in mm)
+    pub fn scaled(self, factor: f64) -> Self {
+        BirdExport {
+            head: self.head.scale(factor, factor, factor),
+            body: self.body.scale(factor, factor, factor),
+            hollow_report: self.hollow_report.map(|report| report.scaled(factor)),
+            ..self
+        }
+    }
+}
+
+impl BirdGenInputs {
+    pub fn get_stl_with(&self, options: &ExportOptions) -> Result<Vec<u8>, std::io::Error> {
+        let export = BirdExport::new(self, options).map_err(std::io::Error::other)?;
+        Ok(export.to_stl(format!("coolbird-{}", export.inputs.get_bird_seed_string()).as_str()))
+    }
+}
+
+// rotate STL's - idk man but when I uploaded to shapeways it thought the bird was on it's side, switching that up here
+pub(crate) fn to_export_orientation(mesh: &CSGMesh) -> CSGMesh {
+    mesh.rotate(-90., 0., 0.)
+}
+
+pub(crate) fn combine_stl(body: &CSGMesh, head: &CSGMesh, name: &str) -> String {
+    let body_stl_str = to_export_orientation(body).to_stl_ascii(name);
+    let head_stl_str = to_export_orientation(head).to_stl_ascii("head");
+    // grab triangles from head and add to body, manually editing the string of the STL
+    // (does feel a bit hacky - but it does maintain head and body triangles better)
+    // much bigger than the binary format stl tho
+    let mut result = body_stl_str.clone();
+
+    // Remove the "endsolid" line from body
+    if let Some(pos) = result.rfind("endsolid") {
+        result.truncate(pos);
+    }
+
+    // Extract facets from head (between "solid" line and "endsolid" line)
+    let facets_start = head_stl_str.find("facet").unwrap_or(head_stl_str.len());
+    let facets_end = head_stl_str.rfind("endsolid").unwrap_or(head_stl_str.len());
+    // (an empty head has no facets at all, so nothing to grab)
+    let head_facets = head_stl_str
+        .get(facets_start..facets_end)
+        .unwrap_or_default();
+
+    // Combine: body (without endsolid) + head facets + endsolid
+    result.push_str(head_facets);
+    result.push_str("endsolid bird\n");
+    result
+}
@@ -0,0 +1,192 @@
+// Small helpers for poking at the triangles of a generated mesh
+// (csgrs polygons are convex, so a fan is enough to triangulate them)
+use crate::CSGMesh;
+use bevy::math::{DQuat, DVec3, EulerRot};
+use std::collections::HashMap;
+
+pub type Triangle = [DVec3; 3];
+
+// hits closer than this are treated as the same hit (rays crossing a shared edge)
+const HIT_EPSILON: f64 = 1e-7;
+// corners closer than this are the same corner
+const WELD_EPSILON: f64 = 1e-4;
+
+pub fn mesh_triangles(mesh: &CSGMesh) -> Vec<Triangle> {
+    let mut triangles = Vec::new();
+    for polygon in &mesh.polygons {
+        let points: Vec<DVec3> = polygon
+            .vertices
+            .iter()
+            .map(|vertex| DVec3::new(vertex.pos.x, vertex.pos.y, vertex.pos.z))
+            .collect();
+        for i in 1..points.len().saturating_sub(1) {
+            triangles.push([points[0], points[i], points[i + 1]]);
+        }
+    }
+    triangles
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl Bounds {
+    pub fn size(&self) -> DVec3 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> DVec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+pub fn bounds(triangles: &[Triangle]) -> Bounds {
+    let mut min = DVec3::splat(f64::INFINITY);
+    let mut max = DVec3::splat(f64::NEG_INFINITY);
+    for point in triangles.iter().flatten() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    Bounds { min, max }
+}
+
+// Moller-Trumbore, returns the distance along `dir` if the ray hits the triangle
+fn ray_triangle(origin: DVec3, dir: DVec3, triangle: &Triangle) -> Option<f64> {
+    let edge1 = triangle[1] - triangle[0];
+    let edge2 = triangle[2] - triangle[0];
+    let p = dir.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - triangle[0];
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(q) * inv_det;
+    (t >= 0.0).then_some(t)
+}
+
+// Sorted distances along the ray to every surface it crosses
+pub fn ray_hits(triangles: &[Triangle], origin: DVec3, dir: DVec3) -> Vec<f64> {
+    let dir = dir.normalize();
+    let mut hits: Vec<f64> = triangles
+        .iter()
+        .filter_map(|triangle| ray_triangle(origin, dir, triangle))
+        .collect();
+    hits.sort_by(|a, b| a.total_cmp(b));
+    hits.dedup_by(|a, b| (*a - *b).abs() < HIT_EPSILON);
+    hits
+}
+
+// (enter, exit) distances for each stretch of solid material along the ray,
+// assumes the ray starts outside of the mesh
+pub fn ray_spans(triangles: &[Triangle], origin: DVec3, dir: DVec3) -> Vec<(f64, f64)> {
+    ray_hits(triangles, origin, dir)
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+pub fn is_inside(triangles: &[Triangle], point: DVec3) -> bool {
+    // slightly skewed direction so we don't run straight down triangle edges
+    let dir = DVec3::new(0.5773, 0.5774, 0.5775);
+    ray_hits(triangles, point, dir).len() % 2 == 1
+}
+
+// How far we can travel from `point` (inside the mesh) along `dir` before leaving the solid
+pub fn distance_to_surface(triangles: &[Triangle], point: DVec3, dir: DVec3) -> Option<f64> {
+    ray_hits(triangles, point, dir).first().copied()
+}
+
+pub fn volume(triangles: &[Triangle]) -> f64 {
+    triangles
+        .iter()
+        .map(|[a, b, c]| a.dot(b.cross(*c)) / 6.0)
+        .sum::<f64>()
+        .abs()
+}
+
+pub fn surface_area(triangles: &[Triangle]) -> f64 {
+    triangles
+        .iter()
+        .map(|[a, b, c]| (*b - *a).cross(*c - *a).length() / 2.0)
+        .sum()
+}
+
+// Center of mass of the solid, assuming it's all the same stuff all the way through
+pub fn centroid(triangles: &[Triangle]) -> DVec3 {
+    let mut weighted = DVec3::ZERO;
+    let mut total = 0.0;
+    for [a, b, c] in triangles {
+        // signed volume of the tetrahedron from the origin to this face
+        let tet_volume = a.dot(b.cross(*c)) / 6.0;
+        weighted += (*a + *b + *c) / 4.0 * tet_volume;
+        total += tet_volume;
+    }
+    if total.abs() < f64::EPSILON {
+        return bounds(triangles).center();
+    }
+    weighted / total
+}
+
+// How many separate pieces there are, counting triangles that share a corner as connected
+pub fn shell_count(triangles: &[Triangle]) -> usize {
+    let mut corners: HashMap<[i64; 3], usize> = HashMap::new();
+    let mut parents: Vec<usize> = (0..triangles.len()).collect();
+    for (index, triangle) in triangles.iter().enumerate() {
+        for point in triangle {
+            let key = (*point / WELD_EPSILON).round().as_i64vec3().to_array();
+            match corners.get(&key) {
+                Some(&other) => {
+                    let (a, b) = (
+                        find_root(&mut parents, index),
+                        find_root(&mut parents, other),
+                    );
+                    parents[a] = b;
+                }
+                None => {
+                    corners.insert(key, index);
+                }
+            }
+        }
+    }
+    (0..triangles.len())
+        .filter(|&index| find_root(&mut parents, index) == index)
+        .count()
+}
+
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+// Same rotation csgrs applies for `.rotate(x, y, z)` (nalgebra euler angles, degrees),
+// handy for following a primitive through the bird's transforms
+pub fn csg_rotation(x_deg: f64, y_deg: f64, z_deg: f64) -> DQuat {
+    DQuat::from_euler(
+        EulerRot::ZYX,
+        z_deg.to_radians(),
+        y_deg.to_radians(),
+        x_deg.to_radians(),
+    )
+}
@@ -0,0 +1,256 @@
+// Hollowing birds out for resin printing
+// The inner shell is a shrunk copy of the body, first scaled per axis so the walls at the extremes of
+// the bounding box match the requested thickness. That isn't guaranteed to stay inside the body though:
+// squashing one axis more than another drags thin slanted bits (like the tail) sideways out of the
+// walls, and smooth bodies dip in between the blobs. So every cavity gets checked against the body,
+// if it breaks out we fall back to scaling evenly about the center of mass (which stays inside anything
+// convex, like the hulled body), and we keep shrinking while the walls are too thin anywhere
+// If none of that gives a cavity that's all inside the body, hollowing fails rather than handing back
+// a bird with holes in its walls
+use crate::{
+    CSGMesh, SPHERE_SEGMENTS,
+    geometry::{
+        Triangle, bounds, centroid, distance_to_surface, is_inside, mesh_triangles, ray_spans,
+        volume,
+    },
+};
+use bevy::math::DVec3;
+use csgrs::csg::CSG;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HollowOptions {
+    // how thick the remaining shell should be
+    pub wall_thickness: f32,
+    // how many drain holes to punch through the floor (0 for none)
+    pub drain_holes: usize,
+    pub drain_hole_diameter: f32,
+}
+
+impl Default for HollowOptions {
+    fn default() -> Self {
+        HollowOptions {
+            wall_thickness: 2.0,
+            drain_holes: 2,
+            drain_hole_diameter: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HollowReport {
+    pub requested_wall_thickness: f64,
+    // thinnest wall we measured on the final shell
+    pub achieved_wall_thickness: f64,
+    pub drain_holes: usize,
+    pub solid_volume: f64,
+    pub hollow_volume: f64,
+    // (in cubic export units, so mm^3)
+    pub resin_saved: f64,
+}
+
+impl HollowReport {
+    // lengths by `factor`, volumes by its cube
+    pub fn scaled(&self, factor: f64) -> Self {
+        let cubed = factor.powi(3);
+        HollowReport {
+            requested_wall_thickness: self.requested_wall_thickness * factor,
+            achieved_wall_thickness: self.achieved_wall_thickness * factor,
+            drain_holes: self.drain_holes,
+            solid_volume: self.solid_volume * cubed,
+            hollow_volume: self.hollow_volume * cubed,
+            resin_saved: self.resin_saved * cubed,
+        }
+    }
+}
+
+// The bits we're going to cut out of the body
+pub struct Hollowing {
+    pub cavity: CSGMesh,
+    pub drains: Vec<CSGMesh>,
+    pub requested_wall_thickness: f64,
+    pub achieved_wall_thickness: f64,
+}
+
+// a few rounds of "measure the walls, shrink the cavity a bit more" is plenty
+const HOLLOW_REFINE_STEPS: usize = 4;
+// measuring every face is slow and doesn't tell us much more
+const WALL_SAMPLES: usize = 1500;
+
+pub fn plan_hollowing(body: &CSGMesh, options: &HollowOptions) -> Result<Hollowing, String> {
+    let requested = options.wall_thickness as f64;
+    let body_triangles = mesh_triangles(body);
+
+    let mut offset = requested;
+    let mut even = false;
+    let mut steps = 0;
+    let mut best: Option<(CSGMesh, f64)> = None;
+    while steps < HOLLOW_REFINE_STEPS {
+        let Some(cavity) = shrunk_shell(body, &body_triangles, offset, even) else {
+            break;
+        };
+        let achieved = thinnest_wall(&body_triangles, &mesh_triangles(&cavity))?;
+        if achieved <= 0.0 && !even {
+            // shrinking per axis more only drags it further out, start again scaling evenly
+            even = true;
+            offset = requested;
+            continue;
+        }
+        steps += 1;
+        let good_enough = achieved >= requested * 0.95;
+        // a cavity that breaks through the wall is no use at all, even as a fallback
+        if achieved > 0.0 {
+            best = Some((cavity, achieved));
+        }
+        if good_enough {
+            break;
+        }
+        // walls came out thin somewhere, push the cavity in further
+        // (they thin out about in step with the offset, so scale it by how far off we were)
+        offset *= (requested / achieved.max(requested * 0.25)).min(4.0);
+    }
+    let (cavity, achieved) = best.ok_or("bird is too thin to hollow at that wall thickness")?;
+
+    let drains = drain_holes(&body_triangles, &mesh_triangles(&cavity), options);
+    Ok(Hollowing {
+        cavity,
+        drains,
+        requested_wall_thickness: requested,
+        achieved_wall_thickness: achieved,
+    })
+}
+
+impl Hollowing {
+    pub fn apply(&self, mesh: CSGMesh) -> (CSGMesh, HollowReport) {
+        let solid_volume = volume(&mesh_triangles(&mesh));
+        let mut hollowed = mesh.difference(&self.cavity);
+        for drain in &self.drains {
+            hollowed = hollowed.difference(drain);
+        }
+        hollowed.renormalize();
+        let hollow_volume = volume(&mesh_triangles(&hollowed));
+        let report = HollowReport {
+            requested_wall_thickness: self.requested_wall_thickness,
+            achieved_wall_thickness: self.achieved_wall_thickness,
+            drain_holes: self.drains.len(),
+            solid_volume,
+            hollow_volume,
+            resin_saved: solid_volume - hollow_volume,
+        };
+        (hollowed, report)
+    }
+}
+
+pub fn hollow_body(
+    body: CSGMesh,
+    options: &HollowOptions,
+) -> Result<(CSGMesh, HollowReport), String> {
+    let hollowing = plan_hollowing(&body, options)?;
+    Ok(hollowing.apply(body))
+}
+
+// `even` scales every axis by the same amount (the smallest) about the center of mass,
+// otherwise each axis gets its own scale about the middle of the bounding box
+fn shrunk_shell(
+    body: &CSGMesh,
+    body_triangles: &[Triangle],
+    offset: f64,
+    even: bool,
+) -> Option<CSGMesh> {
+    let body_bounds = bounds(body_triangles);
+    let size = body_bounds.size();
+    let inner_size = size - DVec3::splat(offset * 2.0);
+    if inner_size.min_element() <= 0.0 {
+        return None;
+    }
+    let (center, scale) = if even {
+        (
+            centroid(body_triangles),
+            DVec3::splat((inner_size / size).min_element()),
+        )
+    } else {
+        (body_bounds.center(), inner_size / size)
+    };
+    Some(
+        body.translate(-center.x, -center.y, -center.z)
+            .scale(scale.x, scale.y, scale.z)
+            .translate(center.x, center.y, center.z),
+    )
+}
+
+// Shoot a ray out of (a sample of) the cavity's faces and see how soon we hit the outside
+// 0 if any of the cavity is outside the body, since a ray from out there still hits the body
+// further along and would measure a wall that isn't there
+fn thinnest_wall(outer: &[Triangle], cavity: &[Triangle]) -> Result<f64, String> {
+    let mut vertices = HashSet::new();
+    for point in cavity.iter().flatten() {
+        let key = [point.x.to_bits(), point.y.to_bits(), point.z.to_bits()];
+        if vertices.insert(key) && !is_inside(outer, *point) {
+            return Ok(0.0);
+        }
+    }
+    let step = (cavity.len() / WALL_SAMPLES).max(1);
+    cavity
+        .iter()
+        .step_by(step)
+        .filter_map(|[a, b, c]| {
+            let normal = (*b - *a).cross(*c - *a).try_normalize()?;
+            let centroid = (*a + *b + *c) / 3.0;
+            distance_to_surface(outer, centroid, normal)
+        })
+        .reduce(f64::min)
+        .ok_or_else(|| "couldn't measure the walls of the hollowed body".to_string())
+}
+
+// Holes straight up through the floor into the cavity, spread out along the bird's length
+fn drain_holes(outer: &[Triangle], cavity: &[Triangle], options: &HollowOptions) -> Vec<CSGMesh> {
+    if options.drain_holes == 0 {
+        return vec![];
+    }
+    let radius = options.drain_hole_diameter as f64 / 2.0;
+    let outer_bounds = bounds(outer);
+    let cavity_bounds = bounds(cavity);
+    let below = outer_bounds.min.z - 1.0;
+
+    // a hole only counts if the whole thing (not just the middle) opens into the cavity
+    let opens_into_cavity = |x: f64, y: f64| -> Option<f64> {
+        let mut floor_top = f64::NEG_INFINITY;
+        for (dx, dy) in [
+            (0.0, 0.0),
+            (radius, 0.0),
+            (-radius, 0.0),
+            (0.0, radius),
+            (0.0, -radius),
+        ] {
+            let origin = DVec3::new(x + dx, y + dy, below);
+            // has to go through the floor and come out in the cavity
+            let (floor_enter, _) = *ray_spans(outer, origin, DVec3::Z).first()?;
+            let (cavity_enter, _) = *ray_spans(cavity, origin, DVec3::Z).first()?;
+            if cavity_enter <= floor_enter {
+                return None;
+            }
+            floor_top = floor_top.max(below + cavity_enter);
+        }
+        Some(floor_top)
+    };
+
+    let center = cavity_bounds.center();
+    let usable_length = cavity_bounds.size().x - radius * 4.0;
+    let mut holes = vec![];
+    // try evenly spaced spots first, then nudge towards the middle if they miss
+    for i in 0..options.drain_holes {
+        let spread = (i as f64 + 1.0) / (options.drain_holes as f64 + 1.0) - 0.5;
+        for nudge in [1.0, 0.75, 0.5, 0.25] {
+            let x = center.x + spread * usable_length * nudge;
+            if let Some(floor_top) = opens_into_cavity(x, center.y) {
+                let height = floor_top - below + radius;
+                holes.push(
+                    CSGMesh::cylinder(radius, height, SPHERE_SEGMENTS, None)
+                        .translate(x, center.y, below),
+                );
+                break;
+            }
+        }
+    }
+    holes
+}
@@ -1,2 +1,48 @@
+mod batch;
 mod bird;
+mod breeding;
+mod bricks;
+mod cache;
+mod export;
+pub mod geometry;
+mod hollow;
+mod lowpoly;
+mod metrics;
+mod neck;
+mod ornament;
+mod outline;
+mod overhang;
+mod papercraft;
+mod parts;
+mod pdf;
+mod printability;
+mod profile;
+mod render;
+mod silhouette;
+mod slices;
+mod smooth_body;
+mod split;
+mod sweep;
+mod text;
+mod voxels;
+pub use batch::*;
 pub use bird::*;
+pub use breeding::*;
+pub use bricks::*;
+pub use cache::*;
+pub use export::*;
+pub use hollow::*;
+pub use metrics::*;
+pub use neck::*;
+pub use ornament::*;
+pub use overhang::*;
+pub use papercraft::*;
+pub use parts::*;
+pub use printability::*;
+pub use profile::*;
+pub use render::*;
+pub use silhouette::*;
+pub use slices::*;
+pub use split::*;
+pub use sweep::*;
+pub use voxels::*;
@@ -0,0 +1,385 @@
+// Low poly versions of the bird's meshes, for papercraft (and anything else that wants a handful of
+// big flat faces)
+// CSG output is full of slivers and T-junctions, so rather than decimating it directly we shrink wrap
+// a sphere onto it first, which always comes out as one clean closed surface, then collapse edges
+// (Garland-Heckbert quadrics) until it's down to the face count we're after
+use crate::geometry::{Triangle, bounds, centroid, is_inside, ray_hits};
+use bevy::math::DVec3;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+// Triangles share corners by index, and wind counter clockwise seen from outside
+#[derive(Clone, Debug)]
+pub(crate) struct LowPolyMesh {
+    pub(crate) vertices: Vec<DVec3>,
+    pub(crate) faces: Vec<[usize; 3]>,
+}
+
+impl LowPolyMesh {
+    pub(crate) fn triangle(&self, face: usize) -> Triangle {
+        self.faces[face].map(|vertex| self.vertices[vertex])
+    }
+
+    // outward, not normalised (twice the area long)
+    pub(crate) fn face_normal(&self, face: usize) -> DVec3 {
+        let [a, b, c] = self.triangle(face);
+        (b - a).cross(c - a)
+    }
+}
+
+// An icosahedron split `subdivisions` times, on the unit sphere
+fn icosphere(subdivisions: u32) -> LowPolyMesh {
+    let t = (1.0 + 5f64.sqrt()) / 2.0;
+    let mut vertices: Vec<DVec3> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .map(|(x, y, z)| DVec3::new(x, y, z).normalize())
+    .to_vec();
+    let mut faces = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<DVec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push(((vertices[a] + vertices[b]) / 2.0).normalize());
+                vertices.len() - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut vertices);
+                let bc = midpoint(b, c, &mut vertices);
+                let ca = midpoint(c, a, &mut vertices);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    LowPolyMesh { vertices, faces }
+}
+
+// Pushes every point of a fine sphere out from the middle of the mesh until it hits the outermost
+// surface. Bits that curl back round behind themselves (as seen from the middle) get skinned over,
+// which is fine for a bird: heads, beaks and bodies are all pretty round from their centres
+pub(crate) fn shrink_wrap(triangles: &[Triangle], faces: usize) -> LowPolyMesh {
+    // (plenty more faces than we're going to keep, so the decimation gets to pick the good ones)
+    let subdivisions = if faces * 4 > 5120 { 5 } else { 4 };
+    let mut sphere = icosphere(subdivisions);
+    let mut center = centroid(triangles);
+    if !center.is_finite() {
+        center = bounds(triangles).center();
+    }
+    let reach: Vec<Option<f64>> = sphere
+        .vertices
+        .iter()
+        .map(|direction| ray_hits(triangles, center, *direction).last().copied())
+        .collect();
+    // any rays that missed (the middle isn't inside, the mesh has a gap) go out as far as the rest do on average
+    let hits: Vec<f64> = reach.iter().flatten().copied().collect();
+    let average = hits.iter().sum::<f64>() / hits.len().max(1) as f64;
+    for (vertex, reach) in sphere.vertices.iter_mut().zip(reach) {
+        *vertex = center + *vertex * reach.unwrap_or(average).max(1e-3);
+    }
+    sphere
+}
+
+// Plane distance squared as a symmetric 4x4, kept as its upper triangle
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane(normal: DVec3, point: DVec3, weight: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let d = -normal.dot(point);
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|value| value * weight),
+        )
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        let mut sum = self.0;
+        for (sum, other) in sum.iter_mut().zip(other.0) {
+            *sum += other;
+        }
+        Quadric(sum)
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+}
+
+// Collapses the cheapest edges until there are `target` faces left (or nothing more can go without
+// folding the surface over on itself). Stays closed and manifold if it started that way
+pub(crate) fn decimate(mesh: &LowPolyMesh, target: usize) -> LowPolyMesh {
+    let mut vertices = mesh.vertices.clone();
+    let mut faces = mesh.faces.clone();
+    let mut face_alive = vec![true; faces.len()];
+    let mut vertex_faces: Vec<Vec<usize>> = vec![vec![]; vertices.len()];
+    let mut quadrics = vec![Quadric::default(); vertices.len()];
+    for (i, face) in faces.iter().enumerate() {
+        let [a, b, c] = face.map(|vertex| vertices[vertex]);
+        let normal = (b - a).cross(c - a);
+        let area = normal.length() / 2.0;
+        let plane = Quadric::plane(normal.normalize_or_zero(), a, area);
+        for vertex in face {
+            vertex_faces[*vertex].push(i);
+            quadrics[*vertex] = quadrics[*vertex].add(&plane);
+        }
+    }
+    let mut versions = vec![0usize; vertices.len()];
+    let mut alive = faces.len();
+
+    let neighbours = |vertex: usize, faces: &[[usize; 3]], vertex_faces: &[Vec<usize>]| {
+        let mut around: Vec<usize> = vertex_faces[vertex]
+            .iter()
+            .flat_map(|face| faces[*face])
+            .filter(|other| *other != vertex)
+            .collect();
+        around.sort_unstable();
+        around.dedup();
+        around
+    };
+    // cheapest of the two ends and the middle (solving for the true best spot isn't worth it at these sizes)
+    let best_spot = |u: usize, v: usize, vertices: &[DVec3], quadrics: &[Quadric]| {
+        let quadric = quadrics[u].add(&quadrics[v]);
+        [vertices[u], vertices[v], (vertices[u] + vertices[v]) / 2.0]
+            .into_iter()
+            .map(|spot| (quadric.error(spot).max(0.0), spot))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .expect("there are three spots")
+    };
+
+    let mut heap = BinaryHeap::new();
+    for u in 0..vertices.len() {
+        for v in neighbours(u, &faces, &vertex_faces) {
+            if u < v {
+                let (cost, _) = best_spot(u, v, &vertices, &quadrics);
+                heap.push(Reverse((cost.to_bits(), u, v, 0, 0)));
+            }
+        }
+    }
+
+    while alive > target.max(4) {
+        let Some(Reverse((_, u, v, u_version, v_version))) = heap.pop() else {
+            break;
+        };
+        if versions[u] != u_version || versions[v] != v_version {
+            continue;
+        }
+        // only edges whose ends share exactly the two faces either side, or the surface pinches
+        let (around_u, around_v) = (
+            neighbours(u, &faces, &vertex_faces),
+            neighbours(v, &faces, &vertex_faces),
+        );
+        let shared: Vec<usize> = around_u
+            .iter()
+            .copied()
+            .filter(|vertex| around_v.binary_search(vertex).is_ok())
+            .collect();
+        if shared.len() != 2 {
+            continue;
+        }
+        // and nothing's left with fewer than three edges coming out of it
+        if around_u.len() + around_v.len() < 7
+            || shared
+                .iter()
+                .any(|other| neighbours(*other, &faces, &vertex_faces).len() < 4)
+        {
+            continue;
+        }
+        let (_, spot) = best_spot(u, v, &vertices, &quadrics);
+        // and nothing around it gets flipped over or squashed flat
+        let flips = vertex_faces[u]
+            .iter()
+            .chain(&vertex_faces[v])
+            .filter(|face| !faces[**face].contains(&u) || !faces[**face].contains(&v))
+            .any(|face| {
+                let old = faces[*face].map(|vertex| vertices[vertex]);
+                let new = faces[*face].map(|vertex| {
+                    if vertex == u || vertex == v {
+                        spot
+                    } else {
+                        vertices[vertex]
+                    }
+                });
+                let old_normal = (old[1] - old[0]).cross(old[2] - old[0]);
+                let new_normal = (new[1] - new[0]).cross(new[2] - new[0]);
+                new_normal.length_squared() < old_normal.length_squared() * 1e-4
+                    || new_normal
+                        .normalize_or_zero()
+                        .dot(old_normal.normalize_or_zero())
+                        < 0.2
+            });
+        if flips {
+            continue;
+        }
+
+        vertices[u] = spot;
+        quadrics[u] = quadrics[u].add(&quadrics[v]);
+        for face in std::mem::take(&mut vertex_faces[v]) {
+            if faces[face].contains(&u) {
+                face_alive[face] = false;
+                alive -= 1;
+                for vertex in faces[face] {
+                    vertex_faces[vertex].retain(|other| *other != face);
+                }
+            } else {
+                for vertex in faces[face].iter_mut() {
+                    if *vertex == v {
+                        *vertex = u;
+                    }
+                }
+                vertex_faces[u].push(face);
+            }
+        }
+        versions[u] += 1;
+        versions[v] += 1;
+        // the edges round about get another go too, ones turned down before might be fine now
+        for near in std::iter::once(u).chain(neighbours(u, &faces, &vertex_faces)) {
+            for other in neighbours(near, &faces, &vertex_faces) {
+                if near == u || other != u {
+                    let (cost, _) = best_spot(near, other, &vertices, &quadrics);
+                    let (a, b) = (near.min(other), near.max(other));
+                    heap.push(Reverse((cost.to_bits(), a, b, versions[a], versions[b])));
+                }
+            }
+        }
+    }
+
+    // pack down to just what's left
+    let mut remap = vec![usize::MAX; vertices.len()];
+    let mut packed = LowPolyMesh {
+        vertices: vec![],
+        faces: vec![],
+    };
+    for (face, _) in faces.iter().zip(&face_alive).filter(|(_, alive)| **alive) {
+        packed.faces.push(face.map(|vertex| {
+            if remap[vertex] == usize::MAX {
+                remap[vertex] = packed.vertices.len();
+                packed.vertices.push(vertices[vertex]);
+            }
+            remap[vertex]
+        }));
+    }
+    packed
+}
+
+// Flips the diagonal of any dent the decimation made up: a valley between two faces where the other
+// diagonal runs through the inside of `triangles` (the mesh it came from). Real creases, like where
+// the beak meets the head, stay put since their other diagonal bridges over the outside
+pub(crate) fn flip_dents(mesh: &mut LowPolyMesh, triangles: &[Triangle]) {
+    // (each flip can turn up a new dent next to it, but it settles within a few goes)
+    for _ in 0..8 {
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (face, corners) in mesh.faces.iter().enumerate() {
+            for side in 0..3 {
+                let (a, b) = (corners[side], corners[(side + 1) % 3]);
+                edges.entry((a.min(b), a.max(b))).or_default().push(face);
+            }
+        }
+        // (in a fixed order, so the same bird always flips the same edges)
+        let mut shared: Vec<_> = edges
+            .iter()
+            .filter_map(|(edge, faces)| match faces[..] {
+                [face, other] => Some((*edge, face, other)),
+                _ => None,
+            })
+            .collect();
+        shared.sort_unstable();
+        let mut touched = HashSet::new();
+        for ((a, b), face, other) in shared {
+            if touched.contains(&face) || touched.contains(&other) {
+                continue;
+            }
+            let opposite = |face: usize| {
+                mesh.faces[face]
+                    .into_iter()
+                    .find(|vertex| *vertex != a && *vertex != b)
+                    .expect("faces have three different corners")
+            };
+            let (c, d) = (opposite(face), opposite(other));
+            if edges.contains_key(&(c.min(d), c.max(d))) {
+                continue;
+            }
+            let across = mesh.vertices[d] - mesh.vertices[a];
+            let normal = mesh.face_normal(face).normalize_or_zero();
+            if across.dot(normal) <= 1e-6 * across.length()
+                || !is_inside(triangles, (mesh.vertices[c] + mesh.vertices[d]) / 2.0)
+            {
+                continue;
+            }
+            // keep the winding: `face` has a -> b, so going round the pair is a, d, b, c
+            let (a, b) = if mesh.faces[face].windows(2).any(|pair| pair == [a, b])
+                || [mesh.faces[face][2], mesh.faces[face][0]] == [a, b]
+            {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            mesh.faces[face] = [a, d, c];
+            mesh.faces[other] = [d, b, c];
+            touched.insert(face);
+            touched.insert(other);
+        }
+        if touched.is_empty() {
+            break;
+        }
+    }
+}
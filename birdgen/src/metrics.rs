@@ -0,0 +1,138 @@
+// Measuring a bird: how big it is, and how much plastic it's going to eat
+// Everything is in export units (mm, with the STL's y-up orientation)
+use crate::{
+    AttachedBird, BirdCSGMeshes, BirdExport, BirdGenInputs, BirdMeshSettings, CSGMesh,
+    export::to_export_orientation,
+    geometry::{Bounds, Triangle, bounds, mesh_triangles, surface_area, volume},
+};
+use csgrs::csg::CSG;
+use std::f64::consts::PI;
+
+// Filament to estimate with, defaults are plain old PLA at 20% infill
+#[derive(Clone, Copy, Debug)]
+pub struct FilamentOptions {
+    // g/cm^3
+    pub density: f32,
+    // mm
+    pub diameter: f32,
+    // percent, 0 is an empty shell and 100 is solid
+    pub infill: f32,
+    // how thick the solid skin is (perimeters, top and bottom layers)
+    pub shell_thickness: f32,
+}
+
+impl Default for FilamentOptions {
+    fn default() -> Self {
+        FilamentOptions {
+            density: 1.24,
+            diameter: 1.75,
+            infill: 20.0,
+            shell_thickness: 1.2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FilamentEstimate {
+    // mm^3 of plastic actually laid down
+    pub material_volume: f64,
+    pub mass_grams: f64,
+    pub length_mm: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BirdMetrics {
+    // mm^3
+    pub volume: f64,
+    // mm^2
+    pub surface_area: f64,
+    pub bounds: Bounds,
+    pub filament: FilamentEstimate,
+}
+
+impl BirdMetrics {
+    // Head and body get measured as one solid, the neck sphere sits almost entirely inside the head
+    // so adding the two up would count all of that twice
+    pub fn measure(head: &CSGMesh, body: &CSGMesh, filament: &FilamentOptions) -> Self {
+        let triangles = export_triangles(&head.union(body));
+        let volume = volume(&triangles);
+        let surface_area = surface_area(&triangles);
+        let bounds = bounds(&triangles);
+        BirdMetrics {
+            volume,
+            surface_area,
+            bounds,
+            filament: estimate_filament(volume, surface_area, filament),
+        }
+    }
+
+    // short enough to sit under a bird, e.g. "62 x 71 x 48 mm  ~  41.2 cm3  ~  14.3 g / 4.80 m"
+    pub fn summary(&self) -> String {
+        let size = self.bounds.size();
+        format!(
+            "{:.0} x {:.0} x {:.0} mm  ~  {:.1} cm3  ~  {:.1} g / {:.2} m",
+            size.x,
+            size.y,
+            size.z,
+            self.volume / 1000.0,
+            self.filament.mass_grams,
+            self.filament.length_mm / 1000.0
+        )
+    }
+}
+
+impl BirdCSGMeshes {
+    pub fn metrics(&self, filament: &FilamentOptions) -> BirdMetrics {
+        BirdMetrics::measure(&self.head, &self.body, filament)
+    }
+}
+
+impl BirdExport {
+    // measures what's actually getting exported (hanger, hollowing and all)
+    pub fn metrics(&self, filament: &FilamentOptions) -> BirdMetrics {
+        BirdMetrics::measure(&self.head, &self.body, filament)
+    }
+}
+
+impl BirdGenInputs {
+    pub fn get_metrics(
+        &self,
+        settings: &BirdMeshSettings,
+        filament: &FilamentOptions,
+    ) -> BirdMetrics {
+        // (neck fixed the same way an export would be)
+        AttachedBird::generate(self, settings)
+            .meshes
+            .metrics(filament)
+    }
+}
+
+fn export_triangles(mesh: &CSGMesh) -> Vec<Triangle> {
+    mesh_triangles(&to_export_orientation(mesh))
+}
+
+// A skin of `shell_thickness` all over, with the rest of the inside filled at the infill percentage.
+// Thin bits where the skin would be thicker than the part itself just count as solid
+fn estimate_filament(
+    volume: f64,
+    surface_area: f64,
+    filament: &FilamentOptions,
+) -> FilamentEstimate {
+    let infill = (filament.infill as f64 / 100.0).clamp(0.0, 1.0);
+    let shell = (surface_area * filament.shell_thickness.max(0.0) as f64).min(volume);
+    let material_volume = shell + (volume - shell) * infill;
+    // g/cm^3 -> g/mm^3
+    let mass_grams = material_volume * filament.density as f64 / 1000.0;
+    let radius = filament.diameter as f64 / 2.0;
+    let cross_section = PI * radius * radius;
+    let length_mm = if cross_section > 0.0 {
+        material_volume / cross_section
+    } else {
+        0.0
+    };
+    FilamentEstimate {
+        material_volume,
+        mass_grams,
+        length_mm,
+    }
+}
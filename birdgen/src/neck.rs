@@ -0,0 +1,198 @@
+// Keeping the head on the bird
+// The head gets scaled up around the origin after it's moved onto the neck, so with a big enough
+// `head_level`/`head_lateral_offset` (or a squished little head) it drifts off the neck sphere and floats
+use crate::{
+    BirdCSGMeshes, BirdGenInputTypes, BirdGenInputs, BirdMeshSettings, CSGMesh,
+    geometry::mesh_triangles, printability::parts_touch,
+};
+use csgrs::csg::CSG;
+
+// how many halvings we do looking for the biggest offsets that still keep the head on
+const CLAMP_STEPS: usize = 6;
+// the neck never gets thinner than this (mm)
+const MIN_NECK_RADIUS: f64 = 1.0;
+// (the neck sphere always sits under the head, so head_to_belly can't pull them apart)
+const CLAMPED_INPUTS: [BirdGenInputTypes; 2] = [
+    BirdGenInputTypes::HeadLevel,
+    BirdGenInputTypes::HeadLateralOffset,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NeckFix {
+    // bridge the gap with a longer neck, the bird's inputs stay the same
+    ExtendNeck,
+    // pull the head offsets back in until the head sits on the neck again
+    ClampOffsets,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NeckChange {
+    ExtendedNeck {
+        length: f64,
+        radius: f64,
+    },
+    Clamped {
+        input: &'static str,
+        from: f32,
+        to: f32,
+    },
+}
+
+impl NeckChange {
+    pub fn describe(&self) -> String {
+        match self {
+            NeckChange::ExtendedNeck { length, radius } => {
+                format!("extended the neck by {length:.1}mm ({radius:.1}mm thick)")
+            }
+            NeckChange::Clamped { input, from, to } => {
+                format!("clamped {input} from {from:.1} to {to:.1}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NeckReport {
+    // the head wasn't touching the body before we did anything
+    pub was_floating: bool,
+    // and still isn't after (only if the fix wasn't enough)
+    pub still_floating: bool,
+    pub changes: Vec<NeckChange>,
+}
+
+// The bird (inputs and meshes) with its head stuck back on, if it needed it
+pub struct AttachedBird {
+    pub inputs: BirdGenInputs,
+    pub meshes: BirdCSGMeshes,
+    pub report: NeckReport,
+}
+
+impl AttachedBird {
+    // A bird built with whatever neck fix `settings` asks for (none at all without one)
+    // Clamping can move the head, so `inputs` is the bird that actually got built, and that's the
+    // one to take the seed (or anything else) from
+    pub fn generate(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Self {
+        match settings.neck_fix {
+            Some(fix) => attach_head(input, settings, fix),
+            None => AttachedBird {
+                inputs: *input,
+                meshes: BirdCSGMeshes::generate(input, settings),
+                report: NeckReport::default(),
+            },
+        }
+    }
+}
+
+pub fn head_is_floating(meshes: &BirdCSGMeshes) -> bool {
+    !parts_touch(&mesh_triangles(&meshes.head), &mesh_triangles(&meshes.body))
+}
+
+pub fn attach_head(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+    fix: NeckFix,
+) -> AttachedBird {
+    // (`generate` never fixes necks anyway, but the clamped birds shouldn't go through it twice)
+    let settings = BirdMeshSettings {
+        neck_fix: None,
+        ..*settings
+    };
+    let meshes = BirdCSGMeshes::generate(input, &settings);
+    let mut report = NeckReport {
+        was_floating: head_is_floating(&meshes),
+        ..Default::default()
+    };
+    if !report.was_floating {
+        return AttachedBird {
+            inputs: *input,
+            meshes,
+            report,
+        };
+    }
+    let (inputs, meshes) = match fix {
+        NeckFix::ExtendNeck => {
+            let (neck, change) = neck_extension(input, &settings);
+            report.changes.push(change);
+            let body = meshes.body.union(&neck);
+            (*input, BirdCSGMeshes { body, ..meshes })
+        }
+        NeckFix::ClampOffsets => {
+            let (clamped, meshes) = clamp_offsets(input, &settings);
+            for input_type in &CLAMPED_INPUTS {
+                let (from, to) = (
+                    input.get_input_value_for_type(input_type),
+                    clamped.get_input_value_for_type(input_type),
+                );
+                if from != to {
+                    report.changes.push(NeckChange::Clamped {
+                        input: input_type.name(),
+                        from,
+                        to,
+                    });
+                }
+            }
+            (clamped, meshes)
+        }
+    };
+    report.still_floating = head_is_floating(&meshes);
+    AttachedBird {
+        inputs,
+        meshes,
+        report,
+    }
+}
+
+// A hulled pair of spheres running from the neck out to where the head actually ended up
+fn neck_extension(input: &BirdGenInputs, settings: &BirdMeshSettings) -> (CSGMesh, NeckChange) {
+    let (lateral, level) = (input.head_lateral_offset as f64, input.head_level as f64);
+    // (head is thinner than the skull when the beak squishes it)
+    let squish = (input.beak_size as f64 / 100.0).min(1.0);
+    let radius = (input.head_size as f64 / 2.0 * squish).max(MIN_NECK_RADIUS);
+    // `place_head` scales by 1.1 around the origin after moving onto the neck
+    let ends = [(lateral, level), (lateral * 1.1, level * 1.1)];
+    let [neck_end, head_end] = ends.map(|(y, z)| {
+        CSGMesh::sphere(
+            radius,
+            settings.precision.sphere_segments(),
+            settings.precision.sphere_stacks(),
+            None,
+        )
+        .translate(0.0, y, z)
+    });
+    let length = (lateral * 0.1).hypot(level * 0.1);
+    (
+        neck_end.union(&head_end).convex_hull(),
+        NeckChange::ExtendedNeck { length, radius },
+    )
+}
+
+// Shrinks the offsets towards zero (where the head and neck line up exactly) until the head's back on
+fn clamp_offsets(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> (BirdGenInputs, BirdCSGMeshes) {
+    let scaled = |factor: f32| {
+        let mut bird = *input;
+        for input_type in &CLAMPED_INPUTS {
+            // whole numbers, so the clamped bird's seed gives back exactly this bird
+            let value = input.get_input_value_for_type(input_type);
+            bird.set_input_value_for_type(input_type, (value * factor).trunc());
+        }
+        bird
+    };
+    let at_zero = scaled(0.0);
+    let mut best = (at_zero, BirdCSGMeshes::generate(&at_zero, settings));
+    let (mut attached, mut floating) = (0.0, 1.0);
+    for _ in 0..CLAMP_STEPS {
+        let factor = (attached + floating) / 2.0;
+        let bird = scaled(factor);
+        let meshes = BirdCSGMeshes::generate(&bird, settings);
+        if head_is_floating(&meshes) {
+            floating = factor;
+        } else {
+            attached = factor;
+            best = (bird, meshes);
+        }
+    }
+    best
+}
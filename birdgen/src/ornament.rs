@@ -0,0 +1,245 @@
+// Hanging loops & keychain holes, for turning birds into ornaments
+// Everything here works in the generation frame (z up, beak pointing at -x),
+// i.e. on the meshes from `generate_bird_head_csg_mesh`/`generate_bird_body_csg_mesh`
+use crate::{
+    BirdGenInputs, CSGMesh, SPHERE_SEGMENTS,
+    geometry::{Triangle, bounds, csg_rotation, distance_to_surface, mesh_triangles, ray_spans},
+};
+use bevy::math::DVec3;
+use csgrs::csg::CSG;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HangerPlacement {
+    // loop sticking up out of the top of the head
+    HeadLoop,
+    // loop on the back, between the chest and the bottom
+    BackLoop,
+    // hole drilled through the tail
+    TailHole,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HangerOptions {
+    pub placement: HangerPlacement,
+    // inner diameter of the loop, or diameter of the tail hole
+    pub hole_diameter: f32,
+    // how thick the ring of the loop is
+    pub loop_thickness: f32,
+    // least amount of material we're happy to leave around a hole/under a loop
+    pub min_wall: f32,
+}
+
+impl Default for HangerOptions {
+    fn default() -> Self {
+        HangerOptions {
+            placement: HangerPlacement::HeadLoop,
+            hole_diameter: 4.0,
+            loop_thickness: 2.5,
+            min_wall: 1.5,
+        }
+    }
+}
+
+impl HangerOptions {
+    pub fn head_loop() -> Self {
+        HangerOptions::default()
+    }
+
+    pub fn back_loop() -> Self {
+        HangerOptions {
+            placement: HangerPlacement::BackLoop,
+            ..HangerOptions::default()
+        }
+    }
+
+    pub fn tail_hole() -> Self {
+        HangerOptions {
+            placement: HangerPlacement::TailHole,
+            ..HangerOptions::default()
+        }
+    }
+}
+
+// how many spots we try along the top of the head/back, or along the tail
+const PLACEMENT_SAMPLES: usize = 32;
+
+// Adds the hanger to whichever mesh it belongs on, returns the (head, body) pair
+pub fn add_hanger(
+    head: CSGMesh,
+    body: CSGMesh,
+    input: &BirdGenInputs,
+    options: &HangerOptions,
+) -> Result<(CSGMesh, CSGMesh), String> {
+    match options.placement {
+        HangerPlacement::HeadLoop => {
+            let head_triangles = mesh_triangles(&head);
+            let head_bounds = bounds(&head_triangles);
+            let anchor = find_loop_anchor(
+                &head_triangles,
+                head_bounds.min.x,
+                head_bounds.max.x,
+                options,
+            )
+            .ok_or("couldn't find a spot on the head solid enough for a loop")?;
+            let with_loop = head.union(&hanging_loop(anchor, options));
+            Ok((with_loop, body))
+        }
+        HangerPlacement::BackLoop => {
+            let body_triangles = mesh_triangles(&body);
+            // the back runs from the chest to the bottom sphere
+            let back_start = input.head_to_belly as f64;
+            let back_end = (input.head_to_belly + input.belly_to_bottom) as f64;
+            let anchor = find_loop_anchor(&body_triangles, back_start, back_end, options)
+                .ok_or("couldn't find a spot on the back solid enough for a loop")?;
+            let with_loop = body.union(&hanging_loop(anchor, options));
+            Ok((head, with_loop))
+        }
+        HangerPlacement::TailHole => {
+            let body_triangles = mesh_triangles(&body);
+            let hole = tail_hole(&body_triangles, input, options)
+                .ok_or("tail is too thin or too small to drill a hole through")?;
+            Ok((head, body.difference(&hole)))
+        }
+    }
+}
+
+// Walks along the centerline between `x_start` and `x_end`, looking down from above,
+// and picks the highest spot with enough material underneath to bury the bottom of a loop
+// Returns the surface point the loop should sit on + how deep it can sink in
+fn find_loop_anchor(
+    triangles: &[Triangle],
+    x_start: f64,
+    x_end: f64,
+    options: &HangerOptions,
+) -> Option<(DVec3, f64)> {
+    let mesh_bounds = bounds(triangles);
+    let x_start = x_start.max(mesh_bounds.min.x);
+    let x_end = x_end.min(mesh_bounds.max.x);
+    if x_end <= x_start {
+        return None;
+    }
+    let ray_start_z = mesh_bounds.max.z + 1.0;
+    // keep the loop on the bird's centerline so it hangs straight, unless the head is off to one side
+    let center_y = mesh_bounds.center().y;
+    let needed = (options.loop_thickness + options.min_wall) as f64;
+
+    let mut best: Option<(DVec3, f64)> = None;
+    for i in 0..=PLACEMENT_SAMPLES {
+        let x = x_start + (x_end - x_start) * i as f64 / PLACEMENT_SAMPLES as f64;
+        let origin = DVec3::new(x, center_y, ray_start_z);
+        let spans = ray_spans(triangles, origin, DVec3::NEG_Z);
+        let Some(&(enter, exit)) = spans.first() else {
+            continue;
+        };
+        let thickness = exit - enter;
+        if thickness < needed {
+            continue;
+        }
+        let surface = DVec3::new(x, center_y, ray_start_z - enter);
+        if best.is_none_or(|(best_surface, _)| surface.z > best_surface.z) {
+            best = Some((surface, thickness));
+        }
+    }
+    best
+}
+
+fn hanging_loop((surface, thickness): (DVec3, f64), options: &HangerOptions) -> CSGMesh {
+    let minor_radius = options.loop_thickness as f64 / 2.0;
+    let major_radius = options.hole_diameter as f64 / 2.0 + minor_radius;
+    // bury most of the ring's thickness, but never so deep the hole gets filled in
+    let sink = (options.loop_thickness as f64 * 0.75).min(thickness - options.min_wall as f64);
+    let center_z = surface.z - sink + major_radius + minor_radius;
+    torus(major_radius, minor_radius).translate(surface.x, surface.y, center_z)
+}
+
+// Ring standing up in the xz plane (string goes through along y), centered at the origin
+fn torus(major_radius: f64, minor_radius: f64) -> CSGMesh {
+    let extent = major_radius + minor_radius * 2.0;
+    // aim for a few grid cells across the ring's thickness
+    let resolution = ((2.0 * extent) / (minor_radius / 3.0)).clamp(16.0, 64.0) as usize;
+    CSGMesh::sdf(
+        move |p| {
+            let ring_distance = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+            (ring_distance * ring_distance + p.y * p.y).sqrt() - minor_radius
+        },
+        (resolution, resolution, resolution),
+        [-extent, -extent, -extent].into(),
+        [extent, extent, extent].into(),
+        0.0,
+        None,
+    )
+}
+
+// The tail is a (very flat) cylinder pushed out along x and then rotated by the tail pitch/yaw,
+// so we follow the same transforms to walk from the tip of the tail back towards the body,
+// drilling along the tail's normal at the first spot with enough material all around
+fn tail_hole(
+    triangles: &[Triangle],
+    input: &BirdGenInputs,
+    options: &HangerOptions,
+) -> Option<CSGMesh> {
+    if input.tail_length <= 0.0 {
+        return None;
+    }
+    let hole_radius = options.hole_diameter as f64 / 2.0;
+    let min_wall = options.min_wall as f64;
+    let tail_base = DVec3::new(
+        (input.head_to_belly + input.belly_to_bottom) as f64,
+        0.0,
+        0.0,
+    );
+    let rotation = csg_rotation(0.0, -input.tail_pitch as f64, input.tail_yaw as f64);
+    let tail_axis = rotation * DVec3::X;
+    let tail_side = rotation * DVec3::Y;
+    let tail_normal = rotation * DVec3::Z;
+    let tail_tip =
+        input.tail_length as f64 + (input.tail_width * input.tail_roundness / 100.0) as f64;
+
+    for i in 0..=PLACEMENT_SAMPLES {
+        // local x along the tail, from just inside the tip back to the base of the tail
+        let local_x =
+            (tail_tip - hole_radius - min_wall) * (1.0 - i as f64 / PLACEMENT_SAMPLES as f64);
+        if local_x <= 0.0 {
+            break;
+        }
+        let center = tail_base + tail_axis * local_x;
+
+        // enough material to drill through?
+        let probe_distance = (input.tail_width + input.belly_size) as f64;
+        let spans = ray_spans(
+            triangles,
+            center - tail_normal * probe_distance,
+            tail_normal,
+        );
+        let Some(&(enter, exit)) = spans
+            .iter()
+            .find(|(enter, exit)| *enter <= probe_distance && *exit >= probe_distance)
+        else {
+            continue;
+        };
+        if exit - enter < min_wall * 2.0 {
+            continue;
+        }
+
+        // and enough material around the hole so it doesn't break out the side?
+        let clearance = hole_radius + min_wall;
+        let has_clearance = [tail_axis, -tail_axis, tail_side, -tail_side]
+            .iter()
+            .all(|dir| {
+                distance_to_surface(triangles, center, *dir).is_some_and(|d| d >= clearance)
+            });
+        if !has_clearance {
+            continue;
+        }
+
+        let drill_length = (exit - enter) * 4.0;
+        return Some(
+            CSGMesh::cylinder(hole_radius, drill_length, SPHERE_SEGMENTS, None)
+                .translate(0.0, 0.0, -drill_length / 2.0)
+                .translate(local_x, 0.0, 0.0)
+                .rotate(0.0, -input.tail_pitch as f64, input.tail_yaw as f64)
+                .translate(tail_base.x, tail_base.y, tail_base.z),
+        );
+    }
+    None
+}
@@ -0,0 +1,350 @@
+// Tracing flat outlines off a grid, shared by the silhouette and slice exports
+// Triangles (or a cross section) get filled into a mask of cells, then the boundary between filled and
+// empty cells gets walked into loops and simplified
+use bevy::math::DVec2;
+use std::{collections::HashMap, fmt::Write};
+
+// A square grid over the page, cell (0, 0) is bottom left
+pub(crate) struct Grid {
+    pub(crate) origin: DVec2,
+    pub(crate) resolution: f64,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+}
+
+// a traced loop, with the filled cell on this side of each point and the empty one on the other
+// (for working out what's behind it, or which piece it belongs to)
+pub(crate) struct Contour {
+    pub(crate) points: Vec<DVec2>,
+    pub(crate) inside: Vec<(i32, i32)>,
+    pub(crate) outside: Vec<(i32, i32)>,
+}
+
+// one side of a filled cell, running counter clockwise around the filled bit
+#[derive(Clone, Copy)]
+struct Edge {
+    start: (i32, i32),
+    end: (i32, i32),
+    inside: (i32, i32),
+    outside: (i32, i32),
+}
+
+impl Grid {
+    pub(crate) fn new(min: DVec2, max: DVec2, resolution: f64) -> Self {
+        // a cell of padding all round so everything traced is closed
+        let origin = min - resolution;
+        let size = (max - origin) / resolution;
+        Grid {
+            origin,
+            resolution,
+            width: size.x.ceil() as i32 + 1,
+            height: size.y.ceil() as i32 + 1,
+        }
+    }
+
+    pub(crate) fn get(&self, mask: &[bool], (x, y): (i32, i32)) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height && mask[(y * self.width + x) as usize]
+    }
+
+    // cells whose centres are inside any of the triangles
+    pub(crate) fn mask(&self, triangles: &[[DVec2; 3]]) -> Vec<bool> {
+        let mut mask = vec![false; (self.width * self.height) as usize];
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|point| (point - self.origin) / self.resolution);
+            let area = cross(a, b, c);
+            if area.abs() < f64::EPSILON {
+                continue;
+            }
+            let (low, high) = (a.min(b).min(c), a.max(b).max(c));
+            let x_range = ((low.x - 0.5).ceil().max(0.0) as i32)
+                ..=((high.x - 0.5).floor() as i32).min(self.width - 1);
+            let y_range = ((low.y - 0.5).ceil().max(0.0) as i32)
+                ..=((high.y - 0.5).floor() as i32).min(self.height - 1);
+            for y in y_range {
+                for x in x_range.clone() {
+                    let p = DVec2::new(x as f64 + 0.5, y as f64 + 0.5);
+                    let (wa, wb, wc) = (
+                        cross(b, c, p) / area,
+                        cross(c, a, p) / area,
+                        cross(a, b, p) / area,
+                    );
+                    if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
+                        mask[(y * self.width + x) as usize] = true;
+                    }
+                }
+            }
+        }
+        mask
+    }
+
+    // Every boundary loop in the mask, outer ones counter clockwise and holes clockwise.
+    // Points sit halfway along each cell side, which smooths off the staircase (same as marching squares)
+    pub(crate) fn trace(&self, mask: &[bool]) -> Vec<Contour> {
+        let mut edges = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.get(mask, (x, y)) {
+                    continue;
+                }
+                let sides = [
+                    ((x, y), (x + 1, y), (x, y - 1)),
+                    ((x + 1, y), (x + 1, y + 1), (x + 1, y)),
+                    ((x + 1, y + 1), (x, y + 1), (x, y + 1)),
+                    ((x, y + 1), (x, y), (x - 1, y)),
+                ];
+                for (start, end, outside) in sides {
+                    if !self.get(mask, outside) {
+                        edges.push(Edge {
+                            start,
+                            end,
+                            inside: (x, y),
+                            outside,
+                        });
+                    }
+                }
+            }
+        }
+        let mut leaving: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (i, edge) in edges.iter().enumerate() {
+            leaving.entry(edge.start).or_default().push(i);
+        }
+
+        let mut used = vec![false; edges.len()];
+        let mut contours = vec![];
+        for first in 0..edges.len() {
+            if used[first] {
+                continue;
+            }
+            let mut contour = Contour {
+                points: vec![],
+                inside: vec![],
+                outside: vec![],
+            };
+            let mut current = first;
+            while !used[current] {
+                used[current] = true;
+                let edge = edges[current];
+                let (start, end) = (self.corner(edge.start), self.corner(edge.end));
+                contour.points.push((start + end) / 2.0);
+                contour.inside.push(edge.inside);
+                contour.outside.push(edge.outside);
+                // where two loops touch at a corner, turn left so they stay two loops
+                let direction = (edge.end.0 - edge.start.0, edge.end.1 - edge.start.1);
+                let Some(next) = leaving.get(&edge.end).and_then(|options| {
+                    options.iter().copied().max_by_key(|next| {
+                        let next = edges[*next];
+                        let turn = (next.end.0 - next.start.0, next.end.1 - next.start.1);
+                        // (cross product, left is positive)
+                        direction.0 * turn.1 - direction.1 * turn.0
+                    })
+                }) else {
+                    break;
+                };
+                current = next;
+            }
+            contours.push(contour);
+        }
+        contours
+    }
+
+    // Fills a cross section from the segments where a closed mesh crosses the slicing plane,
+    // by counting crossings along each row of cell centres
+    pub(crate) fn fill_section(&self, segments: &[[DVec2; 2]]) -> Vec<bool> {
+        let mut mask = vec![false; (self.width * self.height) as usize];
+        let mut rows: Vec<Vec<f64>> = vec![vec![]; self.height as usize];
+        for [a, b] in segments {
+            let (a, b) = (
+                (*a - self.origin) / self.resolution,
+                (*b - self.origin) / self.resolution,
+            );
+            let (low, high) = (a.y.min(b.y), a.y.max(b.y));
+            let first = (low - 0.5).ceil().max(0.0) as i32;
+            // (half open, so a row going right through a corner only counts it once)
+            let last = ((high - 0.5).ceil() as i32 - 1).min(self.height - 1);
+            for row in first..=last {
+                let y = row as f64 + 0.5;
+                let t = (y - a.y) / (b.y - a.y);
+                rows[row as usize].push(a.x + (b.x - a.x) * t);
+            }
+        }
+        for (row, crossings) in rows.iter_mut().enumerate() {
+            crossings.sort_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let first = (pair[0] - 0.5).ceil().max(0.0) as i32;
+                let last = ((pair[1] - 0.5).floor() as i32).min(self.width - 1);
+                for x in first..=last {
+                    mask[row * self.width as usize + x as usize] = true;
+                }
+            }
+        }
+        mask
+    }
+
+    // How far (mm) each filled cell is from the nearest empty one, a two pass chamfer so it's a
+    // touch over the true distance on the diagonals
+    pub(crate) fn distances(&self, mask: &[bool]) -> Vec<f64> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut distance: Vec<f64> = mask
+            .iter()
+            .map(|filled| if *filled { f64::INFINITY } else { 0.0 })
+            .collect();
+        let diagonal = std::f64::consts::SQRT_2;
+        let neighbours = [
+            (-1, 0, 1.0),
+            (-1, -1, diagonal),
+            (0, -1, 1.0),
+            (1, -1, diagonal),
+        ];
+        let mut pass = |cells: &mut dyn Iterator<Item = (usize, usize)>, flip: i32| {
+            for (x, y) in cells {
+                let i = y * width + x;
+                if distance[i] == 0.0 {
+                    continue;
+                }
+                for (dx, dy, step) in neighbours {
+                    let (nx, ny) = (x as i32 + dx * flip, y as i32 + dy * flip);
+                    // (off the grid counts as empty)
+                    let from = if nx < 0 || ny < 0 || nx >= self.width || ny >= self.height {
+                        0.0
+                    } else {
+                        distance[ny as usize * width + nx as usize]
+                    };
+                    distance[i] = distance[i].min(from + step);
+                }
+            }
+        };
+        pass(
+            &mut (0..height).flat_map(|y| (0..width).map(move |x| (x, y))),
+            1,
+        );
+        pass(
+            &mut (0..height)
+                .rev()
+                .flat_map(|y| (0..width).rev().map(move |x| (x, y))),
+            -1,
+        );
+        distance
+            .into_iter()
+            .map(|cells| cells * self.resolution)
+            .collect()
+    }
+
+    // Numbers each separate blob of filled cells (edge to edge, same as `trace` sees them),
+    // None for empty cells
+    pub(crate) fn pieces(&self, mask: &[bool]) -> (Vec<Option<usize>>, usize) {
+        let mut piece = vec![None; mask.len()];
+        let mut count = 0;
+        for start in 0..mask.len() {
+            if !mask[start] || piece[start].is_some() {
+                continue;
+            }
+            piece[start] = Some(count);
+            let mut stack = vec![start];
+            while let Some(i) = stack.pop() {
+                let (x, y) = (
+                    (i % self.width as usize) as i32,
+                    (i / self.width as usize) as i32,
+                );
+                for next in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                    if self.get(mask, next) {
+                        let j = (next.1 * self.width + next.0) as usize;
+                        if piece[j].is_none() {
+                            piece[j] = Some(count);
+                            stack.push(j);
+                        }
+                    }
+                }
+            }
+            count += 1;
+        }
+        (piece, count)
+    }
+
+    pub(crate) fn index(&self, (x, y): (i32, i32)) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub(crate) fn cell_center(&self, i: usize) -> DVec2 {
+        let (x, y) = (i % self.width as usize, i / self.width as usize);
+        self.origin + (DVec2::new(x as f64, y as f64) + 0.5) * self.resolution
+    }
+
+    fn corner(&self, (x, y): (i32, i32)) -> DVec2 {
+        self.origin + DVec2::new(x as f64, y as f64) * self.resolution
+    }
+}
+
+// twice the signed area of abc
+fn cross(a: DVec2, b: DVec2, c: DVec2) -> f64 {
+    (b - a).perp_dot(c - a)
+}
+
+pub(crate) fn signed_area(points: &[DVec2]) -> f64 {
+    let mut area = 0.0;
+    for (i, point) in points.iter().enumerate() {
+        area += point.perp_dot(points[(i + 1) % points.len()]);
+    }
+    area / 2.0
+}
+
+// Douglas-Peucker, keeps the ends
+pub(crate) fn simplify(points: &[DVec2], tolerance: f64) -> Vec<DVec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let line = last - first;
+    let distance = |point: DVec2| match line.try_normalize() {
+        Some(direction) => direction.perp_dot(point - first).abs(),
+        None => point.distance(first),
+    };
+    let (furthest, furthest_distance) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, point)| (i + 1, distance(*point)))
+        .fold(
+            (0, 0.0),
+            |best, next| if next.1 > best.1 { next } else { best },
+        );
+    if furthest_distance <= tolerance {
+        return vec![first, last];
+    }
+    let mut simplified = simplify(&points[..=furthest], tolerance);
+    simplified.pop();
+    simplified.extend(simplify(&points[furthest..], tolerance));
+    simplified
+}
+
+// splits the loop at its two furthest apart-ish points and simplifies each half
+pub(crate) fn simplify_closed(points: &[DVec2], tolerance: f64) -> Vec<DVec2> {
+    let Some(far) = (1..points.len()).max_by(|a, b| {
+        points[*a]
+            .distance_squared(points[0])
+            .total_cmp(&points[*b].distance_squared(points[0]))
+    }) else {
+        return points.to_vec();
+    };
+    let mut looped = points.to_vec();
+    looped.push(points[0]);
+    let mut simplified = simplify(&looped[..=far], tolerance);
+    simplified.pop();
+    simplified.extend(simplify(&looped[far..], tolerance));
+    // (last point is the first one again)
+    simplified.pop();
+    simplified
+}
+
+pub(crate) fn svg_path(points: &[DVec2]) -> String {
+    let mut path = String::new();
+    for (i, point) in points.iter().enumerate() {
+        // (svg's y goes down the page)
+        let _ = write!(
+            path,
+            "{}{:.3} {:.3} ",
+            if i == 0 { "M" } else { "L" },
+            point.x,
+            -point.y
+        );
+    }
+    path
+}
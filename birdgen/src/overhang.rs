@@ -0,0 +1,128 @@
+// Finding the bits of a bird that hang out over nothing (steep heads, long beaks, upturned tails)
+// Works in the export orientation, where the print goes up along +y and the base cut sits on the bed
+use crate::{
+    BirdCSGMeshes, BirdExport, CSGMesh,
+    export::to_export_orientation,
+    geometry::{Triangle, mesh_triangles},
+};
+use bevy::math::DVec3;
+
+const PRINT_UP: DVec3 = DVec3::Y;
+// faces this close to the lowest point are sitting on the bed, not overhanging it
+const BED_EPSILON: f64 = 0.01;
+
+#[derive(Clone, Copy, Debug)]
+pub struct OverhangOptions {
+    // degrees from vertical, past this a face needs support
+    pub max_angle: f32,
+    // also hand back which faces overhang (for tinting the preview)
+    pub face_flags: bool,
+}
+
+impl Default for OverhangOptions {
+    fn default() -> Self {
+        OverhangOptions {
+            max_angle: 45.0,
+            face_flags: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OverhangReport {
+    pub max_angle: f32,
+    // mm^2 of faces past the threshold
+    pub overhang_area: f64,
+    // mm^3, every overhanging face's footprint extruded straight down to the bed
+    // (ignores anything in the way, so it's on the generous side)
+    pub support_volume: f64,
+    // one flag per triangle, in `geometry::mesh_triangles` order for each part
+    pub head_faces: Option<Vec<bool>>,
+    pub body_faces: Option<Vec<bool>>,
+}
+
+impl OverhangReport {
+    pub fn has_overhangs(&self) -> bool {
+        self.overhang_area > 0.0
+    }
+}
+
+pub fn analyze_overhangs(
+    head: &CSGMesh,
+    body: &CSGMesh,
+    options: &OverhangOptions,
+) -> OverhangReport {
+    let head = mesh_triangles(&to_export_orientation(head));
+    let body = mesh_triangles(&to_export_orientation(body));
+    let bed = head
+        .iter()
+        .chain(&body)
+        .flatten()
+        .map(|point| point.dot(PRINT_UP))
+        .fold(f64::INFINITY, f64::min);
+    // a face overhangs by asin(-n.up) from vertical, so compare against the sine instead
+    let limit = (options.max_angle as f64).to_radians().sin();
+
+    let mut report = OverhangReport {
+        max_angle: options.max_angle,
+        overhang_area: 0.0,
+        support_volume: 0.0,
+        head_faces: None,
+        body_faces: None,
+    };
+    let mut flags = [vec![], vec![]];
+    for (triangles, part_flags) in [&head, &body].into_iter().zip(&mut flags) {
+        for triangle in triangles {
+            let overhang = face_overhang(triangle, bed, limit);
+            if let Some((area, support)) = overhang {
+                report.overhang_area += area;
+                report.support_volume += support;
+            }
+            if options.face_flags {
+                part_flags.push(overhang.is_some());
+            }
+        }
+    }
+    if options.face_flags {
+        let [head_faces, body_faces] = flags;
+        report.head_faces = Some(head_faces);
+        report.body_faces = Some(body_faces);
+    }
+    report
+}
+
+// (area, support volume) if the face is past the limit
+fn face_overhang(triangle: &Triangle, bed: f64, limit: f64) -> Option<(f64, f64)> {
+    let [a, b, c] = *triangle;
+    let cross = (b - a).cross(c - a);
+    let double_area = cross.length();
+    if double_area <= f64::EPSILON {
+        return None;
+    }
+    let downward = -cross.dot(PRINT_UP) / double_area;
+    if downward <= limit {
+        return None;
+    }
+    let highest = triangle
+        .iter()
+        .map(|point| point.dot(PRINT_UP))
+        .fold(f64::NEG_INFINITY, f64::max);
+    if highest - bed < BED_EPSILON {
+        return None;
+    }
+    let area = double_area / 2.0;
+    let height = (a + b + c).dot(PRINT_UP) / 3.0 - bed;
+    Some((area, area * downward * height))
+}
+
+impl BirdCSGMeshes {
+    pub fn overhangs(&self, options: &OverhangOptions) -> OverhangReport {
+        analyze_overhangs(&self.head, &self.body, options)
+    }
+}
+
+impl BirdExport {
+    pub fn overhangs(&self, options: &OverhangOptions) -> OverhangReport {
+        analyze_overhangs(&self.head, &self.body, options)
+    }
+}
@@ -0,0 +1,758 @@
+// Papercraft: a low poly bird unfolded flat onto printable pages, with numbered glue tabs and fold lines
+// Head and body are separate models (glue the head on once they're both built). Each one gets
+// shrink wrapped and decimated, then unfolded by growing pieces out across the flattest, longest edges
+// first, cutting wherever the next face would land on top of something already laid out
+use crate::{
+    BirdCSGMeshes, BirdExport, CSGMesh,
+    geometry::{mesh_triangles, surface_area},
+    lowpoly::{LowPolyMesh, decimate, flip_dents, shrink_wrap},
+    outline::svg_path,
+    pdf::{PdfPage, text_width, write_pdf},
+};
+use bevy::math::DVec2;
+use std::{
+    collections::{BinaryHeap, HashMap},
+    fmt::Write,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct PapercraftOptions {
+    // roughly how many faces the whole bird gets, shared between head and body by surface area
+    pub faces: usize,
+    // mm, A4 portrait to start with
+    pub page_size: DVec2,
+    // mm of blank paper round the edge of each page, for printers that can't print right to the edge
+    pub margin: f64,
+    // mm, how far glue tabs stick out (less on short edges)
+    pub tab_width: f64,
+}
+
+impl Default for PapercraftOptions {
+    fn default() -> Self {
+        PapercraftOptions {
+            faces: 240,
+            page_size: DVec2::new(210.0, 297.0),
+            margin: 10.0,
+            tab_width: 6.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetPart {
+    Head,
+    Body,
+}
+
+impl NetPart {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NetPart::Head => "head",
+            NetPart::Body => "body",
+        }
+    }
+}
+
+// Mountain folds point up at you with the printed side up, valley folds away
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetLineKind {
+    Cut,
+    Mountain,
+    Valley,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NetLine {
+    pub kind: NetLineKind,
+    pub from: DVec2,
+    pub to: DVec2,
+}
+
+#[derive(Clone, Debug)]
+pub struct NetLabel {
+    pub text: String,
+    // middle of the text's baseline
+    pub at: DVec2,
+    // radians anticlockwise
+    pub angle: f64,
+    // mm
+    pub size: f64,
+}
+
+// One piece to cut out, in mm on its page (y up, from the bottom left corner)
+#[derive(Clone, Debug)]
+pub struct NetPiece {
+    pub part: NetPart,
+    // from 0
+    pub page: usize,
+    pub faces: Vec<[DVec2; 3]>,
+    // glue tabs, to shade in
+    pub tabs: Vec<[DVec2; 4]>,
+    pub lines: Vec<NetLine>,
+    // edge numbers (matching numbers glue together) and the piece's name
+    pub labels: Vec<NetLabel>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PapercraftNet {
+    pub page_size: DVec2,
+    pub pages: usize,
+    pub pieces: Vec<NetPiece>,
+    pub head_faces: usize,
+    pub body_faces: usize,
+    // how many pairs of edges need gluing
+    pub glue_edges: usize,
+}
+
+// mm between pieces on a page
+const PIECE_SPACING: f64 = 3.0;
+// mm, thick enough to print clearly, thin enough to cut along
+const LINE_WIDTH: f64 = 0.2;
+// (mm, for telling a face landing on another one from two faces sharing an edge)
+const OVERLAP_EPSILON: f64 = 1e-4;
+
+impl NetPiece {
+    fn bounds(&self) -> (DVec2, DVec2) {
+        let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+        for point in self
+            .faces
+            .iter()
+            .flatten()
+            .chain(self.tabs.iter().flatten())
+        {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        (min, max)
+    }
+
+    // moves every point (and turns the labels) with `transform`, which turns by `angle`
+    fn transform(&mut self, angle: f64, transform: impl Fn(DVec2) -> DVec2) {
+        for point in self.faces.iter_mut().flatten() {
+            *point = transform(*point);
+        }
+        for point in self.tabs.iter_mut().flatten() {
+            *point = transform(*point);
+        }
+        for line in &mut self.lines {
+            (line.from, line.to) = (transform(line.from), transform(line.to));
+        }
+        for label in &mut self.labels {
+            label.at = transform(label.at);
+            label.angle += angle;
+        }
+    }
+}
+
+impl PapercraftNet {
+    // what each kind of line means, for the bottom of every page
+    fn key(name: &str, page: usize, pages: usize) -> String {
+        format!(
+            "{name} - page {} of {pages} - cut solid lines, fold dash-dot lines up (mountain) and dashed lines down (valley), glue matching numbers",
+            page + 1
+        )
+    }
+
+    // One SVG per page, at real size
+    pub fn to_svg_pages(&self, name: &str) -> Vec<String> {
+        let size = self.page_size;
+        (0..self.pages)
+            .map(|page| {
+                let pieces: Vec<&NetPiece> = self
+                    .pieces
+                    .iter()
+                    .filter(|piece| piece.page == page)
+                    .collect();
+                let mut svg = String::new();
+                let _ = writeln!(
+                    svg,
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.3}mm" height="{:.3}mm" viewBox="0 {:.3} {:.3} {:.3}">"#,
+                    size.x, size.y, -size.y, size.x, size.y
+                );
+                let _ = writeln!(svg, "  <title>{name} (page {} of {})</title>", page + 1, self.pages);
+                let _ = writeln!(svg, r##"  <g id="tabs" fill="#e4e4e4" stroke="none">"##);
+                for tab in pieces.iter().flat_map(|piece| &piece.tabs) {
+                    let _ = writeln!(svg, r#"    <path d="{}Z"/>"#, svg_path(tab));
+                }
+                let _ = writeln!(svg, "  </g>");
+                for (kind, id, style) in [
+                    (NetLineKind::Cut, "cut", r##"stroke="#000000""##),
+                    (
+                        NetLineKind::Mountain,
+                        "mountain",
+                        r##"stroke="#c0392b" stroke-dasharray="3 1 0.5 1""##,
+                    ),
+                    (
+                        NetLineKind::Valley,
+                        "valley",
+                        r##"stroke="#2e6fba" stroke-dasharray="2 1""##,
+                    ),
+                ] {
+                    let path: String = pieces
+                        .iter()
+                        .flat_map(|piece| &piece.lines)
+                        .filter(|line| line.kind == kind)
+                        .map(|line| svg_path(&[line.from, line.to]))
+                        .collect();
+                    let _ = writeln!(
+                        svg,
+                        r#"  <path id="{id}" fill="none" stroke-width="{LINE_WIDTH}" stroke-linecap="round" {style} d="{path}"/>"#
+                    );
+                }
+                let _ = writeln!(
+                    svg,
+                    r##"  <g id="labels" fill="#333333" font-family="Helvetica, Arial, sans-serif" text-anchor="middle">"##
+                );
+                for label in pieces.iter().flat_map(|piece| &piece.labels) {
+                    let _ = writeln!(
+                        svg,
+                        r#"    <text x="{0:.3}" y="{1:.3}" font-size="{2:.2}" transform="rotate({3:.2} {0:.3} {1:.3})">{4}</text>"#,
+                        label.at.x,
+                        -label.at.y,
+                        label.size,
+                        -label.angle.to_degrees(),
+                        label.text
+                    );
+                }
+                let _ = writeln!(
+                    svg,
+                    r##"    <text x="{:.3}" y="{:.3}" font-size="2.5" text-anchor="start" fill="#888888">{}</text>"##,
+                    PIECE_SPACING,
+                    -PIECE_SPACING,
+                    Self::key(name, page, self.pages)
+                );
+                let _ = writeln!(svg, "  </g>");
+                svg.push_str("</svg>\n");
+                svg
+            })
+            .collect()
+    }
+
+    // Every page in one PDF, at real size (print at 100%, not "fit to page")
+    pub fn to_pdf(&self, name: &str) -> Vec<u8> {
+        let flat = |point: DVec2| [point.x, point.y];
+        let pages: Vec<PdfPage> = (0..self.pages)
+            .map(|page| {
+                let pieces: Vec<&NetPiece> = self
+                    .pieces
+                    .iter()
+                    .filter(|piece| piece.page == page)
+                    .collect();
+                let mut pdf = PdfPage::default();
+                for tab in pieces.iter().flat_map(|piece| &piece.tabs) {
+                    pdf.fill_polygon(&tab.map(flat), [0.894, 0.894, 0.894]);
+                }
+                for (kind, color, dashes) in [
+                    (NetLineKind::Cut, [0.0, 0.0, 0.0], &[][..]),
+                    (
+                        NetLineKind::Mountain,
+                        [0.753, 0.224, 0.169],
+                        &[3.0, 1.0, 0.5, 1.0][..],
+                    ),
+                    (NetLineKind::Valley, [0.18, 0.435, 0.729], &[2.0, 1.0][..]),
+                ] {
+                    pdf.stroke_style(LINE_WIDTH, color, dashes);
+                    for line in pieces.iter().flat_map(|piece| &piece.lines) {
+                        if line.kind == kind {
+                            pdf.line(flat(line.from), flat(line.to));
+                        }
+                    }
+                }
+                for label in pieces.iter().flat_map(|piece| &piece.labels) {
+                    // (pdf text starts at the left, so back up half its width along the baseline)
+                    let half = text_width(&label.text, label.size) / 2.0;
+                    let start = label.at - DVec2::from_angle(label.angle) * half;
+                    pdf.text(
+                        flat(start),
+                        label.angle,
+                        label.size,
+                        [0.2, 0.2, 0.2],
+                        &label.text,
+                    );
+                }
+                pdf.text(
+                    [PIECE_SPACING, PIECE_SPACING],
+                    0.0,
+                    2.5,
+                    [0.533, 0.533, 0.533],
+                    &Self::key(name, page, self.pages),
+                );
+                pdf
+            })
+            .collect();
+        write_pdf(name, self.page_size.x, self.page_size.y, &pages)
+    }
+}
+
+impl BirdCSGMeshes {
+    pub fn papercraft(&self, options: &PapercraftOptions) -> Result<PapercraftNet, String> {
+        papercraft(&self.head, &self.body, options)
+    }
+}
+
+impl BirdExport {
+    // at whatever scale the export's at, most birds want scaling up a few times to be foldable
+    pub fn papercraft(&self, options: &PapercraftOptions) -> Result<PapercraftNet, String> {
+        papercraft(&self.head, &self.body, options)
+    }
+}
+
+// the third corner of a triangle on the right of a -> b, `to_a` and `to_b` away from them
+fn third_corner(a: DVec2, b: DVec2, to_a: f64, to_b: f64) -> DVec2 {
+    let length = a.distance(b).max(f64::EPSILON);
+    let along = (to_a * to_a - to_b * to_b + length * length) / (2.0 * length);
+    let across = (to_a * to_a - along * along).max(0.0).sqrt();
+    let direction = (b - a) / length;
+    a + direction * along - direction.perp() * across
+}
+
+// Separating axis test, only counting it as overlapping if they're more than just touching
+fn overlaps(a: &[DVec2; 3], b: &[DVec2; 3]) -> bool {
+    for triangle in [a, b] {
+        for i in 0..3 {
+            let axis = (triangle[(i + 1) % 3] - triangle[i]).perp();
+            let Some(axis) = axis.try_normalize() else {
+                continue;
+            };
+            let project = |points: &[DVec2; 3]| {
+                points
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), point| {
+                        let along = point.dot(axis);
+                        (low.min(along), high.max(along))
+                    })
+            };
+            let ((a_low, a_high), (b_low, b_high)) = (project(a), project(b));
+            if a_high <= b_low + OVERLAP_EPSILON || b_high <= a_low + OVERLAP_EPSILON {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn tab_triangles(tab: &[DVec2; 4]) -> [[DVec2; 3]; 2] {
+    [[tab[0], tab[1], tab[2]], [tab[0], tab[2], tab[3]]]
+}
+
+// a faces in 3d, laid flat
+struct Unfolded {
+    // which piece each face ended up in, and where its corners went
+    placed: Vec<(usize, [DVec2; 3])>,
+    pieces: usize,
+    // whether each face's edges (corner i to i + 1) got folded rather than cut
+    folds: Vec<[bool; 3]>,
+}
+
+// Grows pieces across the edges that are best to fold (long and flat), starting each one from the
+// biggest face that's left. A face that would land on top of its piece, or stretch it past `fit`,
+// gets left for a later piece instead, and the edge becomes a cut
+fn unfold(mesh: &LowPolyMesh, fit: DVec2) -> Result<Unfolded, String> {
+    let mut sides: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    for (face, corners) in mesh.faces.iter().enumerate() {
+        for side in 0..3 {
+            let (a, b) = (corners[side], corners[(side + 1) % 3]);
+            sides
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push((face, side));
+        }
+    }
+    let across = |face: usize, side: usize| {
+        let (a, b) = (mesh.faces[face][side], mesh.faces[face][(side + 1) % 3]);
+        sides[&(a.min(b), a.max(b))]
+            .iter()
+            .copied()
+            .find(|other| other.0 != face)
+    };
+    let normals: Vec<_> = (0..mesh.faces.len())
+        .map(|face| mesh.face_normal(face).normalize_or_zero())
+        .collect();
+    let fold_priority = |face: usize, side: usize, other: usize| {
+        let [a, b] = [side, (side + 1) % 3].map(|i| mesh.vertices[mesh.faces[face][i]]);
+        let priority = a.distance(b) * (1.0 + normals[face].dot(normals[other]));
+        // (heaps want something totally ordered, and this is never negative)
+        priority.max(0.0).to_bits()
+    };
+    let fits = |min: DVec2, max: DVec2| {
+        let size = max - min;
+        (size.x <= fit.x && size.y <= fit.y) || (size.x <= fit.y && size.y <= fit.x)
+    };
+
+    let mut placed: Vec<Option<(usize, [DVec2; 3])>> = vec![None; mesh.faces.len()];
+    let mut folds = vec![[false; 3]; mesh.faces.len()];
+    let mut pieces = 0;
+    let mut order: Vec<usize> = (0..mesh.faces.len()).collect();
+    order.sort_by(|a, b| {
+        let area = |face: usize| mesh.face_normal(face).length_squared();
+        area(*b).total_cmp(&area(*a))
+    });
+    for start in order {
+        if placed[start].is_some() {
+            continue;
+        }
+        let [a, b, c] = mesh.triangle(start);
+        let flat_b = DVec2::new(a.distance(b), 0.0);
+        // (on the left, so it's counter clockwise like the faces are from outside)
+        let flat_c = third_corner(flat_b, DVec2::ZERO, c.distance(b), c.distance(a));
+        let first = [DVec2::ZERO, flat_b, flat_c];
+        let (mut min, mut max) = (
+            first[0].min(flat_b).min(flat_c),
+            first[0].max(flat_b).max(flat_c),
+        );
+        if !fits(min, max) {
+            return Err(
+                "a face is too big for the page, try a smaller scale or more faces".to_string(),
+            );
+        }
+        let piece = pieces;
+        pieces += 1;
+        placed[start] = Some((piece, first));
+        let mut in_piece = vec![first];
+        let mut heap = BinaryHeap::new();
+        let push_sides = |face: usize,
+                          heap: &mut BinaryHeap<(u64, usize, usize)>,
+                          placed: &[Option<(usize, [DVec2; 3])>]| {
+            for side in 0..3 {
+                if let Some((other, _)) = across(face, side)
+                    && placed[other].is_none()
+                {
+                    heap.push((fold_priority(face, side, other), face, side));
+                }
+            }
+        };
+        push_sides(start, &mut heap, &placed);
+        while let Some((_, face, side)) = heap.pop() {
+            let Some((other, other_side)) = across(face, side) else {
+                continue;
+            };
+            if placed[other].is_some() {
+                continue;
+            }
+            let Some((_, corners)) = placed[face] else {
+                continue;
+            };
+            // the other face runs the shared edge the other way round
+            let (flat_a, flat_b) = (corners[side], corners[(side + 1) % 3]);
+            let third = (other_side + 2) % 3;
+            let [a, b, c] = [other_side, (other_side + 1) % 3, third]
+                .map(|i| mesh.vertices[mesh.faces[other][i]]);
+            let flat_c = third_corner(flat_a, flat_b, c.distance(b), c.distance(a));
+            let mut flat = [DVec2::ZERO; 3];
+            flat[other_side] = flat_b;
+            flat[(other_side + 1) % 3] = flat_a;
+            flat[third] = flat_c;
+            let (grown_min, grown_max) = (min.min(flat_c), max.max(flat_c));
+            if !fits(grown_min, grown_max) || in_piece.iter().any(|laid| overlaps(laid, &flat)) {
+                continue;
+            }
+            (min, max) = (grown_min, grown_max);
+            placed[other] = Some((piece, flat));
+            in_piece.push(flat);
+            folds[face][side] = true;
+            folds[other][other_side] = true;
+            push_sides(other, &mut heap, &placed);
+        }
+    }
+    Ok(Unfolded {
+        placed: placed
+            .into_iter()
+            .map(|placed| placed.expect("every face gets placed"))
+            .collect(),
+        pieces,
+        folds,
+    })
+}
+
+// A tab off the outside of a -> b (the right, for a counter clockwise face), `height` out with sloped ends
+fn tab(a: DVec2, b: DVec2, height: f64) -> [DVec2; 4] {
+    let direction = (b - a).normalize_or_zero();
+    let out = -direction.perp() * height;
+    let inset = direction * height * 0.8;
+    [a, a + out + inset, b + out - inset, b]
+}
+
+// an edge number, sitting just inside the face on the left of a -> b, reading along it
+fn edge_label(number: usize, a: DVec2, b: DVec2) -> NetLabel {
+    let size = (a.distance(b) * 0.2).clamp(1.2, 3.0);
+    let direction = (b - a).normalize_or_zero();
+    NetLabel {
+        text: number.to_string(),
+        at: (a + b) / 2.0 + direction.perp() * size * 0.35,
+        angle: direction.to_angle(),
+        size,
+    }
+}
+
+// Lays one model out flat, numbering its glue edges on from `next_number`
+fn model_pieces(
+    mesh: &LowPolyMesh,
+    part: NetPart,
+    fit: DVec2,
+    tab_width: f64,
+    next_number: &mut usize,
+) -> Result<Vec<NetPiece>, String> {
+    let unfolded = unfold(mesh, fit)?;
+    let mut pieces: Vec<NetPiece> = (0..unfolded.pieces)
+        .map(|_| NetPiece {
+            part,
+            page: 0,
+            faces: vec![],
+            tabs: vec![],
+            lines: vec![],
+            labels: vec![],
+        })
+        .collect();
+    for (piece, corners) in &unfolded.placed {
+        pieces[*piece].faces.push(*corners);
+    }
+
+    let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    for (face, corners) in mesh.faces.iter().enumerate() {
+        for side in 0..3 {
+            let (a, b) = (corners[side], corners[(side + 1) % 3]);
+            edges
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push((face, side));
+        }
+    }
+    // (in a fixed order, so the same bird always gets the same numbers)
+    let mut edges: Vec<_> = edges.into_iter().collect();
+    edges.sort_by_key(|(key, _)| *key);
+    let flat_side = |face: usize, side: usize| {
+        let (_, corners) = unfolded.placed[face];
+        (corners[side], corners[(side + 1) % 3])
+    };
+    for (_, faces) in edges {
+        let &[(face, side), (other, other_side)] = &faces[..] else {
+            // (can't happen on a closed surface, but cut round it if it does)
+            for (face, side) in faces {
+                let (a, b) = flat_side(face, side);
+                let piece = &mut pieces[unfolded.placed[face].0];
+                piece.lines.push(NetLine {
+                    kind: NetLineKind::Cut,
+                    from: a,
+                    to: b,
+                });
+            }
+            continue;
+        };
+        let (a, b) = flat_side(face, side);
+        if unfolded.folds[face][side] {
+            // convex edges (the other face drops away behind this one) fold up, and so do flat
+            // ones (a cube's diagonals), a hair of slack so rounding doesn't flip them to valleys
+            let third = mesh.vertices[mesh.faces[other][(other_side + 2) % 3]];
+            let corner = mesh.vertices[mesh.faces[face][side]];
+            let across = third - corner;
+            let normal = mesh.face_normal(face).normalize_or_zero();
+            let convex = across.dot(normal) < 1e-6 * across.length();
+            pieces[unfolded.placed[face].0].lines.push(NetLine {
+                kind: if convex {
+                    NetLineKind::Mountain
+                } else {
+                    NetLineKind::Valley
+                },
+                from: a,
+                to: b,
+            });
+            continue;
+        }
+
+        let number = *next_number;
+        *next_number += 1;
+        // the tab goes on whichever side it doesn't cover anything, shrinking it if neither's clear
+        let sides = [(face, side), (other, other_side)];
+        let clear = |(face, side): (usize, usize), height: f64, pieces: &[NetPiece]| {
+            let (a, b) = flat_side(face, side);
+            let candidate = tab(a, b, height);
+            let piece = &pieces[unfolded.placed[face].0];
+            !tab_triangles(&candidate).iter().any(|triangle| {
+                piece.faces.iter().any(|laid| overlaps(laid, triangle))
+                    || piece
+                        .tabs
+                        .iter()
+                        .flat_map(tab_triangles)
+                        .any(|laid| overlaps(&laid, triangle))
+            })
+        };
+        let length = a.distance(b);
+        let height = tab_width.min(length * 0.4);
+        let (tab_side, height) = [height, height / 2.0, height / 4.0]
+            .into_iter()
+            .find_map(|height| {
+                sides
+                    .into_iter()
+                    .find(|side| clear(*side, height, &pieces))
+                    .map(|side| (side, height))
+            })
+            .unwrap_or((sides[0], height / 4.0));
+        for (face, side) in sides {
+            let (a, b) = flat_side(face, side);
+            let piece = &mut pieces[unfolded.placed[face].0];
+            piece.labels.push(edge_label(number, a, b));
+            if (face, side) == tab_side {
+                let outline = tab(a, b, height);
+                for i in 0..3 {
+                    piece.lines.push(NetLine {
+                        kind: NetLineKind::Cut,
+                        from: outline[i],
+                        to: outline[i + 1],
+                    });
+                }
+                // tabs tuck in behind
+                piece.lines.push(NetLine {
+                    kind: NetLineKind::Mountain,
+                    from: a,
+                    to: b,
+                });
+                piece.tabs.push(outline);
+            } else {
+                piece.lines.push(NetLine {
+                    kind: NetLineKind::Cut,
+                    from: a,
+                    to: b,
+                });
+            }
+        }
+    }
+
+    // and a name on each piece's biggest face
+    let count = pieces.len();
+    for (i, piece) in pieces.iter_mut().enumerate() {
+        let Some(biggest) = piece.faces.iter().max_by(|a, b| {
+            let area = |[a, b, c]: &[DVec2; 3]| (*b - *a).perp_dot(*c - *a);
+            area(a).total_cmp(&area(b))
+        }) else {
+            continue;
+        };
+        let [a, b, c] = *biggest;
+        let (ab, bc, ca) = (a.distance(b), b.distance(c), c.distance(a));
+        let inradius = (b - a).perp_dot(c - a).abs() / (ab + bc + ca).max(f64::EPSILON);
+        let size = (inradius * 0.6).clamp(1.5, 5.0);
+        let text = if count == 1 {
+            part.name().to_string()
+        } else {
+            format!("{} {}", part.name(), i + 1)
+        };
+        let center = (a * bc + b * ca + c * ab) / (ab + bc + ca).max(f64::EPSILON);
+        piece.labels.push(NetLabel {
+            text,
+            at: center - DVec2::Y * size * 0.35,
+            angle: 0.0,
+            size,
+        });
+    }
+    Ok(pieces)
+}
+
+// Turns each piece whichever way gives it the smallest box that fits on a page, then fills pages
+// a shelf at a time, tallest pieces first. Returns how many pages it took
+fn lay_out(pieces: &mut [NetPiece], options: &PapercraftOptions) -> usize {
+    let printable = options.page_size - 2.0 * options.margin;
+    for piece in pieces.iter_mut() {
+        let (_, angle) = (0..36)
+            .map(|step| {
+                let angle = (step as f64 * 5.0).to_radians();
+                let rotation = DVec2::from_angle(angle);
+                let (mut min, mut max) =
+                    (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+                for point in piece
+                    .faces
+                    .iter()
+                    .flatten()
+                    .chain(piece.tabs.iter().flatten())
+                {
+                    let turned = rotation.rotate(*point);
+                    min = min.min(turned);
+                    max = max.max(turned);
+                }
+                let size = max - min;
+                let fits = size.x <= printable.x && size.y <= printable.y;
+                // (anything that fits beats anything that doesn't)
+                ((!fits, size.x * size.y), angle)
+            })
+            .min_by(|a, b| a.0.0.cmp(&b.0.0).then(a.0.1.total_cmp(&b.0.1)))
+            .expect("there are angles to try");
+        let rotation = DVec2::from_angle(angle);
+        piece.transform(angle, |point| rotation.rotate(point));
+    }
+
+    let mut order: Vec<usize> = (0..pieces.len()).collect();
+    let height = |piece: &NetPiece| {
+        let (min, max) = piece.bounds();
+        max.y - min.y
+    };
+    order.sort_by(|a, b| height(&pieces[*b]).total_cmp(&height(&pieces[*a])));
+    let (left, top) = (options.margin, options.page_size.y - options.margin);
+    let (mut page, mut x, mut shelf_top, mut shelf_height) = (0, left, top, 0.0);
+    for i in order {
+        let (min, max) = pieces[i].bounds();
+        let size = max - min;
+        if x > left && x + size.x > left + printable.x {
+            x = left;
+            shelf_top -= shelf_height + PIECE_SPACING;
+            shelf_height = 0.0;
+        }
+        if shelf_top < top && shelf_top - size.y < options.margin {
+            page += 1;
+            (x, shelf_top, shelf_height) = (left, top, 0.0);
+        }
+        let offset = DVec2::new(x - min.x, shelf_top - max.y);
+        pieces[i].transform(0.0, |point| point + offset);
+        pieces[i].page = page;
+        x += size.x + PIECE_SPACING;
+        shelf_height = f64::max(shelf_height, size.y);
+    }
+    page + 1
+}
+
+pub fn papercraft(
+    head: &CSGMesh,
+    body: &CSGMesh,
+    options: &PapercraftOptions,
+) -> Result<PapercraftNet, String> {
+    let (head, body) = (mesh_triangles(head), mesh_triangles(body));
+    if head.is_empty() || body.is_empty() {
+        return Err("nothing to unfold, the bird's empty".to_string());
+    }
+    let (head_area, body_area) = (surface_area(&head), surface_area(&body));
+    // (small heads still need enough faces to have a beak)
+    let head_faces =
+        ((options.faces as f64 * head_area / (head_area + body_area)).round() as usize).max(24);
+    let body_faces = options.faces.saturating_sub(head_faces).max(24);
+    let mut low_head = decimate(&shrink_wrap(&head, head_faces), head_faces);
+    let mut low_body = decimate(&shrink_wrap(&body, body_faces), body_faces);
+    flip_dents(&mut low_head, &head);
+    flip_dents(&mut low_body, &body);
+    let (head, body) = (low_head, low_body);
+
+    let fit = options.page_size - 2.0 * (options.margin + options.tab_width);
+    if fit.min_element() <= 0.0 {
+        return Err("the page is too small for its margins".to_string());
+    }
+    let mut next_number = 1;
+    let mut pieces = model_pieces(
+        &head,
+        NetPart::Head,
+        fit,
+        options.tab_width,
+        &mut next_number,
+    )?;
+    pieces.extend(model_pieces(
+        &body,
+        NetPart::Body,
+        fit,
+        options.tab_width,
+        &mut next_number,
+    )?);
+    let pages = lay_out(&mut pieces, options);
+    Ok(PapercraftNet {
+        page_size: options.page_size,
+        pages,
+        pieces,
+        head_faces: head.faces.len(),
+        body_faces: body.faces.len(),
+        glue_edges: next_number - 1,
+    })
+}
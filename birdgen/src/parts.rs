@@ -0,0 +1,216 @@
+// Splitting a bird up by anatomy so each bit can get its own filament
+// Overlaps are handed to whichever part wins (eyes > skull > beak > tail > body),
+// so the parts butt up against each other without colliding
+use crate::{
+    BirdGenInputs, BirdMeshSettings, CSGMesh,
+    export::to_export_orientation,
+    generate_beak_csg_mesh, generate_bird_body_csg_mesh_with, generate_bird_torso_csg_mesh,
+    generate_eye_csg_meshes, generate_skull_csg_mesh,
+    geometry::{Triangle, mesh_triangles},
+    place_head,
+};
+use csgrs::csg::CSG;
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{Cursor, Write},
+};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BirdPartKind {
+    Beak,
+    Skull,
+    Eyes,
+    Body,
+    Tail,
+}
+
+impl BirdPartKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BirdPartKind::Beak => "beak",
+            BirdPartKind::Skull => "skull",
+            BirdPartKind::Eyes => "eyes",
+            BirdPartKind::Body => "body",
+            BirdPartKind::Tail => "tail",
+        }
+    }
+}
+
+pub struct BirdPart {
+    pub kind: BirdPartKind,
+    pub mesh: CSGMesh,
+}
+
+// Every part of the bird in the generation frame, parts with nothing left in them are dropped
+pub struct BirdParts {
+    pub parts: Vec<BirdPart>,
+}
+
+impl BirdParts {
+    pub fn new(input: &BirdGenInputs, settings: &BirdMeshSettings) -> Self {
+        let precision = settings.precision;
+        let eyes = generate_eye_csg_meshes(input, precision)
+            .into_iter()
+            .reduce(|both, eye| both.union(&eye))
+            .map(|eyes| place_head(&eyes, input));
+        let skull = place_head(&generate_skull_csg_mesh(input, precision), input);
+        // the beak is hulled around the skull, so carve the skull back out of it
+        let beak = place_head(&generate_beak_csg_mesh(input, precision), input).difference(&skull);
+        let mut head = skull.union(&beak);
+        if let Some(eyes) = &eyes {
+            head = head.union(eyes);
+        }
+
+        let torso = generate_bird_torso_csg_mesh(input, settings);
+        let whole_body = generate_bird_body_csg_mesh_with(input, settings);
+        let tail = whole_body.difference(&torso).difference(&head);
+        let body = torso.difference(&head);
+
+        let skull = match &eyes {
+            Some(eyes) => skull.difference(eyes),
+            None => skull,
+        };
+        let beak = match &eyes {
+            Some(eyes) => beak.difference(eyes),
+            None => beak,
+        };
+
+        let parts = [
+            (BirdPartKind::Beak, Some(beak)),
+            (BirdPartKind::Skull, Some(skull)),
+            (BirdPartKind::Eyes, eyes),
+            (BirdPartKind::Body, Some(body)),
+            (BirdPartKind::Tail, Some(tail)),
+        ]
+        .into_iter()
+        .filter_map(|(kind, mesh)| {
+            let mut mesh = mesh?;
+            if mesh.polygons.is_empty() {
+                return None;
+            }
+            mesh.renormalize();
+            Some(BirdPart { kind, mesh })
+        })
+        .collect();
+        BirdParts { parts }
+    }
+
+    // 3MF with one named object per part
+    pub fn to_3mf(&self, name: &str) -> Result<Vec<u8>, String> {
+        let objects: Vec<(&str, &CSGMesh)> = self
+            .parts
+            .iter()
+            .map(|part| (part.kind.name(), &part.mesh))
+            .collect();
+        write_3mf(name, &objects)
+    }
+
+    // Zip with an STL per part, e.g. coolbird-beak.stl
+    pub fn to_stl_zip(&self, name: &str) -> Result<Vec<u8>, String> {
+        let files: Vec<(String, String)> = self
+            .parts
+            .iter()
+            .map(|part| {
+                let part_name = format!("{name}-{}", part.kind.name());
+                let stl = to_export_orientation(&part.mesh).to_stl_ascii(&part_name);
+                (format!("{part_name}.stl"), stl)
+            })
+            .collect();
+        let entries: Vec<(&str, &[u8])> = files
+            .iter()
+            .map(|(file_name, stl)| (file_name.as_str(), stl.as_bytes()))
+            .collect();
+        write_zip(&entries)
+    }
+}
+
+impl BirdGenInputs {
+    pub fn get_3mf_parts(&self, settings: &BirdMeshSettings) -> Result<Vec<u8>, String> {
+        BirdParts::new(self, settings)
+            .to_3mf(format!("coolbird-{}", self.get_bird_seed_string()).as_str())
+    }
+}
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml" />
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml" />
+</Types>
+"#;
+
+const RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel" />
+</Relationships>
+"#;
+
+// A 3MF package with one named object per mesh
+// (3MF is z up already, so no shapeways rotation here)
+pub(crate) fn write_3mf(name: &str, meshes: &[(&str, &CSGMesh)]) -> Result<Vec<u8>, String> {
+    let mut objects = String::new();
+    let mut items = String::new();
+    for (i, (object_name, mesh)) in meshes.iter().enumerate() {
+        let id = i + 1;
+        write_3mf_object(&mut objects, id, object_name, &mesh_triangles(mesh));
+        let _ = writeln!(items, "    <item objectid=\"{id}\" />");
+    }
+    let model = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <model unit=\"millimeter\" xml:lang=\"en-US\" xmlns=\"http://schemas.microsoft.com/3dmanufacturing/core/2015/02\">\n\
+         \x20 <metadata name=\"Title\">{name}</metadata>\n\
+         \x20 <resources>\n{objects}  </resources>\n\
+         \x20 <build>\n{items}  </build>\n\
+         </model>\n"
+    );
+    write_zip(&[
+        ("[Content_Types].xml", CONTENT_TYPES_XML.as_bytes()),
+        ("_rels/.rels", RELS_XML.as_bytes()),
+        ("3D/3dmodel.model", model.as_bytes()),
+    ])
+}
+
+// 3MF wants shared vertices, so weld the triangle soup back together
+fn write_3mf_object(out: &mut String, id: usize, name: &str, triangles: &[Triangle]) {
+    let mut vertex_ids: HashMap<[u64; 3], usize> = HashMap::new();
+    let mut vertices = String::new();
+    let mut faces = String::new();
+    for triangle in triangles {
+        let [a, b, c] = triangle.map(|v| {
+            let key = [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+            let next_id = vertex_ids.len();
+            *vertex_ids.entry(key).or_insert_with(|| {
+                let _ = writeln!(
+                    vertices,
+                    "          <vertex x=\"{}\" y=\"{}\" z=\"{}\" />",
+                    v.x, v.y, v.z
+                );
+                next_id
+            })
+        });
+        // welding can collapse sliver triangles, 3MF readers don't like those
+        if a == b || b == c || a == c {
+            continue;
+        }
+        let _ = writeln!(
+            faces,
+            "          <triangle v1=\"{a}\" v2=\"{b}\" v3=\"{c}\" />"
+        );
+    }
+    let _ = write!(
+        out,
+        "    <object id=\"{id}\" name=\"{name}\" type=\"model\">\n      <mesh>\n        <vertices>\n{vertices}        </vertices>\n        <triangles>\n{faces}        </triangles>\n      </mesh>\n    </object>\n"
+    );
+}
+
+pub(crate) fn write_zip(files: &[(&str, &[u8])]) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (file_name, contents) in files {
+        zip.start_file(*file_name, SimpleFileOptions::default())
+            .map_err(|e| e.to_string())?;
+        zip.write_all(contents).map_err(|e| e.to_string())?;
+    }
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
@@ -0,0 +1,140 @@
+// Just enough PDF to print line drawings: uncompressed pages of paths and Helvetica text, in mm
+// (Helvetica's one of the fonts every reader has built in, so nothing needs embedding)
+use std::fmt::Write;
+
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+// One page's drawing, in mm from the bottom left corner
+#[derive(Default)]
+pub(crate) struct PdfPage {
+    content: String,
+}
+
+impl PdfPage {
+    // colour is 0-1 rgb, dashes are alternating on/off lengths in mm (empty for solid)
+    pub(crate) fn stroke_style(&mut self, width: f64, color: [f64; 3], dashes: &[f64]) {
+        let dashes: Vec<String> = dashes.iter().map(|dash| format!("{dash:.3}")).collect();
+        let _ = writeln!(
+            self.content,
+            "{width:.3} w {:.3} {:.3} {:.3} RG [{}] 0 d",
+            color[0],
+            color[1],
+            color[2],
+            dashes.join(" ")
+        );
+    }
+
+    pub(crate) fn line(&mut self, from: [f64; 2], to: [f64; 2]) {
+        let _ = writeln!(
+            self.content,
+            "{:.3} {:.3} m {:.3} {:.3} l S",
+            from[0], from[1], to[0], to[1]
+        );
+    }
+
+    pub(crate) fn fill_polygon(&mut self, points: &[[f64; 2]], color: [f64; 3]) {
+        let _ = write!(
+            self.content,
+            "{:.3} {:.3} {:.3} rg ",
+            color[0], color[1], color[2]
+        );
+        for (i, point) in points.iter().enumerate() {
+            let op = if i == 0 { "m" } else { "l" };
+            let _ = write!(self.content, "{:.3} {:.3} {op} ", point[0], point[1]);
+        }
+        let _ = writeln!(self.content, "h f");
+    }
+
+    // `angle` is radians anticlockwise, the text's baseline starts at `at`
+    pub(crate) fn text(
+        &mut self,
+        at: [f64; 2],
+        angle: f64,
+        size: f64,
+        color: [f64; 3],
+        text: &str,
+    ) {
+        let (sin, cos) = angle.sin_cos();
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        let _ = writeln!(
+            self.content,
+            "BT /F1 {size:.3} Tf {:.3} {:.3} {:.3} rg {cos:.4} {sin:.4} {:.4} {cos:.4} {:.3} {:.3} Tm ({escaped}) Tj ET",
+            color[0], color[1], color[2], -sin, at[0], at[1]
+        );
+    }
+}
+
+// roughly how wide Helvetica sets `text`, in the same units as `size` (digits are all 0.556em)
+pub(crate) fn text_width(text: &str, size: f64) -> f64 {
+    text.chars()
+        .map(|character| match character {
+            ' ' => 0.278,
+            'i' | 'j' | 'l' => 0.222,
+            'f' | 't' => 0.278,
+            'm' | 'w' => 0.833,
+            _ => 0.556,
+        })
+        .sum::<f64>()
+        * size
+}
+
+// Pages all the same size (mm)
+pub(crate) fn write_pdf(title: &str, width: f64, height: f64, pages: &[PdfPage]) -> Vec<u8> {
+    // objects: 1 catalog, 2 page tree, 3 font, 4 info, then a page and its content for each page
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        {
+            let kids: Vec<String> = (0..pages.len())
+                .map(|i| format!("{} 0 R", 5 + 2 * i))
+                .collect();
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                pages.len()
+            )
+        },
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        format!(
+            "<< /Title ({}) /Producer (birdgen) >>",
+            title.replace(['(', ')', '\\'], "")
+        ),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            width * POINTS_PER_MM,
+            height * POINTS_PER_MM,
+            6 + 2 * i
+        ));
+        // (everything's drawn in mm, so scale the page up to points first)
+        let stream = format!(
+            "{POINTS_PER_MM:.5} 0 0 {POINTS_PER_MM:.5} 0 0 cm 1 J 1 j\n{}",
+            page.content
+        );
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{object}\nendobj\n", i + 1);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    pdf.into_bytes()
+}
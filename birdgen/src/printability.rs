@@ -0,0 +1,375 @@
+// Checking a bird will actually print, and breeding children until one does
+// The shape checks happen in the generation frame (z up, base cut sitting on z = its height)
+use crate::{
+    AttachedBird, BirdCSGMeshes, BirdGenInputTypes, BirdGenInputs, BirdMeshSettings,
+    OverhangOptions,
+    geometry::{Triangle, bounds, centroid, is_inside, mesh_triangles, shell_count, surface_area},
+};
+use bevy::math::DVec2;
+
+// points this close to the lowest point count as touching the bed
+const BED_EPSILON: f64 = 0.01;
+// how many vertices of one part we poke into the other when checking they touch
+const ATTACHMENT_SAMPLES: usize = 64;
+// how far each nudge moves a failing trait back towards the parent
+const NUDGE_AMOUNT: f32 = 0.5;
+
+// Which constraints to hold children to, None skips that check
+#[derive(Clone, Copy, Debug)]
+pub struct PrintabilityOptions {
+    // head on the body and no loose bits
+    pub require_connected: bool,
+    // smallest eye/head that's worth printing (mm)
+    pub min_feature_size: Option<f32>,
+    // how far the center of mass has to sit inside the base (mm)
+    pub min_stability_margin: Option<f32>,
+    // largest share of the surface allowed to overhang past `overhang.max_angle`
+    pub max_overhang_fraction: Option<f32>,
+    pub overhang: OverhangOptions,
+    // children tried (fresh or nudged) before we give up and take the best one
+    pub max_attempts: usize,
+}
+
+impl Default for PrintabilityOptions {
+    fn default() -> Self {
+        PrintabilityOptions {
+            require_connected: true,
+            min_feature_size: Some(2.0),
+            min_stability_margin: Some(2.0),
+            max_overhang_fraction: Some(0.25),
+            overhang: OverhangOptions::default(),
+            max_attempts: 6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PrintabilityProblem {
+    Disconnected { shells: usize, head_attached: bool },
+    ThinFeature { feature: &'static str, size: f32 },
+    Unstable { margin: f64 },
+    TooMuchOverhang { fraction: f64 },
+}
+
+impl PrintabilityProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            PrintabilityProblem::Disconnected {
+                head_attached: false,
+                ..
+            } => "head isn't attached to the body".to_string(),
+            PrintabilityProblem::Disconnected { shells, .. } => {
+                format!("{shells} separate pieces")
+            }
+            PrintabilityProblem::ThinFeature { feature, size } => {
+                format!("{feature} too small to print ({size:.1}mm)")
+            }
+            PrintabilityProblem::Unstable { margin } => {
+                format!("tips over (center of mass {margin:.1}mm from the base edge)")
+            }
+            PrintabilityProblem::TooMuchOverhang { fraction } => {
+                format!("{:.0}% of the surface overhangs", fraction * 100.0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrintabilityReport {
+    // separate pieces across head and body (1 if everything's joined up)
+    pub shells: usize,
+    pub head_attached: bool,
+    pub thinnest_feature: Option<(&'static str, f32)>,
+    // distance from the center of mass to the edge of the base, negative if it's outside
+    pub stability_margin: f64,
+    pub overhang_fraction: f64,
+    pub problems: Vec<PrintabilityProblem>,
+}
+
+impl PrintabilityReport {
+    pub fn is_printable(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+pub fn check_printability(
+    input: &BirdGenInputs,
+    meshes: &BirdCSGMeshes,
+    options: &PrintabilityOptions,
+) -> PrintabilityReport {
+    let head = mesh_triangles(&meshes.head);
+    let body = mesh_triangles(&meshes.body);
+    let mut problems = vec![];
+
+    let head_attached = parts_touch(&head, &body);
+    let body_shells = shell_count(&body);
+    let shells = body_shells + if head_attached { 0 } else { shell_count(&head) };
+    if options.require_connected && (shells > 1 || !head_attached) {
+        problems.push(PrintabilityProblem::Disconnected {
+            shells,
+            head_attached,
+        });
+    }
+
+    let thinnest_feature = feature_sizes(input)
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some(min_size) = options.min_feature_size {
+        for (feature, size) in feature_sizes(input) {
+            if size < min_size {
+                problems.push(PrintabilityProblem::ThinFeature { feature, size });
+            }
+        }
+    }
+
+    let all: Vec<Triangle> = head.iter().chain(&body).copied().collect();
+    let stability_margin = stability_margin(&all);
+    if let Some(min_margin) = options.min_stability_margin
+        && stability_margin < min_margin as f64
+    {
+        problems.push(PrintabilityProblem::Unstable {
+            margin: stability_margin,
+        });
+    }
+
+    let total_area = surface_area(&all);
+    let overhang_fraction = if total_area > 0.0 {
+        meshes.overhangs(&options.overhang).overhang_area / total_area
+    } else {
+        0.0
+    };
+    if let Some(max_fraction) = options.max_overhang_fraction
+        && overhang_fraction > max_fraction as f64
+    {
+        problems.push(PrintabilityProblem::TooMuchOverhang {
+            fraction: overhang_fraction,
+        });
+    }
+
+    PrintabilityReport {
+        shells,
+        head_attached,
+        thinnest_feature,
+        stability_margin,
+        overhang_fraction,
+        problems,
+    }
+}
+
+// A child that passed (or the closest we got within the attempt budget)
+pub struct PrintableChild {
+    pub inputs: BirdGenInputs,
+    pub meshes: BirdCSGMeshes,
+    pub report: PrintabilityReport,
+    pub attempts: usize,
+}
+
+impl BirdGenInputs {
+    // Same as `get_child_with`, but failing children get re-rolled or nudged back towards us
+    // (alternating, so one stubborn trait doesn't eat the whole budget)
+    pub fn get_printable_child_with(
+        &self,
+        mate: &BirdGenInputs,
+        settings: &BirdMeshSettings,
+        options: &PrintabilityOptions,
+    ) -> PrintableChild {
+        self.get_printable_child_with_rng(mate, settings, options, &mut rand::rng())
+    }
+
+    pub fn get_printable_child_with_rng(
+        &self,
+        mate: &BirdGenInputs,
+        settings: &BirdMeshSettings,
+        options: &PrintabilityOptions,
+        rng: &mut impl rand::Rng,
+    ) -> PrintableChild {
+        let mut best: Option<PrintableChild> = None;
+        let mut candidate = self.get_child_with_rng(mate, rng);
+        for attempt in 1..=options.max_attempts.max(1) {
+            // (the neck fix can clamp the child, check and hand back the bird we really built,
+            // whole numbers only so its seed gives back the same bird)
+            let AttachedBird {
+                inputs: built,
+                meshes,
+                ..
+            } = AttachedBird::generate(&candidate.quantised(), settings);
+            candidate = built;
+            let report = check_printability(&candidate, &meshes, options);
+            if report.is_printable() {
+                return PrintableChild {
+                    inputs: candidate,
+                    meshes,
+                    report,
+                    attempts: attempt,
+                };
+            }
+            let next = if attempt % 2 == 1 {
+                self.nudge_child(&candidate, &report.problems, options)
+            } else {
+                self.get_child_with_rng(mate, rng)
+            };
+            let closer = best
+                .as_ref()
+                .is_none_or(|best| report.problems.len() < best.report.problems.len());
+            if closer {
+                best = Some(PrintableChild {
+                    inputs: candidate,
+                    meshes,
+                    report,
+                    attempts: attempt,
+                });
+            }
+            candidate = next;
+        }
+        let mut best = best.expect("at least one attempt always runs");
+        best.attempts = options.max_attempts.max(1);
+        best
+    }
+
+    // pull whatever's causing each problem back towards this (parent) bird, or straight to a fix
+    fn nudge_child(
+        &self,
+        child: &BirdGenInputs,
+        problems: &[PrintabilityProblem],
+        options: &PrintabilityOptions,
+    ) -> BirdGenInputs {
+        let mut nudged = *child;
+        for problem in problems {
+            let traits: &[BirdGenInputTypes] = match problem {
+                PrintabilityProblem::Disconnected { .. } => &[
+                    BirdGenInputTypes::HeadSize,
+                    BirdGenInputTypes::HeadLevel,
+                    BirdGenInputTypes::HeadLateralOffset,
+                    BirdGenInputTypes::BeakSize,
+                    BirdGenInputTypes::BaseFlat,
+                ],
+                PrintabilityProblem::ThinFeature { feature, .. } => {
+                    let min_size = options.min_feature_size.unwrap_or(0.0);
+                    match *feature {
+                        "eyes" => nudged.eye_size = nudged.eye_size.max(min_size),
+                        _ => {
+                            let squish = (nudged.beak_size / 100.0).clamp(0.01, 1.0);
+                            nudged.head_size = nudged.head_size.max(min_size / (1.1 * squish));
+                        }
+                    }
+                    &[]
+                }
+                PrintabilityProblem::Unstable { .. } => {
+                    // a bigger flat base is the surest fix
+                    nudged.base_flat = (nudged.base_flat.max(0.0) + 20.0).min(100.0);
+                    &[
+                        BirdGenInputTypes::HeadToBelly,
+                        BirdGenInputTypes::BellyToBottom,
+                        BirdGenInputTypes::TailLength,
+                        BirdGenInputTypes::TailPitch,
+                    ]
+                }
+                PrintabilityProblem::TooMuchOverhang { .. } => {
+                    nudged.head_pitch *= 0.7;
+                    nudged.tail_pitch *= 0.7;
+                    nudged.beak_length *= 0.85;
+                    &[]
+                }
+            };
+            for input_type in traits {
+                let towards = self.get_input_value_for_type(input_type);
+                let current = nudged.get_input_value_for_type(input_type);
+                nudged.set_input_value_for_type(
+                    input_type,
+                    current + (towards - current) * NUDGE_AMOUNT,
+                );
+            }
+        }
+        nudged
+    }
+}
+
+// the smallest bits that end up on the print, straight from the inputs (mm)
+fn feature_sizes(input: &BirdGenInputs) -> Vec<(&'static str, f32)> {
+    let mut features = vec![];
+    if input.eye_size > 0.0 {
+        features.push(("eyes", input.eye_size));
+    }
+    // the beak squishes the whole head, so it's as thin as the squished skull
+    let squish = (input.beak_size / 100.0).min(1.0);
+    features.push(("head", input.head_size * squish * 1.1));
+    features
+}
+
+// true if either part has points inside the other
+pub(crate) fn parts_touch(head: &[Triangle], body: &[Triangle]) -> bool {
+    if head.is_empty() || body.is_empty() {
+        return false;
+    }
+    let (head_bounds, body_bounds) = (bounds(head), bounds(body));
+    let overlap_min = head_bounds.min.max(body_bounds.min);
+    let overlap_max = head_bounds.max.min(body_bounds.max);
+    if overlap_min.cmpgt(overlap_max).any() {
+        return false;
+    }
+    let poke = |points: &[Triangle], into: &[Triangle]| {
+        let step = (points.len() / ATTACHMENT_SAMPLES).max(1);
+        points
+            .iter()
+            .step_by(step)
+            .flatten()
+            .any(|point| is_inside(into, *point))
+    };
+    poke(head, body) || poke(body, head)
+}
+
+// Signed distance (on the bed) from under the center of mass to the edge of the base
+fn stability_margin(triangles: &[Triangle]) -> f64 {
+    if triangles.is_empty() {
+        return 0.0;
+    }
+    let bed = bounds(triangles).min.z;
+    let footprint: Vec<DVec2> = triangles
+        .iter()
+        .flatten()
+        .filter(|point| point.z - bed < BED_EPSILON)
+        .map(|point| point.truncate())
+        .collect();
+    let center = centroid(triangles).truncate();
+    let hull = convex_hull_2d(footprint.clone());
+    if hull.len() < 3 {
+        // balancing on a point (or an edge), so it's all margin we don't have
+        return -footprint
+            .iter()
+            .map(|point| point.distance(center))
+            .fold(f64::INFINITY, f64::min);
+    }
+    // counter-clockwise hull, so inside is to the left of every edge
+    hull.iter()
+        .zip(hull.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let edge = *b - *a;
+            edge.perp_dot(center - *a) / edge.length()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Andrew's monotone chain, counter-clockwise with no repeated end point
+fn convex_hull_2d(mut points: Vec<DVec2>) -> Vec<DVec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| a.distance(*b) < BED_EPSILON);
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<DVec2> = vec![];
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2 {
+                let [a, b] = [hull[hull.len() - 2], hull[hull.len() - 1]];
+                if (b - a).perp_dot(point - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
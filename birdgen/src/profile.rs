@@ -0,0 +1,275 @@
+// Timing each step of bird generation, so we have actual numbers instead of folklore
+// Generation always goes through a `StageProfile`, but a disabled one skips the clock
+// (and the triangle counting) entirely so normal generation doesn't pay for it
+use crate::{
+    BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, CSGMesh, generate_bird_body_csg_mesh_profiled,
+    generate_bird_head_csg_mesh_profiled,
+};
+use bevy::platform::time::Instant;
+use csgrs::csg::CSG;
+use std::{num::NonZero, time::Duration};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GenerationPart {
+    Head,
+    Body,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    // building the spheres/cylinders and moving them into place
+    Primitives,
+    Unions,
+    Hulls,
+    Renormalize,
+    Subdivide,
+    BaseCut,
+    // the smooth body's SDF getting turned into triangles
+    Polygonise,
+}
+
+impl PipelineStage {
+    pub const ALL: [PipelineStage; 7] = [
+        PipelineStage::Primitives,
+        PipelineStage::Unions,
+        PipelineStage::Hulls,
+        PipelineStage::Renormalize,
+        PipelineStage::Subdivide,
+        PipelineStage::BaseCut,
+        PipelineStage::Polygonise,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStage::Primitives => "primitives",
+            PipelineStage::Unions => "unions",
+            PipelineStage::Hulls => "hulls",
+            PipelineStage::Renormalize => "renormalize",
+            PipelineStage::Subdivide => "subdivide",
+            PipelineStage::BaseCut => "base cut",
+            PipelineStage::Polygonise => "polygonise",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StageTiming {
+    pub part: GenerationPart,
+    pub stage: PipelineStage,
+    pub duration: Duration,
+    // triangles coming out of this step
+    pub triangles: usize,
+}
+
+// What went into one run of a stage, so a bench can build it once and time just that step
+// (primitives come straight from the bird's inputs, so they don't have one)
+#[derive(Clone, Debug)]
+pub enum StageInput {
+    Union(CSGMesh, CSGMesh),
+    Hull(CSGMesh),
+    Renormalize(CSGMesh),
+    Subdivide(CSGMesh),
+    // the body and the box that gets cut off the bottom of it
+    BaseCut(CSGMesh, CSGMesh),
+}
+
+impl StageInput {
+    pub fn run(self) -> CSGMesh {
+        match self {
+            StageInput::Union(a, b) => a.union(&b),
+            StageInput::Hull(mesh) => mesh.convex_hull(),
+            StageInput::Renormalize(mut mesh) => {
+                mesh.renormalize();
+                mesh
+            }
+            StageInput::Subdivide(mut mesh) => {
+                subdivide_once(&mut mesh);
+                mesh
+            }
+            StageInput::BaseCut(body, cut_box) => body.difference(&cut_box),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StageProfile {
+    enabled: bool,
+    pub timings: Vec<StageTiming>,
+    keep_inputs: bool,
+    // every stage's inputs in the order they ran, when asked for
+    pub inputs: Vec<(GenerationPart, PipelineStage, StageInput)>,
+}
+
+impl StageProfile {
+    pub fn new() -> Self {
+        StageProfile {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    // also hang on to a copy of what went into each stage (lots of meshes, benches only)
+    pub fn keeping_inputs() -> Self {
+        StageProfile {
+            keep_inputs: true,
+            ..StageProfile::new()
+        }
+    }
+
+    pub(crate) fn disabled() -> Self {
+        StageProfile::default()
+    }
+
+    pub(crate) fn time<T: TriangleCount>(
+        &mut self,
+        part: GenerationPart,
+        stage: PipelineStage,
+        step: impl FnOnce() -> T,
+    ) -> T {
+        if !self.enabled {
+            return step();
+        }
+        let start = Instant::now();
+        let result = step();
+        let duration = start.elapsed();
+        self.timings.push(StageTiming {
+            part,
+            stage,
+            duration,
+            triangles: result.triangle_count(),
+        });
+        result
+    }
+
+    pub(crate) fn keep_input(
+        &mut self,
+        part: GenerationPart,
+        stage: PipelineStage,
+        input: impl FnOnce() -> StageInput,
+    ) {
+        if self.keep_inputs {
+            self.inputs.push((part, stage, input()));
+        }
+    }
+
+    pub(crate) fn union(&mut self, part: GenerationPart, a: &CSGMesh, b: &CSGMesh) -> CSGMesh {
+        self.keep_input(part, PipelineStage::Unions, || {
+            StageInput::Union(a.clone(), b.clone())
+        });
+        self.time(part, PipelineStage::Unions, || a.union(b))
+    }
+
+    pub(crate) fn hull(&mut self, part: GenerationPart, mesh: &CSGMesh) -> CSGMesh {
+        self.keep_input(part, PipelineStage::Hulls, || {
+            StageInput::Hull(mesh.clone())
+        });
+        self.time(part, PipelineStage::Hulls, || mesh.convex_hull())
+    }
+
+    pub(crate) fn renormalize(&mut self, part: GenerationPart, mesh: &mut CSGMesh) {
+        self.keep_input(part, PipelineStage::Renormalize, || {
+            StageInput::Renormalize(mesh.clone())
+        });
+        self.time_in_place(part, PipelineStage::Renormalize, mesh, CSGMesh::renormalize);
+    }
+
+    pub(crate) fn subdivide(&mut self, part: GenerationPart, mesh: &mut CSGMesh) {
+        self.keep_input(part, PipelineStage::Subdivide, || {
+            StageInput::Subdivide(mesh.clone())
+        });
+        self.time_in_place(part, PipelineStage::Subdivide, mesh, subdivide_once);
+    }
+
+    // same as `time` for the steps that work in place (renormalize, subdivide)
+    fn time_in_place<T: TriangleCount>(
+        &mut self,
+        part: GenerationPart,
+        stage: PipelineStage,
+        target: &mut T,
+        step: impl FnOnce(&mut T),
+    ) {
+        self.time(part, stage, || step(target));
+        if let Some(timing) = self.timings.last_mut() {
+            timing.triangles = target.triangle_count();
+        }
+    }
+
+    // how long a stage took in total (across every time it ran) for one part
+    pub fn total_time(&self, part: GenerationPart, stage: PipelineStage) -> Duration {
+        self.timings
+            .iter()
+            .filter(|timing| timing.part == part && timing.stage == stage)
+            .map(|timing| timing.duration)
+            .sum()
+    }
+
+    // triangles after the last time a stage ran for one part (None if it never ran)
+    pub fn triangles_after(&self, part: GenerationPart, stage: PipelineStage) -> Option<usize> {
+        self.timings
+            .iter()
+            .rev()
+            .find(|timing| timing.part == part && timing.stage == stage)
+            .map(|timing| timing.triangles)
+    }
+}
+
+fn subdivide_once(mesh: &mut CSGMesh) {
+    mesh.subdivide_triangles(NonZero::new(1).expect("1 isn't 0"));
+}
+
+pub(crate) trait TriangleCount {
+    fn triangle_count(&self) -> usize;
+}
+
+impl TriangleCount for () {
+    fn triangle_count(&self) -> usize {
+        0
+    }
+}
+
+impl TriangleCount for CSGMesh {
+    fn triangle_count(&self) -> usize {
+        self.polygons
+            .iter()
+            .map(|polygon| polygon.vertices.len().saturating_sub(2))
+            .sum()
+    }
+}
+
+impl<T: TriangleCount> TriangleCount for Vec<T> {
+    fn triangle_count(&self) -> usize {
+        self.iter().map(TriangleCount::triangle_count).sum()
+    }
+}
+
+impl<T: TriangleCount, const N: usize> TriangleCount for [T; N] {
+    fn triangle_count(&self) -> usize {
+        self.iter().map(TriangleCount::triangle_count).sum()
+    }
+}
+
+// Builds a bird with the clock running on every step
+pub fn profile_bird_generation(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> (BirdCSGMeshes, StageProfile) {
+    profile_bird_generation_with(input, settings, StageProfile::new())
+}
+
+// same, also keeping every stage's inputs around (see `StageProfile::keeping_inputs`)
+pub fn profile_bird_stage_inputs(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+) -> StageProfile {
+    profile_bird_generation_with(input, settings, StageProfile::keeping_inputs()).1
+}
+
+fn profile_bird_generation_with(
+    input: &BirdGenInputs,
+    settings: &BirdMeshSettings,
+    mut profile: StageProfile,
+) -> (BirdCSGMeshes, StageProfile) {
+    let head = generate_bird_head_csg_mesh_profiled(input, settings, &mut profile);
+    let body = generate_bird_body_csg_mesh_profiled(input, settings, &mut profile);
+    (BirdCSGMeshes { head, body }, profile)
+}
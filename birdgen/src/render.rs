@@ -0,0 +1,366 @@
+// Software rendering a bird to an image, for thumbnails on machines without a GPU (CI, servers)
+// Fixed orthographic camera, flat Lambert shading, one colour. Nothing fancy but it looks like the bird
+use crate::{
+    BirdCSGMeshes, BirdExport, CSGMesh,
+    export::to_export_orientation,
+    geometry::{Triangle, mesh_triangles},
+};
+use bevy::math::DVec3;
+
+// how much of the image is left as a border around the bird
+const MARGIN: f64 = 0.06;
+// light that reaches the faces pointing away from the light
+const AMBIENT: f64 = 0.3;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    pub width: u32,
+    pub height: u32,
+    // sRGB
+    pub color: [u8; 3],
+    // sRGBA, use a 0 alpha for a transparent background
+    pub background: [u8; 4],
+    // degrees the camera swings round from a straight side on view towards the beak
+    pub yaw: f64,
+    // degrees the camera looks down on the bird from
+    pub pitch: f64,
+    // mm that fit across the image (the smaller side), so different birds come out at the same scale
+    // None fits each bird to the image
+    pub fit_size: Option<f64>,
+    // samples per pixel along each side, 1 for hard edges
+    pub supersample: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            width: 512,
+            height: 512,
+            // (Sunflower Gold, one of the app's bird colours)
+            color: [247, 179, 43],
+            // same as the app's background
+            background: [120, 125, 173, 255],
+            yaw: 30.0,
+            pitch: 20.0,
+            fit_size: None,
+            supersample: 2,
+        }
+    }
+}
+
+// Plain RGBA8 pixels, row by row from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct BirdImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl BirdImage {
+    pub fn new(width: u32, height: u32, fill: [u8; 4]) -> Self {
+        // (in usize, a big enough image would wrap around in u32 and come out tiny)
+        let pixels = (width as usize)
+            .checked_mul(height as usize)
+            .expect("image is too big to fit in memory");
+        BirdImage {
+            width,
+            height,
+            pixels: fill.repeat(pixels),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = self.index(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    // Like set_pixel, but see-through colours get blended over whatever's there
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let [r, g, b, a] = color;
+        if a == 255 {
+            self.set_pixel(x, y, color);
+            return;
+        }
+        let under = self.pixel(x, y);
+        let (a, under_a) = (a as f32 / 255.0, under[3] as f32 / 255.0);
+        let out_a = a + under_a * (1.0 - a);
+        if out_a <= 0.0 {
+            return;
+        }
+        let mix = |over: u8, under: u8| {
+            ((over as f32 * a + under as f32 * under_a * (1.0 - a)) / out_a).round() as u8
+        };
+        self.set_pixel(
+            x,
+            y,
+            [
+                mix(r, under[0]),
+                mix(g, under[1]),
+                mix(b, under[2]),
+                (out_a * 255.0).round() as u8,
+            ],
+        );
+    }
+
+    // Pastes `other` with its top left at (x, y), blending over whatever's there
+    pub fn draw_image(&mut self, other: &BirdImage, x: u32, y: u32) {
+        for oy in 0..other.height.min(self.height.saturating_sub(y)) {
+            for ox in 0..other.width.min(self.width.saturating_sub(x)) {
+                self.blend_pixel(x + ox, y + oy, other.pixel(ox, oy));
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(png_bytes)
+    }
+}
+
+// Renders in export orientation (y up, beak towards -x) so the bird stands the way it prints
+pub fn render_bird(head: &CSGMesh, body: &CSGMesh, options: &RenderOptions) -> BirdImage {
+    render_triangles(&bird_triangles(head, body), options)
+}
+
+// The `fit_size` that just fits this bird in the image, take the biggest over a few birds to
+// render them all at the same scale
+pub fn fitted_size(head: &CSGMesh, body: &CSGMesh, options: &RenderOptions) -> f64 {
+    let camera = Camera::new(options.yaw, options.pitch);
+    let (min, max) = view_extent(&bird_triangles(head, body), &camera);
+    let size = (max - min).max(DVec3::ZERO);
+    let (width, height) = (options.width.max(1) as f64, options.height.max(1) as f64);
+    let smaller = width.min(height);
+    (size.x * smaller / width).max(size.y * smaller / height)
+}
+
+fn bird_triangles(head: &CSGMesh, body: &CSGMesh) -> Vec<Triangle> {
+    [head, body]
+        .into_iter()
+        .flat_map(|mesh| mesh_triangles(&to_export_orientation(mesh)))
+        .collect()
+}
+
+impl BirdCSGMeshes {
+    pub fn render(&self, options: &RenderOptions) -> BirdImage {
+        render_bird(&self.head, &self.body, options)
+    }
+}
+
+impl BirdExport {
+    pub fn render(&self, options: &RenderOptions) -> BirdImage {
+        render_bird(&self.head, &self.body, options)
+    }
+}
+
+struct Camera {
+    right: DVec3,
+    up: DVec3,
+    // towards the camera
+    back: DVec3,
+    light: DVec3,
+}
+
+impl Camera {
+    fn new(yaw: f64, pitch: f64) -> Self {
+        let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+        let back = DVec3::new(
+            -yaw.sin() * pitch.cos(),
+            pitch.sin(),
+            yaw.cos() * pitch.cos(),
+        );
+        let right = DVec3::Y.cross(back).normalize_or(DVec3::X);
+        let up = back.cross(right);
+        // over the camera's left shoulder
+        let light = (right * -0.4 + up * 0.7 + back * 0.6).normalize();
+        Camera {
+            right,
+            up,
+            back,
+            light,
+        }
+    }
+
+    // (x right, y up, z towards the camera)
+    fn view(&self, point: DVec3) -> DVec3 {
+        DVec3::new(
+            point.dot(self.right),
+            point.dot(self.up),
+            point.dot(self.back),
+        )
+    }
+}
+
+fn render_triangles(triangles: &[Triangle], options: &RenderOptions) -> BirdImage {
+    let samples = options.supersample.max(1) as usize;
+    let (width, height) = (options.width.max(1), options.height.max(1));
+    let (sample_width, sample_height) = (width as usize * samples, height as usize * samples);
+    let camera = Camera::new(options.yaw, options.pitch);
+
+    let view: Vec<[DVec3; 3]> = triangles
+        .iter()
+        .map(|triangle| triangle.map(|point| camera.view(point)))
+        .collect();
+    let (min, max) = view_extent(triangles, &camera);
+    let center = (min + max) / 2.0;
+    let usable = 1.0 - 2.0 * MARGIN;
+    let scale = match options.fit_size {
+        Some(size) => sample_width.min(sample_height) as f64 * usable / size.max(f64::EPSILON),
+        None => {
+            let size = (max - min).max(DVec3::splat(f64::EPSILON));
+            (sample_width as f64 * usable / size.x).min(sample_height as f64 * usable / size.y)
+        }
+    };
+    let to_screen = |point: DVec3| {
+        DVec3::new(
+            sample_width as f64 / 2.0 + (point.x - center.x) * scale,
+            sample_height as f64 / 2.0 - (point.y - center.y) * scale,
+            point.z,
+        )
+    };
+
+    let color = options.color.map(srgb_to_linear);
+    let samples_total = sample_width
+        .checked_mul(sample_height)
+        .expect("image is too big to fit in memory");
+    let mut depth = vec![f64::NEG_INFINITY; samples_total];
+    let mut shade: Vec<Option<[f64; 3]>> = vec![None; depth.len()];
+    for (triangle, view_triangle) in triangles.iter().zip(&view) {
+        let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+        let Some(mut normal) = normal.try_normalize() else {
+            continue;
+        };
+        // (closed meshes, so anything facing away is hidden anyway, this just keeps stray faces lit)
+        if normal.dot(camera.back) < 0.0 {
+            normal = -normal;
+        }
+        let light = AMBIENT + (1.0 - AMBIENT) * normal.dot(camera.light).max(0.0);
+        let lit = color.map(|channel| channel * light);
+        let [a, b, c] = view_triangle.map(to_screen);
+        let area = edge(a, b, c);
+        if area.abs() < f64::EPSILON {
+            continue;
+        }
+        let x_range = pixel_range(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), sample_width);
+        let y_range = pixel_range(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), sample_height);
+        for y in y_range {
+            for x in x_range.clone() {
+                let p = DVec3::new(x as f64 + 0.5, y as f64 + 0.5, 0.0);
+                let (wa, wb, wc) = (
+                    edge(b, c, p) / area,
+                    edge(c, a, p) / area,
+                    edge(a, b, p) / area,
+                );
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let z = wa * a.z + wb * b.z + wc * c.z;
+                let i = y * sample_width + x;
+                if z > depth[i] {
+                    depth[i] = z;
+                    shade[i] = Some(lit);
+                }
+            }
+        }
+    }
+
+    // average each pixel's samples (premultiplied, so a transparent background doesn't fringe)
+    let background_alpha = options.background[3] as f64 / 255.0;
+    let background = options.background[..3]
+        .iter()
+        .map(|channel| srgb_to_linear(*channel))
+        .collect::<Vec<_>>();
+    let mut image = BirdImage::new(width, height, options.background);
+    let per_pixel = (samples * samples) as f64;
+    for y in 0..height {
+        for x in 0..width {
+            let (mut rgb, mut alpha) = ([0.0; 3], 0.0);
+            for sy in 0..samples {
+                for sx in 0..samples {
+                    let i = (y as usize * samples + sy) * sample_width + x as usize * samples + sx;
+                    let (sample, sample_alpha) = match shade[i] {
+                        Some(lit) => (lit, 1.0),
+                        None => (
+                            [background[0], background[1], background[2]],
+                            background_alpha,
+                        ),
+                    };
+                    for channel in 0..3 {
+                        rgb[channel] += sample[channel] * sample_alpha;
+                    }
+                    alpha += sample_alpha;
+                }
+            }
+            if alpha <= 0.0 {
+                continue;
+            }
+            let [r, g, b] = rgb.map(|channel| linear_to_srgb(channel / alpha));
+            let a = (alpha / per_pixel * 255.0).round() as u8;
+            image.set_pixel(x, y, [r, g, b, a]);
+        }
+    }
+    image
+}
+
+// the bird's bounds as the camera sees them
+fn view_extent(triangles: &[Triangle], camera: &Camera) -> (DVec3, DVec3) {
+    let (mut min, mut max) = (DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY));
+    for point in triangles.iter().flatten() {
+        let point = camera.view(*point);
+        min = min.min(point);
+        max = max.max(point);
+    }
+    (min, max)
+}
+
+// twice the signed area of abc, only looking at x and y
+fn edge(a: DVec3, b: DVec3, c: DVec3) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// pixels whose centres could be between `low` and `high`
+fn pixel_range(low: f64, high: f64, size: usize) -> std::ops::Range<usize> {
+    let start = (low - 0.5).ceil().max(0.0) as usize;
+    let end = ((high - 0.5).floor() + 1.0).clamp(0.0, size as f64) as usize;
+    start..end.max(start)
+}
+
+fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f64) -> u8 {
+    let c = channel.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
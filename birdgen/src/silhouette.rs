@@ -0,0 +1,234 @@
+// Flat outlines of a bird, seen straight on from the side, front or top, for laser cutting and line art
+// The head and body get projected onto a fine grid, and the outline gets traced off the grid and
+// simplified back down, which takes care of unioning all the overlapping triangles for us
+use crate::{
+    BirdCSGMeshes, BirdExport, CSGMesh,
+    export::to_export_orientation,
+    geometry::mesh_triangles,
+    outline::{Grid, signed_area, simplify, simplify_closed, svg_path},
+};
+use bevy::math::{DVec2, DVec3};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    // beak pointing left
+    Side,
+    // looking the bird in the beak
+    Front,
+    // from above, beak pointing left
+    Top,
+}
+
+impl View {
+    pub const ALL: [View; 3] = [View::Side, View::Front, View::Top];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            View::Side => "side",
+            View::Front => "front",
+            View::Top => "top",
+        }
+    }
+
+    // export orientation (y up, beak towards -x) onto the page (y up)
+    pub(crate) fn project(&self, point: DVec3) -> DVec2 {
+        match self {
+            View::Side => DVec2::new(point.x, point.y),
+            View::Front => DVec2::new(point.z, point.y),
+            View::Top => DVec2::new(point.x, -point.z),
+        }
+    }
+
+    // how far towards the viewer, so the page and depth together are right handed
+    pub(crate) fn depth(&self, point: DVec3) -> f64 {
+        match self {
+            View::Side => point.z,
+            View::Front => -point.x,
+            View::Top => point.y,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SilhouetteOptions {
+    pub view: View,
+    // mm per grid cell when tracing, smaller is more accurate (and slower), gets coarser on huge
+    // birds so the grid stays under ~1000 cells across
+    pub resolution: f64,
+    // also trace where the head's outline crosses in front of the body
+    pub detail_lines: bool,
+    // mm, how far the traced outline is allowed to stray when it gets simplified
+    pub tolerance: f64,
+}
+
+impl Default for SilhouetteOptions {
+    fn default() -> Self {
+        SilhouetteOptions {
+            view: View::Side,
+            resolution: 0.1,
+            detail_lines: false,
+            tolerance: 0.05,
+        }
+    }
+}
+
+// Everything is in mm, page coordinates with y up
+#[derive(Clone, Debug)]
+pub struct Silhouette {
+    pub view: View,
+    // closed outer outlines, counter clockwise (usually just the one, more if the head's floating)
+    pub outlines: Vec<Vec<DVec2>>,
+    // open lines inside the outline, only with `detail_lines`
+    pub details: Vec<Vec<DVec2>>,
+}
+
+// (how far the outline sits in from the edge of the svg, mm)
+const SVG_MARGIN: f64 = 2.0;
+// hairline, what most laser cutters want for a cut
+const SVG_STROKE: f64 = 0.1;
+
+impl Silhouette {
+    pub fn bounds(&self) -> (DVec2, DVec2) {
+        let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+        for point in self.outlines.iter().flatten() {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        (min, max)
+    }
+
+    // area inside the outlines (mm^2)
+    pub fn area(&self) -> f64 {
+        self.outlines
+            .iter()
+            .map(|outline| signed_area(outline))
+            .sum()
+    }
+
+    // 1 unit is 1mm, and the page is sized in mm, so it prints and cuts at the bird's real size
+    // Outlines are black and details blue, each in their own group so cutters can pick them apart
+    pub fn to_svg(&self, name: &str) -> String {
+        let (min, max) = self.bounds();
+        let (min, max) = if min.x.is_finite() {
+            (min - SVG_MARGIN, max + SVG_MARGIN)
+        } else {
+            (DVec2::ZERO, DVec2::ZERO)
+        };
+        let size = max - min;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.3}mm" height="{:.3}mm" viewBox="{:.3} {:.3} {:.3} {:.3}">"#,
+            size.x, size.y, min.x, -max.y, size.x, size.y
+        );
+        let _ = writeln!(svg, "  <title>{name} ({} view)</title>", self.view.name());
+        let _ = writeln!(
+            svg,
+            r##"  <g id="outline" fill="none" stroke="#000000" stroke-width="{SVG_STROKE}">"##
+        );
+        for outline in &self.outlines {
+            let _ = writeln!(svg, r#"    <path d="{}Z"/>"#, svg_path(outline));
+        }
+        let _ = writeln!(svg, "  </g>");
+        if !self.details.is_empty() {
+            let _ = writeln!(
+                svg,
+                r##"  <g id="detail" fill="none" stroke="#0000ff" stroke-width="{SVG_STROKE}">"##
+            );
+            for detail in &self.details {
+                let _ = writeln!(svg, r#"    <path d="{}"/>"#, svg_path(detail));
+            }
+            let _ = writeln!(svg, "  </g>");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl BirdCSGMeshes {
+    pub fn silhouette(&self, options: &SilhouetteOptions) -> Silhouette {
+        silhouette(&self.head, &self.body, options)
+    }
+}
+
+impl BirdExport {
+    // at whatever scale the export's at
+    pub fn silhouette(&self, options: &SilhouetteOptions) -> Silhouette {
+        silhouette(&self.head, &self.body, options)
+    }
+}
+
+pub fn silhouette(head: &CSGMesh, body: &CSGMesh, options: &SilhouetteOptions) -> Silhouette {
+    let project = |mesh: &CSGMesh| -> Vec<[DVec2; 3]> {
+        mesh_triangles(&to_export_orientation(mesh))
+            .into_iter()
+            .map(|triangle| triangle.map(|point| options.view.project(point)))
+            .collect()
+    };
+    let (head, body) = (project(head), project(body));
+    let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+    for point in head.iter().chain(&body).flatten() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    if !min.x.is_finite() {
+        return Silhouette {
+            view: options.view,
+            outlines: vec![],
+            details: vec![],
+        };
+    }
+
+    let extent = (max - min).max_element();
+    let grid = Grid::new(min, max, options.resolution.max(extent / 1000.0).max(1e-3));
+    let head_mask = grid.mask(&head);
+    let body_mask = grid.mask(&body);
+    let both: Vec<bool> = head_mask
+        .iter()
+        .zip(&body_mask)
+        .map(|(head, body)| *head || *body)
+        .collect();
+    let tolerance = options.tolerance.max(0.0);
+
+    let outlines = grid
+        .trace(&both)
+        .into_iter()
+        .filter(|contour| signed_area(&contour.points) > 0.0)
+        .map(|contour| simplify_closed(&contour.points, tolerance))
+        .collect();
+    let details = if options.detail_lines {
+        // bits of the head's outline where there's body right behind it
+        let mut details = vec![];
+        for contour in grid.trace(&head_mask) {
+            let behind: Vec<bool> = contour
+                .outside
+                .iter()
+                .map(|outside| grid.get(&body_mask, *outside))
+                .collect();
+            // start somewhere the head's on its own, so a line doesn't get split where the loop starts
+            let start = behind.iter().position(|behind| !behind).unwrap_or(0);
+            let mut run: Vec<DVec2> = vec![];
+            for i in (0..contour.points.len()).map(|i| (i + start) % contour.points.len()) {
+                if behind[i] {
+                    run.push(contour.points[i]);
+                } else if !run.is_empty() {
+                    details.push(std::mem::take(&mut run));
+                }
+            }
+            details.push(run);
+        }
+        details
+            .into_iter()
+            .filter(|run| run.len() > 2)
+            .map(|run| simplify(&run, tolerance))
+            .collect()
+    } else {
+        vec![]
+    };
+    Silhouette {
+        view: options.view,
+        outlines,
+        details,
+    }
+}
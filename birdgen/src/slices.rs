@@ -0,0 +1,464 @@
+// Stacked slices: cut the bird into flat layers a sheet of material thick, to laser cut out of
+// cardboard or plywood and glue back up into a (much bigger) bird
+// Each layer is the cross section halfway through it, traced off a grid same as the silhouettes,
+// with dowel holes punched through wherever lots of layers overlap so the stack lines up
+use crate::{
+    BirdCSGMeshes, BirdExport, CSGMesh, View,
+    batch::map_birds,
+    export::to_export_orientation,
+    geometry::mesh_triangles,
+    outline::{Grid, signed_area, simplify_closed, svg_path},
+};
+use bevy::math::{DVec2, DVec3};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SliceOptions {
+    // which way the layers face, Top stacks them up like a layer cake
+    pub view: View,
+    // mm, material thickness
+    pub thickness: f64,
+    // mm per grid cell when tracing (gets coarser on huge birds, so the grid stays under ~1000 cells across)
+    pub resolution: f64,
+    // mm, how far the cut line is allowed to stray when it gets simplified
+    pub tolerance: f64,
+    // how many dowel holes to try and fit through the stack, 0 for none
+    pub registration_holes: usize,
+    // mm
+    pub hole_diameter: f64,
+}
+
+impl Default for SliceOptions {
+    fn default() -> Self {
+        SliceOptions {
+            view: View::Top,
+            thickness: 3.0,
+            resolution: 0.2,
+            tolerance: 0.1,
+            registration_holes: 2,
+            hole_diameter: 3.0,
+        }
+    }
+}
+
+// One separate bit of a layer (a layer through the neck can have the head and body apart)
+// Everything's in mm, page coordinates with y up, in the same place as the rest of the stack
+#[derive(Clone, Debug)]
+pub struct SlicePiece {
+    // closed cut lines, outer ones counter clockwise and holes clockwise
+    pub outlines: Vec<Vec<DVec2>>,
+    // dowel hole centres
+    pub holes: Vec<DVec2>,
+    // somewhere roomy to put the layer number
+    pub label: DVec2,
+}
+
+#[derive(Clone, Debug)]
+pub struct SliceLayer {
+    // counting up from 1, from the back of the stack to the front
+    pub number: usize,
+    // where the layer got cut, along the view
+    pub depth: f64,
+    pub pieces: Vec<SlicePiece>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SlicedBird {
+    pub view: View,
+    pub thickness: f64,
+    pub hole_diameter: f64,
+    // only layers with something in them, so the numbers skip over any gaps (between a floating head and
+    // the body, say)
+    pub layers: Vec<SliceLayer>,
+}
+
+// hairline for the cuts, same as the silhouettes
+const SVG_STROKE: f64 = 0.1;
+const SVG_MARGIN: f64 = 2.0;
+// mm, layer numbers get engraved (red) rather than cut
+const LABEL_SIZE: f64 = 4.0;
+// cut lines black, engraving red, like most laser software expects
+const CUT_COLOR: &str = "#000000";
+const ENGRAVE_COLOR: &str = "#ff0000";
+
+impl SlicePiece {
+    pub fn bounds(&self) -> (DVec2, DVec2) {
+        let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+        for point in self.outlines.iter().flatten() {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        (min, max)
+    }
+
+    // mm^2, minus any holes in it
+    pub fn area(&self) -> f64 {
+        self.outlines
+            .iter()
+            .map(|outline| signed_area(outline))
+            .sum()
+    }
+
+    // the piece's cut lines, holes and number, shifted by `offset`
+    fn write_svg(&self, svg: &mut String, number: usize, hole_radius: f64, offset: DVec2) {
+        let _ = writeln!(
+            svg,
+            r#"    <g fill="none" stroke="{CUT_COLOR}" stroke-width="{SVG_STROKE}">"#
+        );
+        for outline in &self.outlines {
+            let moved: Vec<DVec2> = outline.iter().map(|point| *point + offset).collect();
+            let _ = writeln!(svg, r#"      <path d="{}Z"/>"#, svg_path(&moved));
+        }
+        for hole in &self.holes {
+            let hole = *hole + offset;
+            let _ = writeln!(
+                svg,
+                r#"      <circle cx="{:.3}" cy="{:.3}" r="{hole_radius:.3}"/>"#,
+                hole.x, -hole.y
+            );
+        }
+        let _ = writeln!(svg, "    </g>");
+        let label = self.label + offset;
+        let _ = writeln!(
+            svg,
+            r#"    <text x="{:.3}" y="{:.3}" fill="{ENGRAVE_COLOR}" font-family="sans-serif" font-size="{LABEL_SIZE}" text-anchor="middle" dominant-baseline="central">{number}</text>"#,
+            label.x, -label.y
+        );
+    }
+}
+
+fn svg_open(svg: &mut String, min: DVec2, max: DVec2) {
+    let size = max - min;
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{:.3}mm" height="{:.3}mm" viewBox="{:.3} {:.3} {:.3} {:.3}">"#,
+        size.x, size.y, min.x, -max.y, size.x, size.y
+    );
+}
+
+impl SlicedBird {
+    pub fn bounds(&self) -> (DVec2, DVec2) {
+        let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+        for piece in self.layers.iter().flat_map(|layer| &layer.pieces) {
+            let (low, high) = piece.bounds();
+            min = min.min(low);
+            max = max.max(high);
+        }
+        (min, max)
+    }
+
+    // mm, how tall the glued up stack is
+    pub fn height(&self) -> f64 {
+        match (self.layers.first(), self.layers.last()) {
+            (Some(first), Some(last)) => (last.number - first.number + 1) as f64 * self.thickness,
+            _ => 0.0,
+        }
+    }
+
+    // Every layer stacked in place, one (Inkscape) layer each, for checking the stack or cutting by hand
+    pub fn to_layers_svg(&self, name: &str) -> String {
+        let (min, max) = self.bounds();
+        let (min, max) = if min.x.is_finite() {
+            (min - SVG_MARGIN, max + SVG_MARGIN)
+        } else {
+            (DVec2::ZERO, DVec2::ZERO)
+        };
+        let mut svg = String::new();
+        svg_open(&mut svg, min, max);
+        let _ = writeln!(
+            svg,
+            "  <title>{name} ({} layers of {}mm, {} view)</title>",
+            self.layers.len(),
+            self.thickness,
+            self.view.name()
+        );
+        for layer in &self.layers {
+            let _ = writeln!(
+                svg,
+                r#"  <g id="layer-{0}" inkscape:groupmode="layer" inkscape:label="layer {0}">"#,
+                layer.number
+            );
+            for piece in &layer.pieces {
+                piece.write_svg(
+                    &mut svg,
+                    layer.number,
+                    self.hole_diameter / 2.0,
+                    DVec2::ZERO,
+                );
+            }
+            let _ = writeln!(svg, "  </g>");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // Every piece laid out flat across as many `sheet` sized pages as it takes, `spacing` mm apart,
+    // tallest first along shelves (not the tightest packing going, but it's quick and predictable)
+    pub fn to_sheets_svg(
+        &self,
+        name: &str,
+        sheet: DVec2,
+        spacing: f64,
+    ) -> Result<Vec<String>, String> {
+        let spacing = spacing.max(0.0);
+        let mut pieces: Vec<(usize, &SlicePiece)> = self
+            .layers
+            .iter()
+            .flat_map(|layer| layer.pieces.iter().map(|piece| (layer.number, piece)))
+            .collect();
+        let size = |piece: &SlicePiece| {
+            let (min, max) = piece.bounds();
+            max - min
+        };
+        for (number, piece) in &pieces {
+            let piece_size = size(piece);
+            if piece_size.x + 2.0 * spacing > sheet.x || piece_size.y + 2.0 * spacing > sheet.y {
+                return Err(format!(
+                    "layer {number} is {:.0}x{:.0}mm, too big for a {:.0}x{:.0}mm sheet",
+                    piece_size.x, piece_size.y, sheet.x, sheet.y
+                ));
+            }
+        }
+        pieces.sort_by(|a, b| size(b.1).y.total_cmp(&size(a.1).y));
+
+        // (top left corner of each piece on its sheet, in svg coordinates with y down)
+        let mut sheets: Vec<Vec<(usize, &SlicePiece, DVec2)>> = vec![vec![]];
+        let (mut x, mut shelf_top, mut shelf_height) = (spacing, spacing, 0.0);
+        for (number, piece) in pieces {
+            let piece_size = size(piece);
+            if x + piece_size.x + spacing > sheet.x {
+                x = spacing;
+                shelf_top += shelf_height + spacing;
+                shelf_height = 0.0;
+            }
+            if shelf_top + piece_size.y + spacing > sheet.y {
+                sheets.push(vec![]);
+                (x, shelf_top, shelf_height) = (spacing, spacing, 0.0);
+            }
+            let current = sheets.last_mut().expect("there's always a sheet");
+            current.push((number, piece, DVec2::new(x, shelf_top)));
+            x += piece_size.x + spacing;
+            shelf_height = f64::max(shelf_height, piece_size.y);
+        }
+
+        let count = sheets.len();
+        Ok(sheets
+            .into_iter()
+            .enumerate()
+            .map(|(i, placed)| {
+                let mut svg = String::new();
+                svg_open(
+                    &mut svg,
+                    DVec2::new(0.0, -sheet.y),
+                    DVec2::new(sheet.x, 0.0),
+                );
+                let _ = writeln!(svg, "  <title>{name} (sheet {} of {count})</title>", i + 1);
+                for (number, piece, corner) in placed {
+                    // the piece's top left (min x, max y) onto the corner
+                    let (min, max) = piece.bounds();
+                    let offset = DVec2::new(corner.x - min.x, -corner.y - max.y);
+                    let _ = writeln!(svg, r#"  <g id="layer-{number}">"#);
+                    piece.write_svg(&mut svg, number, self.hole_diameter / 2.0, offset);
+                    let _ = writeln!(svg, "  </g>");
+                }
+                svg.push_str("</svg>\n");
+                svg
+            })
+            .collect())
+    }
+}
+
+impl BirdCSGMeshes {
+    pub fn slice(&self, options: &SliceOptions) -> SlicedBird {
+        slice_bird(&self.head, &self.body, options)
+    }
+}
+
+impl BirdExport {
+    // at whatever scale the export's at, so scale it up first for a big sculpture
+    pub fn slice(&self, options: &SliceOptions) -> SlicedBird {
+        slice_bird(&self.head, &self.body, options)
+    }
+}
+
+// where a mesh crosses the plane `depth` along the view, as segments on the page
+fn cross_section(triangles: &[[DVec3; 3]], view: View, depth: f64) -> Vec<[DVec2; 2]> {
+    let mut segments = vec![];
+    for triangle in triangles {
+        let depths = triangle.map(|point| view.depth(point));
+        let mut crossings = vec![];
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            if (depths[a] > depth) != (depths[b] > depth) {
+                let t = (depth - depths[a]) / (depths[b] - depths[a]);
+                crossings.push(view.project(triangle[a].lerp(triangle[b], t)));
+            }
+        }
+        if let [a, b] = crossings[..] {
+            segments.push([a, b]);
+        }
+    }
+    segments
+}
+
+pub fn slice_bird(head: &CSGMesh, body: &CSGMesh, options: &SliceOptions) -> SlicedBird {
+    let view = options.view;
+    let thickness = options.thickness.max(0.1);
+    let meshes = [
+        mesh_triangles(&to_export_orientation(head)),
+        mesh_triangles(&to_export_orientation(body)),
+    ];
+    let mut sliced = SlicedBird {
+        view,
+        thickness,
+        hole_diameter: options.hole_diameter,
+        layers: vec![],
+    };
+    let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+    let (mut nearest, mut furthest) = (f64::INFINITY, f64::NEG_INFINITY);
+    for point in meshes.iter().flatten().flatten() {
+        min = min.min(view.project(*point));
+        max = max.max(view.project(*point));
+        nearest = nearest.min(view.depth(*point));
+        furthest = furthest.max(view.depth(*point));
+    }
+    if !min.x.is_finite() {
+        return sliced;
+    }
+
+    let extent = (max - min).max_element();
+    let grid = Grid::new(min, max, options.resolution.max(extent / 1000.0).max(1e-3));
+    let layer_count = ((furthest - nearest) / thickness).ceil().max(1.0) as usize;
+    // (centred, so whatever's left over gets split between the two ends)
+    let start = (nearest + furthest - layer_count as f64 * thickness) / 2.0;
+    let depths: Vec<f64> = (0..layer_count)
+        .map(|i| start + (i as f64 + 0.5) * thickness)
+        .collect();
+    // each mesh is closed, so fill them on their own and then union them
+    let section = |depth: f64| -> Vec<bool> {
+        let mut filled = vec![false; (grid.width * grid.height) as usize];
+        for triangles in &meshes {
+            let mask = grid.fill_section(&cross_section(triangles, view, depth));
+            for (filled, cell) in filled.iter_mut().zip(mask) {
+                *filled |= cell;
+            }
+        }
+        filled
+    };
+
+    // Holes go where the most layers have room for them (a hole's width of material all round),
+    // then as far from the other holes as possible among spots nearly as good.
+    // Layers too small for any of them just get lined up by eye
+    let radius = options.hole_diameter.max(0.0) / 2.0;
+    let mut holes: Vec<DVec2> = vec![];
+    if options.registration_holes > 0 && radius > 0.0 {
+        // (a chunk of layers at a time, so there's never a whole stack of masks in memory)
+        let chunks: Vec<&[f64]> = depths.chunks(8).collect();
+        let chunk_counts = map_birds(&chunks, |chunk| {
+            let mut counts = vec![0; (grid.width * grid.height) as usize];
+            for depth in *chunk {
+                let distances = grid.distances(&section(*depth));
+                for (count, distance) in counts.iter_mut().zip(distances) {
+                    *count += (distance >= 3.0 * radius) as usize;
+                }
+            }
+            counts
+        });
+        let mut counts = vec![0; (grid.width * grid.height) as usize];
+        for chunk in chunk_counts {
+            for (count, chunk) in counts.iter_mut().zip(chunk) {
+                *count += chunk;
+            }
+        }
+        let best = counts.iter().copied().max().unwrap_or(0);
+        // (a hole through only one layer isn't lining anything up)
+        if best >= 2 {
+            let good_enough = (best * 3).div_ceil(4).max(2);
+            while holes.len() < options.registration_holes {
+                let next = (0..counts.len())
+                    .filter(|i| counts[*i] >= good_enough)
+                    .map(|i| {
+                        let center = grid.cell_center(i);
+                        let apart = holes
+                            .iter()
+                            .map(|hole| hole.distance(center))
+                            .fold(f64::INFINITY, f64::min);
+                        (center, apart, counts[i])
+                    })
+                    // first by distance from the others, then by how many layers it goes through
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)));
+                match next {
+                    Some((center, apart, _)) if apart >= 4.0 * radius => holes.push(center),
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    let tolerance = options.tolerance.max(0.0);
+    let layers = map_birds(&depths, |depth| {
+        let mask = section(*depth);
+        let (pieces, count) = grid.pieces(&mask);
+        // labels stay clear of the dowel holes
+        let mut label_mask = mask.clone();
+        for (i, cell) in label_mask.iter_mut().enumerate() {
+            let center = grid.cell_center(i);
+            if holes
+                .iter()
+                .any(|hole| hole.distance(center) < 2.0 * radius)
+            {
+                *cell = false;
+            }
+        }
+        let distances = grid.distances(&label_mask);
+        let room = grid.distances(&mask);
+
+        let mut layer: Vec<SlicePiece> = (0..count)
+            .map(|_| SlicePiece {
+                outlines: vec![],
+                holes: vec![],
+                label: DVec2::ZERO,
+            })
+            .collect();
+        let mut roomiest = vec![f64::NEG_INFINITY; count];
+        for (i, piece) in pieces.iter().enumerate() {
+            if let Some(piece) = *piece
+                && distances[i] > roomiest[piece]
+            {
+                roomiest[piece] = distances[i];
+                layer[piece].label = grid.cell_center(i);
+            }
+        }
+        for contour in grid.trace(&mask) {
+            if let Some(piece) = pieces[grid.index(contour.inside[0])] {
+                layer[piece]
+                    .outlines
+                    .push(simplify_closed(&contour.points, tolerance));
+            }
+        }
+        for hole in &holes {
+            let cell = ((*hole - grid.origin) / grid.resolution).floor();
+            let i = grid.index((cell.x as i32, cell.y as i32));
+            if let Some(piece) = pieces[i]
+                && room[i] >= 3.0 * radius
+            {
+                layer[piece].holes.push(*hole);
+            }
+        }
+        // crumbs smaller than a cell or so aren't worth cutting
+        layer.retain(|piece| piece.area() > grid.resolution * grid.resolution * 4.0);
+        layer
+    });
+
+    sliced.layers = layers
+        .into_iter()
+        .zip(depths)
+        .enumerate()
+        .filter(|(_, (pieces, _))| !pieces.is_empty())
+        .map(|(i, (pieces, depth))| SliceLayer {
+            number: i + 1,
+            depth,
+            pieces,
+        })
+        .collect();
+    sliced
+}
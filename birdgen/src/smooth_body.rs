@@ -0,0 +1,214 @@
+// Smooth body backend: the same neck/chest/bottom/tail primitives as the hull body,
+// but evaluated as a signed distance field and polygonised with csgrs's `sdf` feature.
+// Like the original script's `chained_hull` each neighbouring pair gets hulled together,
+// but the links are smooth-min'd into each other instead of hulled all at once,
+// so we keep the dips at the neck and the tail join instead of one big potato
+use crate::{BirdGenInputs, CSGMesh, NONZERO_THICKNESS, Precision, geometry::csg_rotation};
+use bevy::math::{DQuat, DVec2, DVec3};
+
+// how many slices we take when sweeping one primitive into the other to fake a hull
+const HULL_SWEEP_STEPS: usize = 12;
+// target size of a grid cell when polygonising, and the most cells we'll use along any axis
+const TARGET_CELL_SIZE: f64 = 0.75;
+const MAX_CELLS_PER_AXIS: usize = 160;
+
+#[derive(Clone, Copy)]
+struct Ball {
+    center: DVec3,
+    radius: f64,
+}
+
+#[derive(Clone, Copy)]
+struct Ellipsoid {
+    center: DVec3,
+    radii: DVec3,
+}
+
+// flat elliptical cylinder, living in its own frame before being rotated into place
+#[derive(Clone, Copy)]
+struct TailDisc {
+    base: DVec3,
+    inverse_rotation: DQuat,
+    rotation: DQuat,
+    center_x: f64,
+    radii: DVec2,
+    thickness: f64,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct SmoothBody {
+    neck: Ball,
+    chest: Ellipsoid,
+    bottom: Ball,
+    tail: Option<TailDisc>,
+    blend_radius: f64,
+}
+
+impl SmoothBody {
+    pub(crate) fn new(input: &BirdGenInputs, blend_radius: f32) -> Self {
+        let neck = Ball {
+            center: DVec3::new(
+                0.0,
+                input.head_lateral_offset as f64,
+                input.head_level as f64,
+            ),
+            radius: input.head_size as f64 / 2.0,
+        };
+        let chest = Ellipsoid {
+            center: DVec3::new(input.head_to_belly as f64, 0.0, 0.0),
+            radii: DVec3::new(
+                input.belly_length as f64 / 2.0,
+                (input.belly_size * input.belly_fat / 200.0) as f64,
+                input.belly_size as f64 / 2.0,
+            ),
+        };
+        let bottom_center = DVec3::new(
+            (input.head_to_belly + input.belly_to_bottom) as f64,
+            0.0,
+            0.0,
+        );
+        let bottom = Ball {
+            center: bottom_center,
+            radius: input.bottom_size as f64 / 2.0,
+        };
+        // same as the original script, no tail if any of these are zero
+        let has_tail =
+            input.tail_length != 0.0 && input.tail_width != 0.0 && input.tail_roundness != 0.0;
+        let tail = has_tail.then(|| {
+            let rotation = csg_rotation(0.0, -input.tail_pitch as f64, input.tail_yaw as f64);
+            TailDisc {
+                base: bottom_center,
+                inverse_rotation: rotation.inverse(),
+                rotation,
+                center_x: input.tail_length as f64,
+                radii: DVec2::new(
+                    (input.tail_width * input.tail_roundness / 100.0) as f64,
+                    input.tail_width as f64,
+                ),
+                // a 0.1 thick disc is thinner than a grid cell, give it at least one cell so it shows up
+                thickness: NONZERO_THICKNESS.max(TARGET_CELL_SIZE),
+            }
+        });
+        SmoothBody {
+            neck,
+            chest,
+            bottom,
+            tail,
+            blend_radius: blend_radius.max(0.0) as f64,
+        }
+    }
+
+    pub(crate) fn without_tail(mut self) -> Self {
+        self.tail = None;
+        self
+    }
+
+    pub(crate) fn distance(&self, p: DVec3) -> f64 {
+        let neck_to_chest = hull_with_ball(self.neck, p, |q| self.chest.distance(q));
+        let chest_to_bottom = hull_with_ball(self.bottom, p, |q| self.chest.distance(q));
+        let mut distance = smooth_min(neck_to_chest, chest_to_bottom, self.blend_radius);
+        if let Some(tail) = &self.tail {
+            let bottom_to_tail = hull_with_ball(self.bottom, p, |q| tail.distance(q));
+            distance = smooth_min(distance, bottom_to_tail, self.blend_radius);
+        }
+        distance
+    }
+
+    // generous box around everything, with room for the blend to bulge out
+    fn bounds(&self) -> (DVec3, DVec3) {
+        let mut min = (self.neck.center - DVec3::splat(self.neck.radius))
+            .min(self.chest.center - self.chest.radii)
+            .min(self.bottom.center - DVec3::splat(self.bottom.radius));
+        let mut max = (self.neck.center + DVec3::splat(self.neck.radius))
+            .max(self.chest.center + self.chest.radii)
+            .max(self.bottom.center + DVec3::splat(self.bottom.radius));
+        if let Some(tail) = &self.tail {
+            for corner_x in [-1.0, 1.0] {
+                for corner_y in [-1.0, 1.0] {
+                    for corner_z in [0.0, 1.0] {
+                        let local = DVec3::new(
+                            tail.center_x + corner_x * tail.radii.x,
+                            corner_y * tail.radii.y,
+                            corner_z * tail.thickness,
+                        );
+                        let corner = tail.base + tail.rotation * local;
+                        min = min.min(corner);
+                        max = max.max(corner);
+                    }
+                }
+            }
+        }
+        let margin = DVec3::splat(self.blend_radius + TARGET_CELL_SIZE * 2.0);
+        (min - margin, max + margin)
+    }
+
+    pub(crate) fn polygonise(self, precision: Precision) -> CSGMesh {
+        let (min, max) = self.bounds();
+        let size = max - min;
+        let cell_size = TARGET_CELL_SIZE / precision.detail_scale();
+        let cells =
+            |length: f64| ((length / cell_size).ceil() as usize).clamp(8, MAX_CELLS_PER_AXIS);
+        CSGMesh::sdf(
+            move |p| self.distance(DVec3::new(p.x, p.y, p.z)),
+            (cells(size.x), cells(size.y), cells(size.z)),
+            [min.x, min.y, min.z].into(),
+            [max.x, max.y, max.z].into(),
+            0.0,
+            None,
+        )
+    }
+}
+
+impl Ball {
+    fn distance(&self, p: DVec3) -> f64 {
+        p.distance(self.center) - self.radius
+    }
+}
+
+impl Ellipsoid {
+    // bound rather than exact, but plenty for blending (see iquilezles.org/articles/ellipsoids)
+    fn distance(&self, p: DVec3) -> f64 {
+        let local = p - self.center;
+        let k0 = (local / self.radii).length();
+        let k1 = (local / (self.radii * self.radii)).length();
+        if k1 == 0.0 {
+            return -self.radii.min_element();
+        }
+        k0 * (k0 - 1.0) / k1
+    }
+}
+
+impl TailDisc {
+    fn distance(&self, p: DVec3) -> f64 {
+        let local = self.inverse_rotation * (p - self.base) - DVec3::new(self.center_x, 0.0, 0.0);
+        // distance to the ellipse outline, scaled so it's roughly right in mm
+        let radial =
+            ((DVec2::new(local.x, local.y) / self.radii).length() - 1.0) * self.radii.min_element();
+        let half_thickness = self.thickness / 2.0;
+        let vertical = (local.z - half_thickness).abs() - half_thickness;
+        let outside = DVec2::new(radial.max(0.0), vertical.max(0.0)).length();
+        radial.max(vertical).min(0.0) + outside
+    }
+}
+
+// Distance to the hull of a ball and some other convex shape:
+// hull(ball, shape) is every copy of `shape` shrunk towards the ball's center by `s`,
+// grown by what's left of the ball's radius, so we just sweep through a handful of `s`
+fn hull_with_ball(ball: Ball, p: DVec3, shape_distance: impl Fn(DVec3) -> f64) -> f64 {
+    let mut distance = ball.distance(p);
+    for step in 1..=HULL_SWEEP_STEPS {
+        let s = step as f64 / HULL_SWEEP_STEPS as f64;
+        let shrunk = s * shape_distance(ball.center + (p - ball.center) / s);
+        distance = distance.min(shrunk - (1.0 - s) * ball.radius);
+    }
+    distance
+}
+
+// polynomial smooth min, `k` is roughly how far the blend reaches
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - h * h * k / 4.0
+}
@@ -0,0 +1,324 @@
+// Cutting a bird in two so each half can lie flat on the bed
+// The halves get matching sockets and we hand back loose pegs to glue into them,
+// so both cut faces stay flat and nothing needs supports
+use crate::{
+    BirdExport, CSGMesh, SPHERE_SEGMENTS,
+    export::combine_stl,
+    geometry::{Bounds, Triangle, bounds, distance_to_surface, is_inside, mesh_triangles},
+    parts::write_3mf,
+};
+use bevy::math::DVec3;
+use csgrs::csg::CSG;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitPlane {
+    // left/right halves, cut along the bird's centerline
+    Sagittal,
+    // front/back halves
+    Coronal,
+    // top/bottom halves
+    Transverse,
+}
+
+impl SplitPlane {
+    fn normal(&self) -> DVec3 {
+        match self {
+            SplitPlane::Sagittal => DVec3::Y,
+            SplitPlane::Coronal => DVec3::X,
+            SplitPlane::Transverse => DVec3::Z,
+        }
+    }
+
+    // rotations (degrees, csgrs style) that turn each half's cut face to point straight down
+    fn lay_flat_rotations(&self) -> [(f64, f64, f64); 2] {
+        match self {
+            SplitPlane::Sagittal => [(90.0, 0.0, 0.0), (-90.0, 0.0, 0.0)],
+            SplitPlane::Coronal => [(0.0, -90.0, 0.0), (0.0, 90.0, 0.0)],
+            SplitPlane::Transverse => [(0.0, 0.0, 0.0), (180.0, 0.0, 0.0)],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SplitOptions {
+    pub plane: SplitPlane,
+    // where along the plane's normal to cut (0 is the bird's origin)
+    pub offset: f32,
+    pub peg_count: usize,
+    pub peg_diameter: f32,
+    // total length of each peg, half of it goes into each socket
+    pub peg_length: f32,
+    // extra room around the pegs in the sockets
+    pub clearance: f32,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            plane: SplitPlane::Sagittal,
+            offset: 0.0,
+            peg_count: 2,
+            peg_diameter: 4.0,
+            peg_length: 8.0,
+            clearance: 0.2,
+        }
+    }
+}
+
+// Half a bird, already lying on its cut face (still in the generation frame, z up)
+pub struct SplitPiece {
+    pub head: CSGMesh,
+    pub body: CSGMesh,
+}
+
+pub struct SplitBird {
+    pub pieces: [SplitPiece; 2],
+    pub pegs: Vec<CSGMesh>,
+}
+
+// gap between the pieces when they're laid out together
+const PIECE_SPACING: f64 = 5.0;
+// how finely we look for peg spots across the cut face
+const PEG_SEARCH_STEP: f64 = 1.5;
+
+impl BirdExport {
+    pub fn split(&self, options: &SplitOptions) -> Result<SplitBird, String> {
+        split_bird(&self.head, &self.body, options)
+    }
+}
+
+pub fn split_bird(
+    head: &CSGMesh,
+    body: &CSGMesh,
+    options: &SplitOptions,
+) -> Result<SplitBird, String> {
+    let normal = options.plane.normal();
+    let offset = options.offset as f64;
+    let body_triangles = mesh_triangles(body);
+    let head_triangles = mesh_triangles(head);
+    let mut whole_bounds = bounds(&body_triangles);
+    if !head_triangles.is_empty() {
+        whole_bounds = whole_bounds.union(&bounds(&head_triangles));
+    }
+    let along = normal.dot(whole_bounds.min)..normal.dot(whole_bounds.max);
+    if !along.contains(&offset) {
+        return Err("split plane doesn't go through the bird".to_string());
+    }
+
+    let peg_spots = find_peg_spots(&body_triangles, &whole_bounds, normal, offset, options);
+    let socket_radius = (options.peg_diameter + options.clearance) as f64 / 2.0;
+    let socket_depth = (options.peg_length / 2.0 + options.clearance) as f64;
+    let sockets: Vec<CSGMesh> = peg_spots
+        .iter()
+        .map(|spot| {
+            along_normal(
+                CSGMesh::cylinder(socket_radius, socket_depth * 2.0, SPHERE_SEGMENTS, None),
+                normal,
+                socket_depth,
+                *spot,
+            )
+        })
+        .collect();
+
+    let [above, below] = options.plane.lay_flat_rotations();
+    let mut pieces = [(1.0, above), (-1.0, below)].map(|(side, (x, y, z))| {
+        let keep = half_space(&whole_bounds, normal, offset, side);
+        let mut piece_body = body.intersection(&keep);
+        for socket in &sockets {
+            piece_body = piece_body.difference(socket);
+        }
+        SplitPiece {
+            head: head.intersection(&keep).rotate(x, y, z),
+            body: piece_body.rotate(x, y, z),
+        }
+    });
+
+    // sit both pieces on z = 0, side by side
+    // (export turns z up into the STL's y up, same as a whole bird)
+    let mut next_x = 0.0;
+    for piece in pieces.iter_mut() {
+        let mut piece_triangles = mesh_triangles(&piece.body);
+        piece_triangles.extend(mesh_triangles(&piece.head));
+        if piece_triangles.is_empty() {
+            return Err("split plane only grazes the bird, one half is empty".to_string());
+        }
+        let piece_bounds = bounds(&piece_triangles);
+        let shift = DVec3::new(next_x - piece_bounds.min.x, 0.0, -piece_bounds.min.z);
+        piece.head = piece.head.translate(shift.x, shift.y, shift.z);
+        piece.body = piece.body.translate(shift.x, shift.y, shift.z);
+        next_x += piece_bounds.size().x + PIECE_SPACING;
+    }
+
+    // pegs lie on their side next to the pieces
+    let peg_radius = options.peg_diameter as f64 / 2.0;
+    let pegs = (0..peg_spots.len())
+        .map(|i| {
+            CSGMesh::cylinder(peg_radius, options.peg_length as f64, SPHERE_SEGMENTS, None)
+                .rotate(0.0, 90.0, 0.0)
+                .translate(
+                    next_x,
+                    i as f64 * (options.peg_diameter as f64 + PIECE_SPACING),
+                    peg_radius,
+                )
+        })
+        .collect();
+
+    Ok(SplitBird { pieces, pegs })
+}
+
+impl SplitBird {
+    // One STL per piece (plus one for the pegs), as (file name, contents)
+    pub fn to_stl_files(&self, name: &str) -> Vec<(String, Vec<u8>)> {
+        let mut files: Vec<(String, Vec<u8>)> = self
+            .pieces
+            .iter()
+            .zip(["a", "b"])
+            .map(|(piece, suffix)| {
+                let piece_name = format!("{name}-{suffix}");
+                let stl = combine_stl(&piece.body, &piece.head, &piece_name);
+                (format!("{piece_name}.stl"), stl.into_bytes())
+            })
+            .collect();
+        if let Some(pegs) = self.pegs_mesh() {
+            let pegs_name = format!("{name}-pegs");
+            let stl = combine_stl(&pegs, &CSGMesh::new(), &pegs_name);
+            files.push((format!("{pegs_name}.stl"), stl.into_bytes()));
+        }
+        files
+    }
+
+    // Everything in one STL, each piece as its own solid
+    pub fn to_stl(&self, name: &str) -> Vec<u8> {
+        self.to_stl_files(name)
+            .into_iter()
+            .flat_map(|(_, stl)| stl)
+            .collect()
+    }
+
+    // Everything in one 3MF, laid out as it is, with each piece's body and head as their own objects
+    pub fn to_3mf(&self, name: &str) -> Result<Vec<u8>, String> {
+        let pegs = self.pegs_mesh();
+        let [a, b] = &self.pieces;
+        let mut objects = vec![
+            ("a-body", &a.body),
+            ("a-head", &a.head),
+            ("b-body", &b.body),
+            ("b-head", &b.head),
+        ];
+        if let Some(pegs) = &pegs {
+            objects.push(("pegs", pegs));
+        }
+        write_3mf(name, &objects)
+    }
+
+    fn pegs_mesh(&self) -> Option<CSGMesh> {
+        let (first, rest) = self.pegs.split_first()?;
+        Some(rest.iter().fold(first.clone(), |all, peg| all.union(peg)))
+    }
+}
+
+// Box covering everything on one side (`side` = 1 or -1) of the cut
+fn half_space(whole_bounds: &Bounds, normal: DVec3, offset: f64, side: f64) -> CSGMesh {
+    let pad = DVec3::splat(10.0);
+    let mut min = whole_bounds.min - pad;
+    let mut max = whole_bounds.max + pad;
+    if side > 0.0 {
+        min = min * (DVec3::ONE - normal) + normal * offset;
+    } else {
+        max = max * (DVec3::ONE - normal) + normal * offset;
+    }
+    let size = max - min;
+    CSGMesh::cuboid(size.x, size.y, size.z, None).translate(min.x, min.y, min.z)
+}
+
+// Points on the cut face with enough body around them (and behind them, on both sides) for a socket
+fn find_peg_spots(
+    triangles: &[Triangle],
+    whole_bounds: &Bounds,
+    normal: DVec3,
+    offset: f64,
+    options: &SplitOptions,
+) -> Vec<DVec3> {
+    if options.peg_count == 0 {
+        return vec![];
+    }
+    let socket_radius = (options.peg_diameter + options.clearance) as f64 / 2.0;
+    let socket_depth = (options.peg_length / 2.0 + options.clearance) as f64;
+    // leave at least a peg's radius of wall around and behind each socket
+    let wall = options.peg_diameter as f64 / 2.0;
+    let (axis_u, axis_v) = match normal {
+        n if n == DVec3::X => (DVec3::Y, DVec3::Z),
+        n if n == DVec3::Y => (DVec3::X, DVec3::Z),
+        _ => (DVec3::X, DVec3::Y),
+    };
+
+    let u_range = axis_u.dot(whole_bounds.min)..axis_u.dot(whole_bounds.max);
+    let v_range = axis_v.dot(whole_bounds.min)..axis_v.dot(whole_bounds.max);
+    let mut candidates = vec![];
+    let mut u = u_range.start;
+    while u <= u_range.end {
+        let mut v = v_range.start;
+        while v <= v_range.end {
+            let spot = normal * offset + axis_u * u + axis_v * v;
+            let roomy = (0..8).all(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 8.0;
+                let edge =
+                    spot + (axis_u * angle.cos() + axis_v * angle.sin()) * (socket_radius + wall);
+                is_inside(triangles, edge)
+            });
+            let deep = [normal, -normal].iter().all(|dir| {
+                distance_to_surface(triangles, spot, *dir).is_some_and(|d| d >= socket_depth + wall)
+            });
+            if roomy && deep && is_inside(triangles, spot) {
+                candidates.push(spot);
+            }
+            v += PEG_SEARCH_STEP;
+        }
+        u += PEG_SEARCH_STEP;
+    }
+    if candidates.is_empty() {
+        return vec![];
+    }
+
+    // spread the pegs out as far as we can, starting from the one furthest from the middle
+    let middle = candidates.iter().sum::<DVec3>() / candidates.len() as f64;
+    let mut chosen: Vec<DVec3> = vec![];
+    while chosen.len() < options.peg_count {
+        let next = candidates
+            .iter()
+            .filter(|spot| {
+                chosen
+                    .iter()
+                    .all(|c| c.distance(**spot) > socket_radius * 2.0 + wall)
+            })
+            .max_by(|a, b| {
+                let spread = |spot: &DVec3| {
+                    if chosen.is_empty() {
+                        spot.distance(middle)
+                    } else {
+                        chosen
+                            .iter()
+                            .map(|c| c.distance(*spot))
+                            .fold(f64::INFINITY, f64::min)
+                    }
+                };
+                spread(a).total_cmp(&spread(b))
+            });
+        match next {
+            Some(spot) => chosen.push(*spot),
+            None => break,
+        }
+    }
+    chosen
+}
+
+// Cylinder (built along z from 0 to its height) centered on `spot`, running along `normal`
+fn along_normal(cylinder: CSGMesh, normal: DVec3, half_length: f64, spot: DVec3) -> CSGMesh {
+    let centered = cylinder.translate(0.0, 0.0, -half_length);
+    let aligned = match normal {
+        n if n == DVec3::X => centered.rotate(0.0, 90.0, 0.0),
+        n if n == DVec3::Y => centered.rotate(-90.0, 0.0, 0.0),
+        _ => centered,
+    };
+    aligned.translate(spot.x, spot.y, spot.z)
+}
@@ -0,0 +1,225 @@
+// Parameter sweeps: hold a bird still and walk one or two inputs across their ranges, to see what they do
+// Comes out as a labelled contact sheet (rendered on the CPU) or a plain list of seeds
+use crate::{
+    AttachedBird, BirdGenInputTypes, BirdGenInputs, BirdImage, BirdMeshSettings, RenderOptions,
+    batch::map_birds,
+    fitted_size,
+    text::{LINE_HEIGHT, draw_text, text_width},
+};
+
+// (the app's text colours)
+const TEXT_COLOR: [u8; 4] = [230, 230, 230, 255];
+const FADED_TEXT_COLOR: [u8; 4] = [230, 230, 230, 200];
+
+#[derive(Clone, Copy, Debug)]
+pub struct SweepAxis {
+    pub input: BirdGenInputTypes,
+    pub from: f32,
+    pub to: f32,
+    pub steps: usize,
+}
+
+impl SweepAxis {
+    // across everything the input can be
+    pub fn full(input: BirdGenInputTypes, steps: usize) -> Self {
+        let range = input.range();
+        SweepAxis {
+            input,
+            from: *range.start(),
+            to: *range.end(),
+            steps,
+        }
+    }
+
+    // Evenly spaced, and rounded to whole numbers since that's all a seed string keeps
+    // (so every cell's seed gives back exactly the bird in the cell)
+    pub fn values(&self) -> Vec<f32> {
+        let steps = self.steps.max(1);
+        (0..steps)
+            .map(|i| {
+                let t = if steps == 1 {
+                    0.0
+                } else {
+                    i as f32 / (steps - 1) as f32
+                };
+                (self.from + (self.to - self.from) * t).round()
+            })
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} {:.0} to {:.0}", self.input.name(), self.from, self.to)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Sweep {
+    pub base: BirdGenInputs,
+    pub columns: SweepAxis,
+    // leave out for a single row
+    pub rows: Option<SweepAxis>,
+}
+
+#[derive(Clone)]
+pub struct SweepCell {
+    pub row: usize,
+    pub column: usize,
+    pub inputs: BirdGenInputs,
+    // what got swept to what in this cell
+    pub values: Vec<(BirdGenInputTypes, f32)>,
+}
+
+impl SweepCell {
+    // e.g. ["beak_length 25", "head_size 30"]
+    pub fn labels(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .map(|(input, value)| format!("{} {value:.0}", input.name()))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ContactSheetOptions {
+    // px, each bird gets a square this big (plus its labels underneath)
+    pub cell_size: u32,
+    pub settings: BirdMeshSettings,
+    // colours and camera (the size gets set per cell)
+    pub render: RenderOptions,
+    // render every cell at the same scale, so size changes show up (otherwise each bird fills its cell)
+    pub same_scale: bool,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        ContactSheetOptions {
+            cell_size: 192,
+            settings: BirdMeshSettings::default(),
+            render: RenderOptions::default(),
+            same_scale: true,
+        }
+    }
+}
+
+impl Sweep {
+    pub fn describe(&self) -> String {
+        match &self.rows {
+            Some(rows) => format!("{} x {}", self.columns.describe(), rows.describe()),
+            None => self.columns.describe(),
+        }
+    }
+
+    // row by row, left to right
+    pub fn cells(&self) -> Vec<SweepCell> {
+        let row_values: Vec<Option<f32>> = match &self.rows {
+            Some(rows) => rows.values().into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+        let mut cells = vec![];
+        for (row, row_value) in row_values.into_iter().enumerate() {
+            for (column, column_value) in self.columns.values().into_iter().enumerate() {
+                let mut inputs = self.base;
+                let mut values = vec![(self.columns.input, column_value)];
+                inputs.set_input_value_for_type(&self.columns.input, column_value);
+                if let (Some(rows), Some(row_value)) = (&self.rows, row_value) {
+                    inputs.set_input_value_for_type(&rows.input, row_value);
+                    values.push((rows.input, row_value));
+                }
+                cells.push(SweepCell {
+                    row,
+                    column,
+                    inputs,
+                    values,
+                });
+            }
+        }
+        cells
+    }
+
+    // One seed per cell, each with a comment saying which cell it is. Comment lines start with #
+    pub fn seed_list(&self) -> String {
+        let mut lines = vec![
+            format!("# sweep of {}", self.describe()),
+            format!("# base {}", self.base.get_bird_seed_string()),
+        ];
+        for cell in self.cells() {
+            lines.push(format!("# {}", cell.labels().join(", ")));
+            lines.push(cell.inputs.get_bird_seed_string());
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // Every cell rendered into one labelled grid image
+    pub fn contact_sheet(&self, options: &ContactSheetOptions) -> BirdImage {
+        let cells = self.cells();
+        // (necks get fixed for the picture, but each cell stays labelled with what was swept)
+        let meshes = map_birds(&cells, |cell| {
+            AttachedBird::generate(&cell.inputs, &options.settings).meshes
+        });
+        let cell_size = options.cell_size.max(16);
+        let mut render = RenderOptions {
+            width: cell_size,
+            height: cell_size,
+            ..options.render
+        };
+        if options.same_scale {
+            render.fit_size = meshes
+                .iter()
+                .map(|bird| fitted_size(&bird.head, &bird.body, &render))
+                .reduce(f64::max);
+        }
+        let images = map_birds(&meshes, |bird| bird.render(&render));
+
+        // bigger cells get bigger text
+        let text_scale = (cell_size / 128).max(1);
+        let line = LINE_HEIGHT * text_scale;
+        let gap = 4 * text_scale;
+        let columns = self.columns.steps.max(1) as u32;
+        let rows = self.rows.map_or(1, |rows| rows.steps.max(1)) as u32;
+        let label_lines = if self.rows.is_some() { 2 } else { 1 };
+        let header = gap + 2 * line;
+        let (cell_width, cell_height) = (cell_size + gap, cell_size + label_lines * line + gap);
+
+        let mut sheet = BirdImage::new(
+            columns * cell_width + gap,
+            header + rows * cell_height + gap,
+            options.render.background,
+        );
+        draw_text(
+            &mut sheet,
+            gap,
+            gap,
+            &self.describe(),
+            TEXT_COLOR,
+            text_scale,
+        );
+        let base = format!("base {}", self.base.get_bird_seed_string());
+        draw_text(
+            &mut sheet,
+            gap,
+            gap + line,
+            &base,
+            FADED_TEXT_COLOR,
+            text_scale,
+        );
+        for (cell, image) in cells.iter().zip(&images) {
+            let x = gap + cell.column as u32 * cell_width;
+            let y = header + gap + cell.row as u32 * cell_height;
+            sheet.draw_image(image, x, y);
+            for (i, label) in cell.labels().iter().enumerate() {
+                // centred under the bird if it fits
+                let indent = cell_size.saturating_sub(text_width(label, text_scale)) / 2;
+                let label_y = y + cell_size + i as u32 * line;
+                draw_text(
+                    &mut sheet,
+                    x + indent,
+                    label_y,
+                    label,
+                    TEXT_COLOR,
+                    text_scale,
+                );
+            }
+        }
+        sheet
+    }
+}
@@ -0,0 +1,351 @@
+// Voxel birds for voxel games: MagicaVoxel .vox and Minecraft (Sponge schematic) .schem
+// Every part gets filled in a column at a time, casting a ray up each column of voxel centres and
+// filling between where it goes into the part and where it comes back out
+use crate::{
+    BirdPartKind, BirdParts,
+    export::to_export_orientation,
+    geometry::{Triangle, mesh_triangles, ray_spans},
+};
+use bevy::math::DVec3;
+use flate2::{Compression, write::GzEncoder};
+use std::io::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoxelMaterial {
+    Body,
+    Beak,
+    Eyes,
+}
+
+impl VoxelMaterial {
+    pub const ALL: [VoxelMaterial; 3] = [
+        VoxelMaterial::Body,
+        VoxelMaterial::Beak,
+        VoxelMaterial::Eyes,
+    ];
+
+    // palette index, 0 is empty
+    pub fn index(&self) -> u8 {
+        match self {
+            VoxelMaterial::Body => 1,
+            VoxelMaterial::Beak => 2,
+            VoxelMaterial::Eyes => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoxelMaterial::Body => "body",
+            VoxelMaterial::Beak => "beak",
+            VoxelMaterial::Eyes => "eyes",
+        }
+    }
+
+    // (the skull and tail are just more bird)
+    fn for_part(kind: BirdPartKind) -> Self {
+        match kind {
+            BirdPartKind::Beak => VoxelMaterial::Beak,
+            BirdPartKind::Eyes => VoxelMaterial::Eyes,
+            BirdPartKind::Skull | BirdPartKind::Body | BirdPartKind::Tail => VoxelMaterial::Body,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VoxelOptions {
+    // voxels along the bird's longest side (.vox tops out at 256)
+    pub size: usize,
+    pub body_color: [u8; 3],
+    pub beak_color: [u8; 3],
+    pub eye_color: [u8; 3],
+}
+
+impl Default for VoxelOptions {
+    fn default() -> Self {
+        VoxelOptions {
+            size: 64,
+            // (same yellow as the renders)
+            body_color: [247, 179, 43],
+            beak_color: [232, 106, 23],
+            eye_color: [26, 26, 26],
+        }
+    }
+}
+
+// Export orientation: x along the bird (beak towards 0), y up, z across
+#[derive(Clone, Debug)]
+pub struct BirdVoxels {
+    pub size: [usize; 3],
+    // mm per voxel
+    pub voxel_size: f64,
+    // palette index for each voxel (0 for empty), x fastest, then z, then y, like a schematic
+    pub voxels: Vec<u8>,
+    // by palette index - 1
+    pub colors: [[u8; 3]; 3],
+}
+
+// the largest model MagicaVoxel will open
+const MAX_SIZE: usize = 256;
+// a hair off the middle of each column, so rays don't run straight down mesh edges
+const COLUMN_NUDGE: f64 = 1.3e-4;
+// Minecraft 1.20.1, what WorldEdit reads the block names against
+const DATA_VERSION: i32 = 3465;
+// roughly what each concrete block looks like, for picking the closest one to each colour
+const CONCRETE: [(&str, [u8; 3]); 16] = [
+    ("white", [207, 213, 214]),
+    ("orange", [224, 97, 1]),
+    ("magenta", [169, 48, 159]),
+    ("light_blue", [36, 137, 199]),
+    ("yellow", [241, 175, 21]),
+    ("lime", [94, 169, 24]),
+    ("pink", [214, 101, 143]),
+    ("gray", [54, 57, 61]),
+    ("light_gray", [125, 125, 115]),
+    ("cyan", [21, 119, 136]),
+    ("purple", [100, 32, 156]),
+    ("blue", [45, 47, 143]),
+    ("brown", [96, 60, 32]),
+    ("green", [73, 91, 36]),
+    ("red", [142, 33, 33]),
+    ("black", [8, 10, 15]),
+];
+
+impl BirdVoxels {
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (y * self.size[2] + z) * self.size[0] + x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.voxels[self.index(x, y, z)]
+    }
+
+    pub fn count(&self, material: VoxelMaterial) -> usize {
+        self.voxels
+            .iter()
+            .filter(|voxel| **voxel == material.index())
+            .count()
+    }
+
+    pub fn color(&self, material: VoxelMaterial) -> [u8; 3] {
+        self.colors[material.index() as usize - 1]
+    }
+
+    // MagicaVoxel's format is z up, so the bird gets stood up the right way for it on the way out
+    pub fn to_vox(&self) -> Vec<u8> {
+        let [width, height, depth] = self.size;
+        let chunk = |id: &[u8; 4], content: &[u8]| {
+            let mut chunk = id.to_vec();
+            chunk.extend((content.len() as i32).to_le_bytes());
+            chunk.extend(0i32.to_le_bytes());
+            chunk.extend(content);
+            chunk
+        };
+        let mut size = vec![];
+        for side in [width, depth, height] {
+            size.extend((side as i32).to_le_bytes());
+        }
+        let mut xyzi = vec![];
+        let mut count = 0i32;
+        for y in 0..height {
+            for z in 0..depth {
+                for x in 0..width {
+                    let voxel = self.get(x, y, z);
+                    if voxel != 0 {
+                        // (turned about x, so it's not mirrored)
+                        xyzi.extend([x as u8, (depth - 1 - z) as u8, y as u8, voxel]);
+                        count += 1;
+                    }
+                }
+            }
+        }
+        let mut counted = count.to_le_bytes().to_vec();
+        counted.extend(xyzi);
+        // palette entry i is colour index i + 1, anything we don't use is white
+        let mut rgba = vec![];
+        for i in 0..256 {
+            let [r, g, b] = self.colors.get(i).copied().unwrap_or([255, 255, 255]);
+            rgba.extend([r, g, b, 255]);
+        }
+        let children = [
+            chunk(b"SIZE", &size),
+            chunk(b"XYZI", &counted),
+            chunk(b"RGBA", &rgba),
+        ]
+        .concat();
+        let mut main = b"MAIN".to_vec();
+        main.extend(0i32.to_le_bytes());
+        main.extend((children.len() as i32).to_le_bytes());
+        main.extend(children);
+
+        let mut vox = b"VOX ".to_vec();
+        vox.extend(150i32.to_le_bytes());
+        vox.extend(main);
+        vox
+    }
+
+    // Which block each material turns into, the closest coloured concrete
+    pub fn blocks(&self) -> [String; 3] {
+        self.colors.map(|color| {
+            let (name, _) = CONCRETE
+                .iter()
+                .min_by_key(|(_, block)| {
+                    (0..3)
+                        .map(|i| (color[i] as i32 - block[i] as i32).pow(2))
+                        .sum::<i32>()
+                })
+                .expect("there's concrete");
+            format!("minecraft:{name}_concrete")
+        })
+    }
+
+    // Sponge schematic (version 2), which WorldEdit and most other tools can paste
+    pub fn to_schem(&self) -> Result<Vec<u8>, String> {
+        let [width, height, length] = self.size;
+        let mut nbt = vec![];
+        nbt_tag(&mut nbt, NBT_COMPOUND, "Schematic");
+        nbt_tag(&mut nbt, NBT_INT, "Version");
+        nbt.extend(2i32.to_be_bytes());
+        nbt_tag(&mut nbt, NBT_INT, "DataVersion");
+        nbt.extend(DATA_VERSION.to_be_bytes());
+        for (name, side) in [("Width", width), ("Height", height), ("Length", length)] {
+            nbt_tag(&mut nbt, NBT_SHORT, name);
+            nbt.extend((side as i16).to_be_bytes());
+        }
+
+        // air's always 0, then each different block in turn (two materials can end up as the same block)
+        let mut palette: Vec<String> = vec!["minecraft:air".to_string()];
+        let mut block_ids = [0u8; 4];
+        for (material, block) in self.blocks().into_iter().enumerate() {
+            block_ids[material + 1] = match palette.iter().position(|known| *known == block) {
+                Some(id) => id as u8,
+                None => {
+                    palette.push(block);
+                    (palette.len() - 1) as u8
+                }
+            };
+        }
+        nbt_tag(&mut nbt, NBT_INT, "PaletteMax");
+        nbt.extend((palette.len() as i32).to_be_bytes());
+        nbt_tag(&mut nbt, NBT_COMPOUND, "Palette");
+        for (id, block) in palette.iter().enumerate() {
+            nbt_tag(&mut nbt, NBT_INT, block);
+            nbt.extend((id as i32).to_be_bytes());
+        }
+        nbt.push(NBT_END);
+
+        // (varints, but with a handful of blocks every id fits in one byte)
+        let blocks: Vec<u8> = self
+            .voxels
+            .iter()
+            .map(|voxel| block_ids[*voxel as usize])
+            .collect();
+        nbt_tag(&mut nbt, NBT_BYTE_ARRAY, "BlockData");
+        nbt.extend((blocks.len() as i32).to_be_bytes());
+        nbt.extend(blocks);
+        nbt_tag(&mut nbt, NBT_LIST, "BlockEntities");
+        nbt.push(NBT_COMPOUND);
+        nbt.extend(0i32.to_be_bytes());
+        nbt.push(NBT_END);
+
+        let mut gzip = GzEncoder::new(vec![], Compression::default());
+        gzip.write_all(&nbt).map_err(|e| e.to_string())?;
+        gzip.finish().map_err(|e| e.to_string())
+    }
+}
+
+const NBT_END: u8 = 0;
+const NBT_SHORT: u8 = 2;
+const NBT_INT: u8 = 3;
+const NBT_BYTE_ARRAY: u8 = 7;
+const NBT_LIST: u8 = 9;
+const NBT_COMPOUND: u8 = 10;
+
+// a named tag's header, the payload goes straight after
+fn nbt_tag(nbt: &mut Vec<u8>, kind: u8, name: &str) {
+    nbt.push(kind);
+    nbt.extend((name.len() as u16).to_be_bytes());
+    nbt.extend(name.as_bytes());
+}
+
+impl BirdParts {
+    pub fn voxelise(&self, options: &VoxelOptions) -> BirdVoxels {
+        voxelise(self, options)
+    }
+}
+
+pub fn voxelise(parts: &BirdParts, options: &VoxelOptions) -> BirdVoxels {
+    let colors = [options.body_color, options.beak_color, options.eye_color];
+    let mut parts: Vec<(VoxelMaterial, Vec<Triangle>)> = parts
+        .parts
+        .iter()
+        .map(|part| {
+            let triangles = mesh_triangles(&to_export_orientation(&part.mesh));
+            (VoxelMaterial::for_part(part.kind), triangles)
+        })
+        .collect();
+    let (mut min, mut max) = (DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY));
+    for point in parts.iter().flat_map(|(_, triangles)| triangles).flatten() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    if !min.x.is_finite() {
+        return BirdVoxels {
+            size: [0; 3],
+            voxel_size: 0.0,
+            voxels: vec![],
+            colors,
+        };
+    }
+
+    let voxel_size =
+        (max - min).max_element().max(f64::EPSILON) / options.size.clamp(1, MAX_SIZE) as f64;
+    let size = ((max - min) / voxel_size)
+        .ceil()
+        .to_array()
+        .map(|side| (side as usize).clamp(1, MAX_SIZE));
+    let mut voxels = BirdVoxels {
+        size,
+        voxel_size,
+        voxels: vec![0; size[0] * size[1] * size[2]],
+        colors,
+    };
+    // eyes last, so they win anywhere parts touch
+    parts.sort_by_key(|(material, _)| material.index());
+    for (material, triangles) in &parts {
+        for x in 0..size[0] {
+            let column_x = min.x + (x as f64 + 0.5 + COLUMN_NUDGE) * voxel_size;
+            // only the triangles this slice of columns could hit
+            let slice: Vec<Triangle> = triangles
+                .iter()
+                .filter(|triangle| {
+                    let (low, high) = triangle
+                        .iter()
+                        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), point| {
+                            (low.min(point.x), high.max(point.x))
+                        });
+                    low <= column_x && column_x <= high
+                })
+                .copied()
+                .collect();
+            if slice.is_empty() {
+                continue;
+            }
+            for z in 0..size[2] {
+                let column_z = min.z + (z as f64 + 0.5 + COLUMN_NUDGE * 0.7) * voxel_size;
+                let start = DVec3::new(column_x, min.y - voxel_size, column_z);
+                for (enter, exit) in ray_spans(&slice, start, DVec3::Y) {
+                    // voxel centres between where the ray goes in and comes out
+                    let (enter, exit) = (start.y + enter - min.y, start.y + exit - min.y);
+                    let first = (enter / voxel_size - 0.5).ceil().max(0.0) as usize;
+                    let last =
+                        ((exit / voxel_size - 0.5).floor() as isize).min(size[1] as isize - 1);
+                    for y in first as isize..=last {
+                        let index = voxels.index(x, y as usize, z);
+                        voxels.voxels[index] = material.index();
+                    }
+                }
+            }
+        }
+    }
+    voxels
+}
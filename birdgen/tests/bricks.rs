@@ -0,0 +1,107 @@
+// Brick birds: every voxel in a layer gets exactly one brick of its own colour, the biggest that fits,
+// and the parts list and .ldr agree on what's in the model
+use birdgen::{BirdVoxels, BrickOptions, VoxelMaterial};
+use std::collections::HashMap;
+
+// 4 studs by 2, 6 voxels (5 layers) tall, with an orange 1x2 strip on one end of the top half
+fn block() -> BirdVoxels {
+    let mut voxels = BirdVoxels {
+        size: [4, 6, 2],
+        voxel_size: 1.0,
+        voxels: vec![VoxelMaterial::Body.index(); 48],
+        colors: [[247, 179, 43], [232, 106, 23], [26, 26, 26]],
+    };
+    for y in 3..6 {
+        for z in 0..2 {
+            let index = voxels.index(3, y, z);
+            voxels.voxels[index] = VoxelMaterial::Beak.index();
+        }
+    }
+    voxels
+}
+
+#[test]
+fn every_stud_gets_one_brick() {
+    let voxels = block();
+    let model = voxels.to_bricks(&BrickOptions::default());
+    assert_eq!(model.layers, 5);
+    assert_eq!(model.studs, [4, 2]);
+    for layer in 0..model.layers {
+        let mut covered = HashMap::new();
+        for brick in model.bricks.iter().filter(|brick| brick.layer == layer) {
+            let [size_x, size_z] = brick.footprint();
+            for x in brick.x..brick.x + size_x {
+                for z in brick.z..brick.z + size_z {
+                    let before = covered.insert((x, z), brick.material.index());
+                    assert!(before.is_none(), "two bricks on {x},{z}");
+                }
+            }
+        }
+        // (layers take every 1.2th voxel row)
+        let y = (layer as f64 * 1.2 + 0.6) as usize;
+        assert_eq!(covered.len(), 8);
+        for ((x, z), material) in covered {
+            assert_eq!(material, voxels.get(x, y, z), "layer {layer} at {x},{z}");
+        }
+    }
+    // the all yellow bottom layer's one 2x4, the top's a 2x3 and an orange 1x2 turned across
+    let layer = |layer: usize| -> Vec<_> {
+        model
+            .bricks
+            .iter()
+            .filter(|brick| brick.layer == layer)
+            .map(|brick| (brick.part.ldraw, brick.footprint(), brick.material))
+            .collect()
+    };
+    assert_eq!(layer(0), [("3001.dat", [4, 2], VoxelMaterial::Body)]);
+    assert_eq!(
+        layer(4),
+        [
+            ("3002.dat", [3, 2], VoxelMaterial::Body),
+            ("3004.dat", [1, 2], VoxelMaterial::Beak)
+        ]
+    );
+}
+
+#[test]
+fn one_wide_bricks_only() {
+    let model = block().to_bricks(&BrickOptions {
+        max_length: 3,
+        two_wide: false,
+    });
+    assert!(
+        model
+            .bricks
+            .iter()
+            .all(|brick| brick.part.width == 1 && brick.part.length <= 3)
+    );
+}
+
+#[test]
+fn parts_list_matches_the_model() {
+    let model = block().to_bricks(&BrickOptions::default());
+    let list = model.parts_list();
+    assert_eq!(
+        list.iter().map(|counted| counted.count).sum::<usize>(),
+        model.bricks.len()
+    );
+    assert_eq!(list[0].color.name, "Yellow");
+    assert!(list.iter().any(|counted| counted.color.name == "Orange"));
+    let csv = model.to_parts_csv();
+    assert_eq!(csv.lines().count(), list.len() + 1);
+
+    let ldr = model.to_ldr("block");
+    let bricks: Vec<Vec<&str>> = ldr
+        .lines()
+        .filter(|line| line.starts_with("1 "))
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(bricks.len(), model.bricks.len());
+    assert!(bricks.iter().all(|brick| brick.len() == 15));
+    assert_eq!(
+        ldr.lines().filter(|line| *line == "0 STEP").count(),
+        model.layers
+    );
+    // bottom layer sits on the ground (LDraw's y is down), centred
+    assert_eq!(bricks[0][..5], ["1", "14", "0", "-24", "0"]);
+}
@@ -0,0 +1,70 @@
+// The mesh cache: hits and misses get counted, and a full cache drops whichever bird was used longest ago
+use birdgen::{BirdGenInputs, BirdMeshSettings, BirdMeshes, MeshCache, MeshCacheStats, Precision};
+
+mod common;
+use common::good_bird_inputs;
+
+// the good bird with a different head size each, so they're all different birds
+fn birds() -> [BirdGenInputs; 3] {
+    [20.0, 25.0, 30.0].map(|head_size| BirdGenInputs {
+        head_size,
+        ..good_bird_inputs()
+    })
+}
+
+#[test]
+fn counts_hits_and_misses() {
+    let settings = BirdMeshSettings::default();
+    let [bird, other, _] = birds();
+    let mut cache = MeshCache::new(2);
+    assert!(cache.get(&bird, &settings).is_none());
+    // (the cache doesn't care what the meshes are of, so a low precision build does for all of them)
+    let meshes = BirdMeshes::generate(
+        &bird,
+        &BirdMeshSettings {
+            precision: Precision::Low,
+            ..settings
+        },
+    );
+    cache.insert(&bird, &settings, meshes);
+    assert!(cache.get(&bird, &settings).is_some());
+    assert!(cache.get(&other, &settings).is_none());
+    // same bird, different settings
+    let high = BirdMeshSettings {
+        precision: Precision::High,
+        ..settings
+    };
+    assert!(cache.get(&bird, &high).is_none());
+    assert_eq!(
+        cache.stats(),
+        MeshCacheStats {
+            hits: 1,
+            misses: 3,
+            entries: 1,
+            capacity: 2,
+        }
+    );
+}
+
+#[test]
+fn evicts_the_least_recently_used() {
+    let settings = BirdMeshSettings::default();
+    let [first, second, third] = birds();
+    let meshes = BirdMeshes::generate(
+        &first,
+        &BirdMeshSettings {
+            precision: Precision::Low,
+            ..settings
+        },
+    );
+    let mut cache = MeshCache::new(2);
+    cache.insert(&first, &settings, meshes.clone());
+    cache.insert(&second, &settings, meshes.clone());
+    // using the first one makes the second the oldest
+    assert!(cache.get(&first, &settings).is_some());
+    cache.insert(&third, &settings, meshes);
+    assert_eq!(cache.stats().entries, 2);
+    assert!(cache.get(&second, &settings).is_none());
+    assert!(cache.get(&first, &settings).is_some());
+    assert!(cache.get(&third, &settings).is_some());
+}
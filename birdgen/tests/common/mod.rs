@@ -0,0 +1,32 @@
+// Birds shared between the test files (each file only uses some of them)
+#![allow(dead_code)]
+use birdgen::{BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, GOOD_BIRDS};
+
+pub fn bird(seed: &str) -> BirdGenInputs {
+    let mut bird = BirdGenInputs::default();
+    bird.update_from_seed_string(seed.to_string())
+        .expect("good birds have good seeds");
+    bird
+}
+
+// the first of the known good birds, which all the mesh tests use
+pub fn good_bird_inputs() -> BirdGenInputs {
+    bird(GOOD_BIRDS[0])
+}
+
+pub fn good_bird() -> BirdCSGMeshes {
+    BirdCSGMeshes::generate(&good_bird_inputs(), &BirdMeshSettings::default())
+}
+
+// the first good bird with its head offsets at the top of their ranges, and a small squished
+// head with no eyes (big eyes hang down far enough to reach the neck anyway), so the head floats
+pub fn floating_head_bird() -> BirdGenInputs {
+    BirdGenInputs {
+        head_level: 80.0,
+        head_lateral_offset: 15.0,
+        head_size: 10.0,
+        beak_size: 40.0,
+        eye_size: 0.0,
+        ..good_bird_inputs()
+    }
+}
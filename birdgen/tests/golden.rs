@@ -0,0 +1,242 @@
+// Golden geometry checks over every GOOD_BIRDS seed
+// Each bird's head and body get compared against tests/golden/good_birds.txt, so changes to
+// the CSG code (or the csgrs fork) that move the geometry show up here before a release
+// If the change is on purpose, re-record with `BIRDGEN_BLESS=1 cargo test -p birdgen --test golden`
+use birdgen::{
+    BirdGenInputs, CSGMesh, GOOD_BIRDS, generate_bird_body_csg_mesh, generate_bird_head_csg_mesh,
+    geometry::{Bounds, bounds, mesh_triangles, surface_area, volume},
+};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+mod common;
+
+const SNAPSHOT_FILE: &str = "tests/golden/good_birds.txt";
+const BLESS_VAR: &str = "BIRDGEN_BLESS";
+
+// how far things can drift before we call it a regression
+const TRIANGLE_TOLERANCE: f64 = 0.01;
+// (bounds are in mm, the rest are relative)
+const BOUNDS_TOLERANCE: f64 = 0.05;
+const MEASURE_TOLERANCE: f64 = 0.01;
+// how close the bottom of the body has to be to where the base cut should be
+const BASE_CUT_TOLERANCE: f64 = 1e-3;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Snapshot {
+    triangles: usize,
+    bounds: Bounds,
+    volume: f64,
+    area: f64,
+}
+
+impl Snapshot {
+    fn of(mesh: &CSGMesh) -> Self {
+        let triangles = mesh_triangles(mesh);
+        Snapshot {
+            triangles: triangles.len(),
+            bounds: bounds(&triangles),
+            volume: volume(&triangles),
+            area: surface_area(&triangles),
+        }
+    }
+
+    fn to_line(&self, key: &str) -> String {
+        let (min, max) = (self.bounds.min, self.bounds.max);
+        format!(
+            "{key} {} {:.4} {:.4} {:.4} {:.4} {:.4} {:.4} {:.4} {:.4}",
+            self.triangles, min.x, min.y, min.z, max.x, max.y, max.z, self.volume, self.area
+        )
+    }
+
+    fn from_line(line: &str) -> Option<(String, Snapshot)> {
+        let mut fields = line.split_whitespace();
+        let key = format!("{} {}", fields.next()?, fields.next()?);
+        let triangles = fields.next()?.parse().ok()?;
+        let numbers: Vec<f64> = fields
+            .map(|field| field.parse().ok())
+            .collect::<Option<_>>()?;
+        let [min_x, min_y, min_z, max_x, max_y, max_z, volume, area] = numbers[..] else {
+            return None;
+        };
+        Some((
+            key,
+            Snapshot {
+                triangles,
+                bounds: Bounds {
+                    min: [min_x, min_y, min_z].into(),
+                    max: [max_x, max_y, max_z].into(),
+                },
+                volume,
+                area,
+            },
+        ))
+    }
+
+    // everything that's drifted too far, empty if we're good
+    fn differences(&self, expected: &Snapshot) -> Vec<String> {
+        let mut differences = vec![];
+        let close = |actual: f64, expected: f64, tolerance: f64| {
+            (actual - expected).abs() <= tolerance * expected.abs().max(1.0)
+        };
+        if !close(
+            self.triangles as f64,
+            expected.triangles as f64,
+            TRIANGLE_TOLERANCE,
+        ) {
+            differences.push(format!(
+                "triangles {} (expected {})",
+                self.triangles, expected.triangles
+            ));
+        }
+        let corners = [
+            (self.bounds.min, expected.bounds.min),
+            (self.bounds.max, expected.bounds.max),
+        ];
+        for (actual, expected) in corners {
+            if (actual - expected).abs().max_element() > BOUNDS_TOLERANCE {
+                differences.push(format!("bounds corner {actual} (expected {expected})"));
+            }
+        }
+        if !close(self.volume, expected.volume, MEASURE_TOLERANCE) {
+            differences.push(format!(
+                "volume {:.4} (expected {:.4})",
+                self.volume, expected.volume
+            ));
+        }
+        if !close(self.area, expected.area, MEASURE_TOLERANCE) {
+            differences.push(format!(
+                "area {:.4} (expected {:.4})",
+                self.area, expected.area
+            ));
+        }
+        differences
+    }
+}
+
+fn good_birds() -> Vec<(String, BirdGenInputs)> {
+    GOOD_BIRDS
+        .iter()
+        .map(|seed| (seed.to_string(), common::bird(seed)))
+        .collect()
+}
+
+fn snapshot_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT_FILE)
+}
+
+fn read_snapshots() -> Option<HashMap<String, Snapshot>> {
+    let contents = fs::read_to_string(snapshot_path()).ok()?;
+    Some(
+        contents
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .filter_map(Snapshot::from_line)
+            .collect(),
+    )
+}
+
+fn write_snapshots(lines: &[String]) {
+    let header = "# seed part triangles min_x min_y min_z max_x max_y max_z volume area\n";
+    let contents = format!("{header}{}\n", lines.join("\n"));
+    let path = snapshot_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("couldn't make the golden snapshot folder");
+    }
+    fs::write(path, contents).expect("couldn't write golden snapshots");
+}
+
+#[test]
+fn good_birds_match_golden_snapshots() {
+    let bless = std::env::var_os(BLESS_VAR).is_some();
+    let expected = if bless {
+        None
+    } else {
+        // a missing snapshot is a failure, not a free pass (CI would just record whatever it got)
+        Some(read_snapshots().unwrap_or_else(|| {
+            panic!(
+                "no golden snapshots at {}, run with {BLESS_VAR}=1 to record them",
+                snapshot_path().display()
+            )
+        }))
+    };
+
+    let mut lines = vec![];
+    let mut failures = vec![];
+    for (seed, bird) in good_birds() {
+        let parts = [
+            ("head", generate_bird_head_csg_mesh(&bird)),
+            ("body", generate_bird_body_csg_mesh(&bird)),
+        ];
+        for (part, mesh) in parts {
+            let key = format!("{seed} {part}");
+            let snapshot = Snapshot::of(&mesh);
+            lines.push(snapshot.to_line(&key));
+            let Some(expected) = &expected else {
+                continue;
+            };
+            match expected.get(&key) {
+                Some(expected_snapshot) => {
+                    for difference in snapshot.differences(expected_snapshot) {
+                        failures.push(format!("{key}: {difference}"));
+                    }
+                }
+                None => failures.push(format!("{key}: no snapshot, re-record with {BLESS_VAR}=1")),
+            }
+        }
+    }
+
+    if bless {
+        write_snapshots(&lines);
+        eprintln!("recorded golden snapshots to {}", snapshot_path().display());
+    }
+    assert!(
+        failures.is_empty(),
+        "{} golden mismatches:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn good_birds_are_well_formed() {
+    let mut failures = vec![];
+    for (seed, bird) in good_birds() {
+        let head = mesh_triangles(&generate_bird_head_csg_mesh(&bird));
+        let body = mesh_triangles(&generate_bird_body_csg_mesh(&bird));
+
+        if head.is_empty() {
+            failures.push(format!("{seed}: empty head"));
+        }
+        if body.is_empty() {
+            failures.push(format!("{seed}: empty body"));
+        }
+        if head.iter().chain(&body).flatten().any(|p| !p.is_finite()) {
+            failures.push(format!("{seed}: NaN/infinite vertices"));
+        }
+
+        if let (Some(base_height), false) = (bird.base_height(), body.is_empty()) {
+            let base_height = base_height as f64;
+            let lowest = bounds(&body).min.z;
+            if lowest < base_height - BASE_CUT_TOLERANCE {
+                failures.push(format!(
+                    "{seed}: body reaches z {lowest:.4}, below the base cut at {base_height:.4}"
+                ));
+            }
+            // the chest alone reaches half a belly down, so a cut above that always takes something off
+            let chest_bottom = -bird.belly_size as f64 / 2.0;
+            if base_height > chest_bottom + BASE_CUT_TOLERANCE
+                && (lowest - base_height).abs() > BASE_CUT_TOLERANCE
+            {
+                failures.push(format!(
+                    "{seed}: flat base at z {lowest:.4}, expected {base_height:.4}"
+                ));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} malformed birds:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
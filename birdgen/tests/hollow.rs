@@ -0,0 +1,43 @@
+// Hollowing: the cavity has to stay inside the body for every good bird (the tail used to poke
+// out through the walls), and a wall thicker than the bird is an error, not a mangled bird
+use birdgen::{
+    BirdCSGMeshes, GOOD_BIRDS, HollowOptions,
+    geometry::{is_inside, mesh_triangles},
+    hollow_body, plan_hollowing,
+};
+
+mod common;
+use common::bird;
+
+#[test]
+fn cavity_stays_inside_the_walls() {
+    let options = HollowOptions::default();
+    for seed in GOOD_BIRDS {
+        let body = BirdCSGMeshes::generate(&bird(seed), &Default::default()).body;
+        let hollowing = plan_hollowing(&body, &options).expect("good birds hollow");
+        let outer = mesh_triangles(&body);
+        let cavity = mesh_triangles(&hollowing.cavity);
+        assert!(
+            cavity
+                .iter()
+                .flatten()
+                .all(|&point| is_inside(&outer, point)),
+            "{seed} broke through its walls"
+        );
+        assert!(
+            hollowing.achieved_wall_thickness >= options.wall_thickness as f64 * 0.9,
+            "{seed} only got {:.2}mm walls",
+            hollowing.achieved_wall_thickness
+        );
+    }
+}
+
+#[test]
+fn walls_thicker_than_the_bird_fail() {
+    let body = BirdCSGMeshes::generate(&bird(GOOD_BIRDS[0]), &Default::default()).body;
+    let options = HollowOptions {
+        wall_thickness: 1000.0,
+        ..Default::default()
+    };
+    assert!(hollow_body(body, &options).is_err());
+}
@@ -0,0 +1,24 @@
+// Measuring: where the head and body overlap only gets counted once, checked on two cubes
+// pushed halfway into each other, which make a 15x10x10 box between them
+use birdgen::{BirdMetrics, CSGMesh, FilamentOptions};
+use csgrs::csg::CSG;
+
+#[test]
+fn overlap_counts_once() {
+    let head = CSGMesh::cuboid(10.0, 10.0, 10.0, None).translate(5.0, 0.0, 0.0);
+    let body = CSGMesh::cuboid(10.0, 10.0, 10.0, None);
+    let metrics = BirdMetrics::measure(&head, &body, &FilamentOptions::default());
+    assert!(
+        (metrics.volume - 1500.0).abs() < 1e-6,
+        "{} mm3",
+        metrics.volume
+    );
+    let area = 2.0 * (15.0 * 10.0 + 15.0 * 10.0 + 10.0 * 10.0);
+    assert!(
+        (metrics.surface_area - area).abs() < 1e-6,
+        "{} mm2",
+        metrics.surface_area
+    );
+    let size = metrics.bounds.size();
+    assert!((size.x - 15.0).abs() < 1e-6 && (size.y - 10.0).abs() < 1e-6);
+}
@@ -0,0 +1,86 @@
+// Keeping heads on: a bird with its head pushed as far out as the inputs go floats, and both
+// neck fixes put it back on (clamping hands back the inputs it actually built)
+use birdgen::{
+    AttachedBird, BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, NeckChange, NeckFix, attach_head,
+    head_is_floating,
+};
+
+mod common;
+use common::floating_head_bird;
+
+fn seed(bird: &BirdGenInputs) -> String {
+    bird.get_bird_seed_string()
+}
+
+#[test]
+fn extreme_offsets_float_without_a_fix() {
+    let bird = floating_head_bird();
+    let meshes = BirdCSGMeshes::generate(&bird, &BirdMeshSettings::default());
+    assert!(head_is_floating(&meshes));
+}
+
+#[test]
+fn clamping_puts_the_head_back_on() {
+    let bird = floating_head_bird();
+    let attached = attach_head(&bird, &BirdMeshSettings::default(), NeckFix::ClampOffsets);
+    assert!(attached.report.was_floating);
+    assert!(!attached.report.still_floating);
+    assert!(!head_is_floating(&attached.meshes));
+
+    // the report says which inputs moved, and the inputs handed back are the moved ones
+    assert!(!attached.report.changes.is_empty());
+    for change in &attached.report.changes {
+        let NeckChange::Clamped { input, from, to } = *change else {
+            panic!("clamping only clamps, got {}", change.describe());
+        };
+        let value = match input {
+            "head_level" => attached.inputs.head_level,
+            "head_lateral_offset" => attached.inputs.head_lateral_offset,
+            _ => panic!("clamped {input}, which isn't a head offset"),
+        };
+        assert_eq!(value, to);
+        assert!(to.abs() < from.abs());
+    }
+    assert_ne!(seed(&attached.inputs), seed(&bird));
+}
+
+#[test]
+fn generate_builds_the_clamped_bird() {
+    let bird = floating_head_bird();
+    let settings = BirdMeshSettings {
+        neck_fix: Some(NeckFix::ClampOffsets),
+        ..Default::default()
+    };
+    let attached = AttachedBird::generate(&bird, &settings);
+    assert!(!attached.report.changes.is_empty());
+    let clamped_seed = seed(&attached.inputs);
+    assert_ne!(clamped_seed, seed(&bird));
+    // the seed it hands back is exactly the bird it built, and that bird keeps its head on
+    let mut rebuilt = BirdGenInputs::default();
+    rebuilt.update_from_seed_string(clamped_seed).unwrap();
+    assert_eq!(rebuilt.head_level, attached.inputs.head_level);
+    assert_eq!(
+        rebuilt.head_lateral_offset,
+        attached.inputs.head_lateral_offset
+    );
+    let plain = BirdMeshSettings {
+        neck_fix: None,
+        ..settings
+    };
+    assert!(!head_is_floating(&BirdCSGMeshes::generate(
+        &rebuilt, &plain
+    )));
+}
+
+#[test]
+fn extending_the_neck_keeps_the_inputs() {
+    let bird = floating_head_bird();
+    let attached = attach_head(&bird, &BirdMeshSettings::default(), NeckFix::ExtendNeck);
+    assert!(attached.report.was_floating);
+    assert!(!attached.report.still_floating);
+    assert_eq!(seed(&attached.inputs), seed(&bird));
+    assert!(matches!(
+        attached.report.changes[..],
+        [NeckChange::ExtendedNeck { .. }]
+    ));
+}
@@ -0,0 +1,73 @@
+// Hangers: loops end up fused onto the bird (not floating next to it) and the tail hole
+// actually goes through the tail, taking material out without breaking it off
+use birdgen::{
+    BirdExport, ExportOptions, HangerOptions,
+    geometry::{bounds, mesh_triangles, shell_count, volume},
+};
+use std::f64::consts::PI;
+
+mod common;
+use common::good_bird_inputs;
+
+fn export(hanger: Option<HangerOptions>) -> BirdExport {
+    BirdExport::new(
+        &good_bird_inputs(),
+        &ExportOptions {
+            hanger,
+            ..Default::default()
+        },
+    )
+    .expect("the good bird has room for a hanger")
+}
+
+#[test]
+fn loops_are_fused_on() {
+    let plain = export(None);
+    for (hanger, on_head) in [
+        (HangerOptions::head_loop(), true),
+        (HangerOptions::back_loop(), false),
+    ] {
+        let with_loop = export(Some(hanger));
+        let (before, after) = if on_head {
+            (&plain.head, &with_loop.head)
+        } else {
+            (&plain.body, &with_loop.body)
+        };
+        let (before, after) = (mesh_triangles(before), mesh_triangles(after));
+        // one piece, with some of the ring sunk into the bird and the rest (with the hole) out in the open
+        let minor_radius = hanger.loop_thickness as f64 / 2.0;
+        let major_radius = hanger.hole_diameter as f64 / 2.0 + minor_radius;
+        let ring_volume = 2.0 * PI * PI * major_radius * minor_radius * minor_radius;
+        let added = volume(&after) - volume(&before);
+        assert_eq!(
+            shell_count(&after),
+            1,
+            "{:?} isn't attached",
+            hanger.placement
+        );
+        assert!(
+            added > ring_volume * 0.5 && added < ring_volume * 0.95,
+            "{:?} added {added:.1}mm3 for a {ring_volume:.1}mm3 ring",
+            hanger.placement
+        );
+    }
+}
+
+#[test]
+fn tail_hole_goes_through_the_tail() {
+    let options = HangerOptions::tail_hole();
+    let plain = mesh_triangles(&export(None).body);
+    let drilled = mesh_triangles(&export(Some(options)).body);
+    let removed = volume(&plain) - volume(&drilled);
+    // at least a thin disc's worth came out, but the tail's still on
+    let radius = options.hole_diameter as f64 / 2.0;
+    assert!(
+        removed > PI * radius * radius * options.min_wall as f64,
+        "only {removed:.2}mm3 drilled out"
+    );
+    assert_eq!(shell_count(&drilled), 1);
+    assert_eq!(
+        bounds(&drilled).size().round(),
+        bounds(&plain).size().round()
+    );
+}
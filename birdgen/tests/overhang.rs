@@ -0,0 +1,60 @@
+// Overhangs on a mesh simple enough to work out by hand: a cube sitting on the bed with
+// another one floating 10mm above it, so only the floating cube's bottom face needs support
+use birdgen::{CSGMesh, OverhangOptions, analyze_overhangs};
+use csgrs::csg::CSG;
+
+const SIZE: f64 = 10.0;
+// bottom of the floating cube
+const FLOATING_AT: f64 = 20.0;
+
+fn cubes() -> (CSGMesh, CSGMesh) {
+    // generation frame, z up (the analysis turns it into the export's y up itself)
+    let floating = CSGMesh::cuboid(SIZE, SIZE, SIZE, None).translate(0.0, 0.0, FLOATING_AT);
+    let on_bed = CSGMesh::cuboid(SIZE, SIZE, SIZE, None);
+    (floating, on_bed)
+}
+
+#[test]
+fn floating_cube_bottom_overhangs() {
+    let (head, body) = cubes();
+    let report = analyze_overhangs(&head, &body, &OverhangOptions::default());
+    // the floating bottom face, straight down to the bed
+    let area = SIZE * SIZE;
+    assert!(
+        (report.overhang_area - area).abs() < 1e-6,
+        "{} mm2 of overhang",
+        report.overhang_area
+    );
+    assert!((report.support_volume - area * FLOATING_AT).abs() < 1e-6);
+}
+
+#[test]
+fn flat_faces_are_fine_at_90_degrees() {
+    let (head, body) = cubes();
+    let report = analyze_overhangs(
+        &head,
+        &body,
+        &OverhangOptions {
+            max_angle: 90.0,
+            ..Default::default()
+        },
+    );
+    assert!(!report.has_overhangs());
+}
+
+#[test]
+fn face_flags_mark_the_bottom_face() {
+    let (head, body) = cubes();
+    let report = analyze_overhangs(
+        &head,
+        &body,
+        &OverhangOptions {
+            face_flags: true,
+            ..Default::default()
+        },
+    );
+    // a cube face is two triangles
+    let flagged = |faces: Option<Vec<bool>>| faces.unwrap().iter().filter(|&&f| f).count();
+    assert_eq!(flagged(report.head_faces), 2);
+    assert_eq!(flagged(report.body_faces), 0);
+}
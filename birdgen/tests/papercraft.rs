@@ -0,0 +1,90 @@
+// Papercraft nets: the right number of faces come out, nothing's flipped over (which would put the
+// printed side on the inside), every piece sits on its page without running into another, every
+// glue edge has its pair and a tab, and a convex solid only folds one way
+use bevy::math::DVec2;
+use birdgen::{CSGMesh, NetLineKind, PapercraftOptions, papercraft};
+use csgrs::csg::CSG;
+use std::collections::HashMap;
+
+mod common;
+use common::good_bird;
+
+#[test]
+fn net_lays_out_cleanly() {
+    let options = PapercraftOptions {
+        faces: 160,
+        ..Default::default()
+    };
+    let net = good_bird().papercraft(&options).expect("the bird unfolds");
+    let faces: usize = net.pieces.iter().map(|piece| piece.faces.len()).sum();
+    assert_eq!(faces, net.head_faces + net.body_faces);
+    assert!(faces <= 200, "{faces} faces for a target of 160");
+
+    let mut boxes: Vec<(usize, DVec2, DVec2)> = vec![];
+    for piece in &net.pieces {
+        let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+        for [a, b, c] in &piece.faces {
+            assert!(
+                (*b - *a).perp_dot(*c - *a) > 0.0,
+                "faces stay printed side up"
+            );
+            for point in [a, b, c] {
+                min = min.min(*point);
+                max = max.max(*point);
+            }
+        }
+        for point in piece.tabs.iter().flatten() {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        assert!(piece.page < net.pages);
+        assert!(min.cmpge(DVec2::splat(options.margin - 1e-6)).all());
+        assert!(max.cmple(options.page_size - options.margin + 1e-6).all());
+        for (page, other_min, other_max) in &boxes {
+            let apart = max.x <= other_min.x
+                || other_max.x <= min.x
+                || max.y <= other_min.y
+                || other_max.y <= min.y;
+            assert!(
+                *page != piece.page || apart,
+                "pieces overlap on page {page}"
+            );
+        }
+        boxes.push((piece.page, min, max));
+    }
+
+    // every glue edge number is on both of the edges that get glued, and one of them has the tab
+    let mut numbers: HashMap<usize, usize> = HashMap::new();
+    for label in net.pieces.iter().flat_map(|piece| &piece.labels) {
+        // (the piece names aren't numbers)
+        if let Ok(number) = label.text.parse() {
+            *numbers.entry(number).or_default() += 1;
+        }
+    }
+    assert_eq!(numbers.len(), net.glue_edges);
+    for (number, count) in &numbers {
+        assert_eq!(*count, 2, "edge {number} is on {count} edges");
+    }
+    let tabs: usize = net.pieces.iter().map(|piece| piece.tabs.len()).sum();
+    assert_eq!(tabs, net.glue_edges);
+
+    assert_eq!(net.to_svg_pages("bird").len(), net.pages);
+    let pdf = net.to_pdf("bird");
+    assert!(pdf.starts_with(b"%PDF-") && pdf.ends_with(b"%%EOF\n"));
+}
+
+#[test]
+fn cubes_only_fold_up() {
+    let head = CSGMesh::cuboid(10.0, 10.0, 10.0, None).translate(0.0, 0.0, 20.0);
+    let body = CSGMesh::cuboid(20.0, 20.0, 20.0, None);
+    let net = papercraft(&head, &body, &PapercraftOptions::default()).expect("cubes unfold");
+    let folds: Vec<NetLineKind> = net
+        .pieces
+        .iter()
+        .flat_map(|piece| &piece.lines)
+        .map(|line| line.kind)
+        .filter(|kind| *kind != NetLineKind::Cut)
+        .collect();
+    assert!(!folds.is_empty());
+    assert!(folds.iter().all(|kind| *kind == NetLineKind::Mountain));
+}
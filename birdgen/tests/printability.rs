@@ -0,0 +1,54 @@
+// Children that come out of the printability filter really do print: building them again
+// from just their seed strings and checking from scratch gives the same answer
+use birdgen::{
+    BirdCSGMeshes, BirdGenInputTypes, BirdGenInputs, BirdMeshSettings, BreedingOptions,
+    PrintabilityOptions, breed_birds, check_printability,
+};
+
+mod common;
+use common::good_bird_inputs;
+
+#[test]
+fn filtered_children_pass_the_check() {
+    let settings = BirdMeshSettings::default();
+    let printability = PrintabilityOptions::default();
+    let children = breed_birds(
+        &good_bird_inputs(),
+        &[1, 2, 3],
+        &BreedingOptions {
+            printability: Some(printability),
+            ..Default::default()
+        },
+        &settings,
+    );
+    let printable: Vec<_> = children
+        .iter()
+        .filter(|child| {
+            child
+                .printability
+                .as_ref()
+                .is_some_and(|report| report.is_printable())
+        })
+        .collect();
+    assert!(!printable.is_empty(), "no printable children at all");
+    for child in printable {
+        // the seed is all that gets saved, so it has to hold the whole child
+        let seed = child.inputs.get_bird_seed_string();
+        let mut rebuilt = BirdGenInputs::default();
+        rebuilt.update_from_seed_string(seed.clone()).unwrap();
+        for input_type in BirdGenInputTypes::ALL {
+            assert_eq!(
+                rebuilt.get_input_value_for_type(&input_type),
+                child.inputs.get_input_value_for_type(&input_type),
+                "{seed} lost part of its {input_type:?}"
+            );
+        }
+        let meshes = BirdCSGMeshes::generate(&rebuilt, &settings);
+        let report = check_printability(&rebuilt, &meshes, &printability);
+        assert!(
+            report.is_printable(),
+            "{seed} passed the filter but not the check: {:?}",
+            report.problems
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// Software renderer sanity checks: the bird ends up in the middle of the picture, the
+// background stays background, the same bird always renders to the same pixels, and a couple of
+// blocks land exactly where the camera says they should
+use birdgen::{CSGMesh, RenderOptions, render_bird};
+use csgrs::csg::CSG;
+
+mod common;
+use common::good_bird;
+
+const SIZE: u32 = 96;
+
+fn options() -> RenderOptions {
+    RenderOptions {
+        width: SIZE,
+        height: SIZE,
+        background: [0, 0, 0, 0],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn bird_is_in_frame() {
+    let image = good_bird().render(&options());
+    assert_eq!(image.pixels.len(), (SIZE * SIZE * 4) as usize);
+    for (x, y) in [(0, 0), (SIZE - 1, 0), (0, SIZE - 1), (SIZE - 1, SIZE - 1)] {
+        assert_eq!(image.pixel(x, y)[3], 0, "corner ({x}, {y}) should be empty");
+    }
+    let covered = image
+        .pixels
+        .chunks(4)
+        .filter(|pixel| pixel[3] == 255)
+        .count();
+    // fitted to the frame, so the bird should fill a decent chunk of it (but not all)
+    let fraction = covered as f64 / (SIZE * SIZE) as f64;
+    assert!(
+        (0.15..0.9).contains(&fraction),
+        "bird covers {fraction:.2} of the image"
+    );
+}
+
+#[test]
+fn renders_are_deterministic() {
+    let meshes = good_bird();
+    assert_eq!(meshes.render(&options()), meshes.render(&options()));
+}
+
+#[test]
+fn png_has_a_png_header() {
+    let png = good_bird()
+        .render(&options())
+        .to_png()
+        .expect("png encodes");
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+}
+
+#[test]
+fn blocks_land_where_they_should() {
+    // generation frame: a 40x10x10 body with a 10mm cube head sat on its front (-x) end,
+    // seen straight from the side that's a 40x20 L with the head at the top left
+    let head = CSGMesh::cuboid(10.0, 10.0, 10.0, None).translate(0.0, 0.0, 10.0);
+    let body = CSGMesh::cuboid(40.0, 10.0, 10.0, None);
+    let image = render_bird(
+        &head,
+        &body,
+        &RenderOptions {
+            yaw: 0.0,
+            pitch: 0.0,
+            supersample: 1,
+            ..options()
+        },
+    );
+    // 6% margin each side, so the 40mm fills 88 of the 96px (2.112px/mm) and it's centred
+    let scale = SIZE as f64 * 0.88 / 40.0;
+    let (left, top) = (SIZE as f64 * 0.06, (SIZE as f64 - 20.0 * scale) / 2.0);
+    let covered = |x: f64, y: f64| image.pixel(x as u32, y as u32)[3] == 255;
+    // into each block from its corners, and just past the edges of the whole thing
+    let inset = 2.0;
+    let (head_right, middle, right, bottom) = (
+        left + 10.0 * scale,
+        top + 10.0 * scale,
+        left + 40.0 * scale,
+        top + 20.0 * scale,
+    );
+    assert!(covered(left + inset, top + inset), "head's top left");
+    assert!(
+        covered(head_right - inset, middle - inset),
+        "head's bottom right"
+    );
+    assert!(
+        covered(right - inset, bottom - inset),
+        "body's bottom right"
+    );
+    assert!(covered(left + inset, bottom - inset), "body's bottom left");
+    assert!(
+        !covered(head_right + inset, middle - inset),
+        "above the body"
+    );
+    assert!(!covered(left - inset, middle + inset), "left of the body");
+    assert!(!covered(right + inset, middle + inset), "right of the body");
+    assert!(!covered(left + inset, top - inset), "above the head");
+    assert!(!covered(right - inset, bottom + inset), "under the body");
+}
@@ -0,0 +1,242 @@
+// The HTTP server, started on a free localhost port: metadata and meshes come back for good seeds,
+// breeding is repeatable, file links build the bird they're for, and anything over the limits gets
+// turned away before any bird is built
+#![cfg(feature = "server")]
+use birdgen::{BirdExport, BirdGenInputs, BirdMeshSettings, ExportOptions, GOOD_BIRDS, Precision};
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Child, Command, Stdio},
+};
+
+mod common;
+use common::{floating_head_bird, good_bird_inputs};
+
+struct Server {
+    process: Child,
+    address: String,
+}
+
+impl Server {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_birdgen-server"))
+            .args(["--port", "0", "--threads", "2"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("server starts");
+        let mut line = String::new();
+        BufReader::new(process.stdout.take().expect("stdout is piped"))
+            .read_line(&mut line)
+            .expect("server says where it's listening");
+        let address = line
+            .trim()
+            .strip_prefix("listening on http://")
+            .expect("listening line")
+            .to_string();
+        Server { process, address }
+    }
+
+    // (HTTP/1.0, so the reply's never chunked and the connection closes after it)
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(&self.address).expect("connects");
+        write!(
+            stream,
+            "{method} {path} HTTP/1.0\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("sends");
+        let mut reply = vec![];
+        stream.read_to_end(&mut reply).expect("reads");
+        let split = reply
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .expect("headers end");
+        let status = String::from_utf8_lossy(&reply[..split])
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .expect("status code");
+        (status, reply[split + 4..].to_vec())
+    }
+
+    fn json(&self, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = self.request(method, path, body);
+        (status, serde_json::from_slice(&body).expect("JSON reply"))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+fn good_seed() -> String {
+    good_bird_inputs().get_bird_seed_string()
+}
+
+#[test]
+fn good_birds_come_with_metadata_and_meshes() {
+    let server = Server::start();
+    let (status, good) = server.json("GET", "/good?index=0&precision=low", "");
+    assert_eq!(status, 200);
+    let seed = good_seed();
+    assert_eq!(good["seed"], seed.as_str());
+    assert!(good["metrics"]["volume_mm3"].as_f64().unwrap_or(0.0) > 0.0);
+    assert_eq!(
+        good["files"]["stl"],
+        format!("/bird/{seed}.stl?precision=low")
+    );
+
+    let (status, stl) = server.request("GET", &format!("/bird/{seed}.stl?precision=low"), "");
+    assert_eq!(status, 200);
+    assert!(stl.starts_with(b"solid"));
+    let (status, png) = server.request("GET", &format!("/bird/{seed}.png?size=64"), "");
+    assert_eq!(status, 200);
+    assert!(png.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn breeding_is_repeatable() {
+    let server = Server::start();
+    let body = format!(
+        r#"{{"parents": ["{}"], "rng_seed": 7, "count": 2}}"#,
+        good_seed()
+    );
+    let (status, first) = server.json("POST", "/breed?precision=low", &body);
+    assert_eq!(status, 200);
+    let (_, second) = server.json("POST", "/breed?precision=low", &body);
+    let children = first["children"].as_array().expect("children");
+    assert_eq!(children.len(), 2);
+    assert_eq!(first["children"], second["children"]);
+    assert_ne!(children[0]["seed"], children[1]["seed"]);
+}
+
+#[test]
+fn clamped_birds_link_to_the_bird_that_got_built() {
+    let server = Server::start();
+    let seed = floating_head_bird().get_bird_seed_string();
+    let (status, reply) = server.json(
+        "GET",
+        &format!("/bird/{seed}.json?precision=low&fix_neck=clamp"),
+        "",
+    );
+    assert_eq!(status, 200);
+    let built = reply["seed"].as_str().expect("seed");
+    assert_ne!(built, seed);
+    assert_eq!(
+        reply["files"]["stl"],
+        format!("/bird/{built}.stl?precision=low&fix_neck=clamp")
+    );
+}
+
+#[test]
+fn two_parents_breed_printable_children() {
+    let server = Server::start();
+    let parents = [GOOD_BIRDS[0], GOOD_BIRDS[1]];
+    let body = |printable: bool| {
+        format!(
+            r#"{{"parents": ["{}", "{}"], "rng_seed": 7, "printable": {printable}}}"#,
+            parents[0], parents[1]
+        )
+    };
+    let (status, reply) = server.json("POST", "/breed?precision=low", &body(true));
+    assert_eq!(status, 200);
+    let child = &reply["children"][0];
+    // tried until one printed (or it ran out of attempts)
+    let attempts = child["attempts"].as_u64().expect("attempts");
+    assert!(child["printability"]["printable"] == true || attempts > 1);
+    let (_, reply) = server.json("POST", "/breed?precision=low", &body(false));
+    assert!(reply["children"][0]["attempts"].is_null());
+}
+
+// lowest and highest corner of every vertex in an ascii STL
+fn stl_bounds(stl: &[u8]) -> ([f64; 3], [f64; 3]) {
+    let mut bounds = ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]);
+    for line in String::from_utf8_lossy(stl).lines() {
+        let Some(vertex) = line.trim().strip_prefix("vertex ") else {
+            continue;
+        };
+        for (i, value) in vertex.split_whitespace().enumerate() {
+            let value: f64 = value.parse().expect("vertex coordinates are numbers");
+            bounds.0[i] = bounds.0[i].min(value);
+            bounds.1[i] = bounds.1[i].max(value);
+        }
+    }
+    bounds
+}
+
+#[test]
+fn bred_children_link_to_their_own_mesh() {
+    let server = Server::start();
+    let body = format!(
+        r#"{{"parents": ["{}", "{}"], "rng_seed": 7}}"#,
+        GOOD_BIRDS[0], GOOD_BIRDS[1]
+    );
+    let (status, reply) = server.json("POST", "/breed?precision=low", &body);
+    assert_eq!(status, 200);
+    let child = &reply["children"][0];
+    let seed = child["seed"].as_str().expect("seed");
+    let link = child["files"]["stl"].as_str().expect("stl link");
+    assert_eq!(link, format!("/bird/{seed}.stl?precision=low"));
+    let (status, stl) = server.request("GET", link, "");
+    assert_eq!(status, 200);
+
+    // the same bird built straight from its seed (the hulls can come out a few triangles
+    // different from one build to the next, so it's the bounds that have to match)
+    let mut bird = BirdGenInputs::default();
+    bird.update_from_seed_string(seed.to_string()).unwrap();
+    let direct = BirdExport::new(
+        &bird,
+        &ExportOptions {
+            mesh: BirdMeshSettings {
+                precision: Precision::Low,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .expect("child builds")
+    .to_stl("direct");
+    let (served, built) = (stl_bounds(&stl), stl_bounds(&direct));
+    for (served, built) in [(served.0, built.0), (served.1, built.1)] {
+        for axis in 0..3 {
+            assert!(
+                (served[axis] - built[axis]).abs() < 1e-3,
+                "{seed} served {served:?}, built {built:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn limits_turn_requests_away() {
+    let server = Server::start();
+    let seed = good_seed();
+    for path in [
+        format!("/bird/{seed}.stl?precision=high"),
+        format!("/bird/{seed}.stl?scale=1000"),
+        format!("/bird/{seed}.png?size=100000"),
+        "/bird/not-a-bird.stl".to_string(),
+        "/good?index=100000".to_string(),
+    ] {
+        let (status, reply) = server.json("GET", &path, "");
+        assert_eq!(status, 400, "{path}");
+        assert!(reply["error"].is_string(), "{path}");
+    }
+    let (status, _) = server.json(
+        "POST",
+        "/breed",
+        &format!(r#"{{"parents": ["{seed}"], "count": 1000}}"#),
+    );
+    assert_eq!(status, 400);
+    let (status, _) = server.json("POST", "/breed", &"x".repeat(100_000));
+    assert_eq!(status, 413);
+    let (status, _) = server.json("GET", "/nowhere", "");
+    assert_eq!(status, 404);
+    let (status, _) = server.json("DELETE", "/random", "");
+    assert_eq!(status, 405);
+}
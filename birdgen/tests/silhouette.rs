@@ -0,0 +1,86 @@
+// Silhouettes of a mesh simple enough to work out by hand: a 20x10x10 block on the bed with a
+// 10mm cube floating 10mm above its front half, so every view's outline is a couple of rectangles
+use birdgen::{CSGMesh, SilhouetteOptions, View, silhouette};
+use csgrs::csg::CSG;
+
+// the svg's margin around the outline (mm), each side
+const MARGIN: f64 = 2.0;
+
+fn blocks() -> (CSGMesh, CSGMesh) {
+    // generation frame, z up (the silhouette turns it into the export's y up itself)
+    let head = CSGMesh::cuboid(10.0, 10.0, 10.0, None).translate(0.0, 0.0, 20.0);
+    let body = CSGMesh::cuboid(20.0, 10.0, 10.0, None);
+    (head, body)
+}
+
+// the svg's width and height, in mm
+fn svg_size(svg: &str) -> (f64, f64) {
+    let attribute = |name: &str| -> f64 {
+        let start = svg.find(&format!(" {name}=\"")).expect("svg has a size") + name.len() + 3;
+        let value = &svg[start..];
+        value[..value.find("mm\"").expect("sized in mm")]
+            .parse()
+            .unwrap()
+    };
+    (attribute("width"), attribute("height"))
+}
+
+#[test]
+fn outlines_match_the_projected_blocks() {
+    let (head, body) = blocks();
+    // (view, outline width and height, area inside the outlines)
+    let expected = [
+        // head over the body, with the gap between them
+        (View::Side, (20.0, 30.0), 20.0 * 10.0 + 10.0 * 10.0),
+        (View::Front, (10.0, 30.0), 10.0 * 10.0 + 10.0 * 10.0),
+        // the head's right over the body from above, so it's just the body
+        (View::Top, (20.0, 10.0), 20.0 * 10.0),
+    ];
+    for (view, (width, height), area) in expected {
+        let silhouette = silhouette(
+            &head,
+            &body,
+            &SilhouetteOptions {
+                view,
+                ..Default::default()
+            },
+        );
+        let (min, max) = silhouette.bounds();
+        let size = max - min;
+        let name = view.name();
+        // traced on a 0.1mm grid, so the edges can be a cell out
+        assert!(
+            (size.x - width).abs() < 0.2 && (size.y - height).abs() < 0.2,
+            "{name} outline is {size}"
+        );
+        assert!(
+            (silhouette.area() - area).abs() < area * 0.02,
+            "{name} outline has {:.1}mm2 inside",
+            silhouette.area()
+        );
+
+        // a 1:1 page, whatever the view
+        let (svg_width, svg_height) = svg_size(&silhouette.to_svg("blocks"));
+        assert!(
+            (svg_width - (width + 2.0 * MARGIN)).abs() < 0.2
+                && (svg_height - (height + 2.0 * MARGIN)).abs() < 0.2,
+            "{name} svg is {svg_width}x{svg_height}mm"
+        );
+    }
+}
+
+#[test]
+fn floating_head_gets_its_own_outline() {
+    let (head, body) = blocks();
+    let side = silhouette(&head, &body, &SilhouetteOptions::default());
+    assert_eq!(side.outlines.len(), 2);
+    let top = silhouette(
+        &head,
+        &body,
+        &SilhouetteOptions {
+            view: View::Top,
+            ..Default::default()
+        },
+    );
+    assert_eq!(top.outlines.len(), 1);
+}
@@ -0,0 +1,127 @@
+// Stacked slices: the stack is as tall as the bird, every piece is a real bit of material,
+// the dowel holes go through solid material in more than one layer, and the cut sheets hold every piece
+use bevy::math::DVec2;
+use birdgen::{SliceOptions, SlicePiece, geometry::mesh_triangles};
+
+mod common;
+use common::good_bird;
+
+#[test]
+fn stack_covers_the_bird() {
+    let options = SliceOptions {
+        thickness: 2.0,
+        resolution: 0.25,
+        ..Default::default()
+    };
+    let sliced = good_bird().slice(&options);
+    assert!(
+        sliced.layers.len() > 3,
+        "only {} layers",
+        sliced.layers.len()
+    );
+    let mut last = 0;
+    for layer in &sliced.layers {
+        assert!(layer.number > last, "layers come out in order");
+        last = layer.number;
+        for piece in &layer.pieces {
+            assert!(piece.area() > 0.0);
+            let (min, max) = piece.bounds();
+            assert!(piece.label.cmpge(min).all() && piece.label.cmple(max).all());
+        }
+    }
+    // the top view stacks along y, so the stack should be about as tall as the bird
+    let triangles = mesh_triangles(&good_bird().body);
+    let (low, high) = triangles
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), point| {
+            (low.min(point.z), high.max(point.z))
+        });
+    assert!(sliced.height() >= high - low - 2.0 * options.thickness);
+}
+
+#[test]
+fn sheets_hold_every_piece() {
+    let sliced = good_bird().slice(&SliceOptions {
+        resolution: 0.25,
+        ..Default::default()
+    });
+    let pieces: usize = sliced.layers.iter().map(|layer| layer.pieces.len()).sum();
+    let sheets = sliced
+        .to_sheets_svg("bird", DVec2::new(120.0, 80.0), 2.0)
+        .expect("a bird fits on a 120x80 sheet");
+    let placed: usize = sheets
+        .iter()
+        .map(|sheet| sheet.matches("<text").count())
+        .sum();
+    assert_eq!(placed, pieces);
+    // and one that's too small says so rather than overlapping things
+    assert!(
+        sliced
+            .to_sheets_svg("bird", DVec2::new(5.0, 5.0), 2.0)
+            .is_err()
+    );
+}
+
+// even-odd, so the piece's own holes count as outside
+fn is_inside(piece: &SlicePiece, point: DVec2) -> bool {
+    let mut inside = false;
+    for outline in &piece.outlines {
+        for (i, a) in outline.iter().enumerate() {
+            let b = outline[(i + 1) % outline.len()];
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn distance_to_edge(piece: &SlicePiece, point: DVec2) -> f64 {
+    let mut nearest = f64::INFINITY;
+    for outline in &piece.outlines {
+        for (i, a) in outline.iter().enumerate() {
+            let b = outline[(i + 1) % outline.len()];
+            let along = ((point - *a).dot(b - *a) / (b - *a).length_squared().max(f64::EPSILON))
+                .clamp(0.0, 1.0);
+            nearest = nearest.min(point.distance(*a + (b - *a) * along));
+        }
+    }
+    nearest
+}
+
+#[test]
+fn dowel_holes_line_the_layers_up() {
+    let options = SliceOptions {
+        resolution: 0.25,
+        ..Default::default()
+    };
+    let sliced = good_bird().slice(&options);
+    let radius = options.hole_diameter / 2.0;
+    // (the outline's allowed to stray a cell and the simplifying tolerance from the traced grid)
+    let slack = options.resolution + options.tolerance;
+
+    let mut holes: Vec<(DVec2, usize)> = vec![];
+    for layer in &sliced.layers {
+        for piece in &layer.pieces {
+            for hole in &piece.holes {
+                assert!(is_inside(piece, *hole), "hole {hole} isn't in its piece");
+                let clearance = distance_to_edge(piece, *hole);
+                assert!(
+                    clearance >= 3.0 * radius - slack,
+                    "hole {hole} is only {clearance:.2}mm from the edge"
+                );
+                match holes.iter_mut().find(|(center, _)| center == hole) {
+                    Some((_, layers)) => *layers += 1,
+                    None => holes.push((*hole, 1)),
+                }
+            }
+        }
+    }
+    assert_eq!(holes.len(), options.registration_holes);
+    for (hole, layers) in holes {
+        assert!(layers >= 2, "hole {hole} only goes through {layers} layer");
+    }
+}
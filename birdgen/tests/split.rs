@@ -0,0 +1,69 @@
+// Split birds: both halves sit flat on their cut faces, and the sockets are the pegs plus clearance
+use birdgen::{
+    BirdExport, ExportOptions, SplitOptions,
+    geometry::{Triangle, bounds, mesh_triangles, volume},
+};
+
+mod common;
+use common::good_bird_inputs;
+
+fn export() -> BirdExport {
+    BirdExport::new(&good_bird_inputs(), &ExportOptions::default()).expect("good bird exports")
+}
+
+// area of the faces lying on the bed, facing down
+fn area_on_bed(triangles: &[Triangle]) -> f64 {
+    triangles
+        .iter()
+        .filter(|[a, b, c]| [a, b, c].iter().all(|point| point.z.abs() < 1e-6))
+        .map(|[a, b, c]| (*b - *a).cross(*c - *a))
+        .filter(|normal| normal.z < 0.0)
+        .map(|normal| normal.length() / 2.0)
+        .sum()
+}
+
+#[test]
+fn halves_lie_flat() {
+    let split = export().split(&SplitOptions::default()).unwrap();
+    for piece in &split.pieces {
+        let mut triangles = mesh_triangles(&piece.body);
+        triangles.extend(mesh_triangles(&piece.head));
+        assert!(bounds(&triangles).min.z.abs() < 1e-6);
+        // the whole cut face is down there, not just a corner touching
+        assert!(area_on_bed(&triangles) > 500.0);
+    }
+}
+
+#[test]
+fn pegs_fit_their_sockets() {
+    let export = export();
+    let options = SplitOptions::default();
+    let split = export.split(&options).unwrap();
+    let solid = export
+        .split(&SplitOptions {
+            peg_count: 0,
+            ..options
+        })
+        .unwrap();
+    assert_eq!(split.pegs.len(), options.peg_count);
+
+    // same polygon for sockets and pegs, so the volumes only differ by the clearance
+    let (diameter, length, clearance) = (
+        options.peg_diameter as f64,
+        options.peg_length as f64,
+        options.clearance as f64,
+    );
+    let peg_volume = volume(&mesh_triangles(&split.pegs[0]));
+    let socket_volume = peg_volume
+        * ((diameter + clearance) / diameter).powi(2)
+        * ((length / 2.0 + clearance) / length);
+    for (piece, solid_piece) in split.pieces.iter().zip(&solid.pieces) {
+        let drilled =
+            volume(&mesh_triangles(&solid_piece.body)) - volume(&mesh_triangles(&piece.body));
+        let expected = socket_volume * options.peg_count as f64;
+        assert!(
+            (drilled - expected).abs() < expected * 0.02,
+            "sockets took {drilled:.1}mm3 out, expected {expected:.1}mm3"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+// Parameter sweeps: the grid covers the range it says it does, and every seed in the
+// seed list gives back exactly the bird from its cell
+use birdgen::{BirdGenInputTypes, BirdGenInputs, Sweep, SweepAxis};
+
+fn sweep() -> Sweep {
+    Sweep {
+        base: BirdGenInputs::default(),
+        columns: SweepAxis::full(BirdGenInputTypes::BeakLength, 5),
+        rows: Some(SweepAxis {
+            input: BirdGenInputTypes::HeadSize,
+            from: 15.0,
+            to: 35.0,
+            steps: 3,
+        }),
+    }
+}
+
+#[test]
+fn axis_covers_its_range() {
+    let axis = SweepAxis::full(BirdGenInputTypes::HeadPitch, 6);
+    assert_eq!(axis.values(), vec![-80.0, -55.0, -30.0, -5.0, 20.0, 45.0]);
+    // odd ranges get rounded to whole numbers, a seed can't hold anything else
+    let axis = SweepAxis {
+        input: BirdGenInputTypes::EyeSize,
+        from: 0.0,
+        to: 10.0,
+        steps: 4,
+    };
+    assert_eq!(axis.values(), vec![0.0, 3.0, 7.0, 10.0]);
+}
+
+#[test]
+fn cells_only_change_the_swept_inputs() {
+    let sweep = sweep();
+    let cells = sweep.cells();
+    assert_eq!(cells.len(), 15);
+    let base = BirdGenInputs::default();
+    for cell in &cells {
+        for input in BirdGenInputTypes::ALL {
+            let swept = cell.values.iter().find(|(swept, _)| *swept == input);
+            let expected = match swept {
+                Some((_, value)) => *value,
+                None => base.get_input_value_for_type(&input),
+            };
+            assert_eq!(cell.inputs.get_input_value_for_type(&input), expected);
+        }
+    }
+    let last = cells.last().expect("there are cells");
+    assert_eq!((last.row, last.column), (2, 4));
+    assert_eq!(last.labels(), vec!["beak_length 50", "head_size 35"]);
+}
+
+#[test]
+fn seed_list_round_trips() {
+    let sweep = sweep();
+    let seed_list = sweep.seed_list();
+    let seeds: Vec<&str> = seed_list
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    let cells = sweep.cells();
+    assert_eq!(seeds.len(), cells.len());
+    for (seed, cell) in seeds.into_iter().zip(cells) {
+        let mut bird = BirdGenInputs::default();
+        bird.update_from_seed_string(seed.to_string())
+            .expect("sweep seeds parse");
+        assert_eq!(bird.get_bird_seed_string(), seed);
+        for input in BirdGenInputTypes::ALL {
+            assert_eq!(
+                bird.get_input_value_for_type(&input),
+                cell.inputs.get_input_value_for_type(&input)
+            );
+        }
+    }
+}
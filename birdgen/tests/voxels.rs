@@ -0,0 +1,98 @@
+// Voxel birds: beak, eyes and body all show up in their own colours, and the .vox and .schem files
+// are laid out how MagicaVoxel and WorldEdit expect
+use birdgen::{BirdMeshSettings, BirdParts, BirdVoxels, VoxelMaterial, VoxelOptions};
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+mod common;
+use common::good_bird_inputs;
+
+// a 2x3x2 stack: body at the bottom, a beak voxel and an eye voxel on top
+fn little_stack() -> BirdVoxels {
+    let mut voxels = BirdVoxels {
+        size: [2, 3, 2],
+        voxel_size: 1.0,
+        voxels: vec![0; 12],
+        colors: [[247, 179, 43], [232, 106, 23], [26, 26, 26]],
+    };
+    for x in 0..2 {
+        for z in 0..2 {
+            let index = voxels.index(x, 0, z);
+            voxels.voxels[index] = VoxelMaterial::Body.index();
+        }
+    }
+    let beak = voxels.index(0, 2, 0);
+    voxels.voxels[beak] = VoxelMaterial::Beak.index();
+    let eye = voxels.index(1, 1, 1);
+    voxels.voxels[eye] = VoxelMaterial::Eyes.index();
+    voxels
+}
+
+fn int(bytes: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(bytes[at..at + 4].try_into().expect("four bytes"))
+}
+
+#[test]
+fn good_bird_has_every_material() {
+    let voxels =
+        BirdParts::new(&good_bird_inputs(), &BirdMeshSettings::default()).voxelise(&VoxelOptions {
+            size: 48,
+            ..Default::default()
+        });
+    assert_eq!(voxels.size.into_iter().max(), Some(48));
+    for material in VoxelMaterial::ALL {
+        assert!(voxels.count(material) > 0, "no {} voxels", material.name());
+    }
+    // most of a bird is bird
+    assert!(voxels.count(VoxelMaterial::Body) > voxels.count(VoxelMaterial::Beak));
+}
+
+#[test]
+fn vox_stands_the_bird_up() {
+    let vox = little_stack().to_vox();
+    assert_eq!(&vox[..4], b"VOX ");
+    assert_eq!(&vox[8..12], b"MAIN");
+    // SIZE is x, then across, then up
+    assert_eq!(&vox[20..24], b"SIZE");
+    assert_eq!([int(&vox, 32), int(&vox, 36), int(&vox, 40)], [2, 2, 3]);
+    assert_eq!(&vox[44..48], b"XYZI");
+    assert_eq!(int(&vox, 56), 6);
+    let voxels: Vec<&[u8]> = vox[60..60 + 6 * 4].chunks(4).collect();
+    // the beak's at the top, the eye's in the middle
+    assert!(voxels.contains(&&[0, 1, 2, 2][..]));
+    assert!(voxels.contains(&&[1, 0, 1, 3][..]));
+    let rgba = 60 + 6 * 4 + 12;
+    assert_eq!(&vox[rgba..rgba + 4], &[247, 179, 43, 255]);
+}
+
+#[test]
+fn schem_is_gzipped_nbt() {
+    let stack = little_stack();
+    let mut nbt = vec![];
+    GzDecoder::new(&stack.to_schem().expect("writes")[..])
+        .read_to_end(&mut nbt)
+        .expect("gunzips");
+    assert_eq!(&nbt[..12], b"\x0a\x00\x09Schematic");
+    let find = |needle: &[u8]| {
+        nbt.windows(needle.len())
+            .position(|window| window == needle)
+    };
+    for block in stack.blocks() {
+        assert!(
+            find(block.as_bytes()).is_some(),
+            "{block} isn't in the palette"
+        );
+    }
+    assert_eq!(stack.blocks()[0], "minecraft:yellow_concrete");
+    // block data runs x, then z, then y, so the first row is all body
+    let data = find(b"BlockData").expect("there's block data") + 9;
+    let length = i32::from_be_bytes(nbt[data..data + 4].try_into().expect("four bytes"));
+    assert_eq!(length, 12);
+    let blocks = &nbt[data + 4..data + 16];
+    assert!(
+        blocks[..4]
+            .iter()
+            .all(|block| *block == blocks[0] && *block != 0)
+    );
+    assert_eq!(blocks[4..].iter().filter(|block| **block != 0).count(), 2);
+}
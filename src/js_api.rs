@@ -0,0 +1,293 @@
+// The web build's JavaScript API, so the page around the canvas can do its own thing with birds
+// Trunk hands the exports to the page as `window.wasmBindings` (index.html copies them to `window.coolbirds`)
+// The birdgen functions run right there and then, but the app lives inside Bevy's world, so commands
+// for it go in a queue that a system picks up every frame
+// (on native there's no JS, so the plugin doesn't do anything)
+use bevy::prelude::*;
+
+pub struct JsApiPlugin;
+
+impl Plugin for JsApiPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(target_arch = "wasm32")]
+        _app.add_systems(Update, (web::run_js_commands, web::publish_current_seed));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use crate::{RebuildBird, log_text::NewLog};
+    use bevy::prelude::*;
+    use birdgen::{
+        BirdCSGMeshes, BirdExport, BirdGenInputTypes, BirdGenInputs, BirdMeshSettings,
+        BreedingOptions, ExportOptions, FilamentOptions, GOOD_BIRDS, Precision,
+        PrintabilityOptions, check_printability,
+    };
+    use js_sys::{Array, Object, Reflect};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use std::sync::Mutex;
+    use wasm_bindgen::prelude::*;
+
+    // what the page's asked the app to do since last frame
+    enum JsCommand {
+        LoadSeed(String),
+        Randomize,
+    }
+
+    static COMMANDS: Mutex<Vec<JsCommand>> = Mutex::new(Vec::new());
+    // kept up to date by `publish_current_seed`, so `currentSeed()` doesn't have to wait a frame
+    static CURRENT_SEED: Mutex<String> = Mutex::new(String::new());
+
+    // the event the page can listen for on `window`, with the new seed as its `detail`
+    const SEED_EVENT: &str = "coolbirds:seed";
+
+    fn parse(seed: &str) -> Result<BirdGenInputs, JsError> {
+        let mut bird = BirdGenInputs::default();
+        bird.update_from_seed_string(seed.trim().to_string())
+            .map_err(|e| JsError::new(&e))?;
+        Ok(bird)
+    }
+
+    // an rng seed from JS, or a fresh one
+    fn rng(rng_seed: Option<u32>) -> StdRng {
+        StdRng::seed_from_u64(rng_seed.map_or_else(rand::random, u64::from))
+    }
+
+    fn object(entries: &[(&str, JsValue)]) -> Object {
+        let object = Object::new();
+        for (key, value) in entries {
+            let _ = Reflect::set(&object, &JsValue::from_str(key), value);
+        }
+        object
+    }
+
+    fn settings(precision: Option<String>) -> Result<BirdMeshSettings, JsError> {
+        let precision = match precision {
+            None => Precision::default(),
+            Some(name) => Precision::ALL
+                .into_iter()
+                .find(|precision| format!("{precision:?}").eq_ignore_ascii_case(&name))
+                .ok_or_else(|| JsError::new(&format!("{name} isn't low, medium or high")))?,
+        };
+        Ok(BirdMeshSettings {
+            precision,
+            ..Default::default()
+        })
+    }
+
+    // seed string -> { beak_length: 22, beak_size: 67, ... }
+    #[wasm_bindgen(js_name = parseSeed)]
+    pub fn parse_seed(seed: &str) -> Result<Object, JsError> {
+        let bird = parse(seed)?;
+        let entries: Vec<(&str, JsValue)> = BirdGenInputTypes::ALL
+            .iter()
+            .map(|input| {
+                let value = bird.get_input_value_for_type(input) as f64;
+                (input.name(), JsValue::from_f64(value))
+            })
+            .collect();
+        Ok(object(&entries))
+    }
+
+    // and back again, anything left out stays at the default bird's value
+    #[wasm_bindgen(js_name = formatSeed)]
+    pub fn format_seed(inputs: &JsValue) -> Result<String, JsError> {
+        let mut bird = BirdGenInputs::default();
+        for input in BirdGenInputTypes::ALL {
+            let value = Reflect::get(inputs, &JsValue::from_str(input.name()))
+                .map_err(|_| JsError::new("inputs should be an object"))?;
+            if value.is_undefined() {
+                continue;
+            }
+            let value = value
+                .as_f64()
+                .ok_or_else(|| JsError::new(&format!("{} should be a number", input.name())))?;
+            bird.set_input_value_for_type(&input, value as f32);
+        }
+        Ok(bird.get_bird_seed_string())
+    }
+
+    // A child of `parent`, either with `mate` or (like the app) a random mate pulled towards the parent
+    #[wasm_bindgen]
+    pub fn breed(
+        parent: &str,
+        mate: Option<String>,
+        #[wasm_bindgen(js_name = rngSeed)] rng_seed: Option<u32>,
+    ) -> Result<String, JsError> {
+        let parent = parse(parent)?;
+        let mut rng = rng(rng_seed);
+        let mate = match mate {
+            Some(mate) => parse(&mate)?,
+            None => {
+                let options = BreedingOptions::default();
+                let mut mate = BirdGenInputs::get_semi_random_bird_with_rng(&mut rng);
+                mate.bird_lerp(
+                    &parent,
+                    rng.random_range(options.min_mate_lerp..=options.max_mate_lerp),
+                );
+                mate
+            }
+        };
+        Ok(parent
+            .get_child_with_rng(&mate, &mut rng)
+            .get_bird_seed_string())
+    }
+
+    #[wasm_bindgen(js_name = randomSeed)]
+    pub fn random_seed(#[wasm_bindgen(js_name = rngSeed)] rng_seed: Option<u32>) -> String {
+        let mut bird = BirdGenInputs::default();
+        bird.randomize_values_with_rng(&mut rng(rng_seed));
+        bird.get_bird_seed_string()
+    }
+
+    #[wasm_bindgen(js_name = goodSeed)]
+    pub fn good_seed(#[wasm_bindgen(js_name = rngSeed)] rng_seed: Option<u32>) -> String {
+        BirdGenInputs::get_a_good_bird_with_rng(&mut rng(rng_seed)).get_bird_seed_string()
+    }
+
+    #[wasm_bindgen(js_name = goodSeeds)]
+    pub fn good_seeds() -> Array {
+        GOOD_BIRDS
+            .iter()
+            .map(|seed| JsValue::from_str(seed))
+            .collect()
+    }
+
+    // The printable STL, same as the app's save button, as a Uint8Array
+    #[wasm_bindgen(js_name = birdStl)]
+    pub fn bird_stl(seed: &str, precision: Option<String>) -> Result<Vec<u8>, JsError> {
+        let bird = parse(seed)?;
+        let export = BirdExport::new(
+            &bird,
+            &ExportOptions {
+                mesh: settings(precision)?,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| JsError::new(&e))?;
+        Ok(export.to_stl(&format!("coolbird-{}", export.inputs.get_bird_seed_string())))
+    }
+
+    // Size, filament and printability, with the same names as the command line's --json
+    #[wasm_bindgen(js_name = birdMetrics)]
+    pub fn bird_metrics(seed: &str, precision: Option<String>) -> Result<Object, JsError> {
+        let bird = parse(seed)?;
+        let meshes = BirdCSGMeshes::generate(&bird, &settings(precision)?);
+        let metrics = meshes.metrics(&FilamentOptions::default());
+        let printability = check_printability(&bird, &meshes, &PrintabilityOptions::default());
+        let point = |point: [f64; 3]| -> JsValue {
+            point
+                .iter()
+                .map(|value| JsValue::from_f64(*value))
+                .collect::<Array>()
+                .into()
+        };
+        let warnings: Array = printability
+            .problems
+            .iter()
+            .map(|problem| JsValue::from_str(&problem.describe()))
+            .collect();
+        Ok(object(&[
+            ("seed", bird.get_bird_seed_string().into()),
+            ("volume_mm3", metrics.volume.into()),
+            ("surface_area_mm2", metrics.surface_area.into()),
+            (
+                "bounds",
+                object(&[
+                    ("min", point(metrics.bounds.min.to_array())),
+                    ("max", point(metrics.bounds.max.to_array())),
+                ])
+                .into(),
+            ),
+            (
+                "filament",
+                object(&[
+                    (
+                        "material_volume_mm3",
+                        metrics.filament.material_volume.into(),
+                    ),
+                    ("mass_g", metrics.filament.mass_grams.into()),
+                    ("length_mm", metrics.filament.length_mm.into()),
+                ])
+                .into(),
+            ),
+            ("printable", printability.is_printable().into()),
+            ("warnings", warnings.into()),
+        ]))
+    }
+
+    // Shows `seed` in the app, next frame (throws straight away if it's not a seed)
+    #[wasm_bindgen(js_name = loadSeed)]
+    pub fn load_seed(seed: &str) -> Result<(), JsError> {
+        parse(seed)?;
+        push(JsCommand::LoadSeed(seed.trim().to_string()));
+        Ok(())
+    }
+
+    // Same as the randomize button
+    #[wasm_bindgen(js_name = randomizeBird)]
+    pub fn randomize_bird() {
+        push(JsCommand::Randomize);
+    }
+
+    // The seed bird the app's showing right now (empty until the app's started)
+    #[wasm_bindgen(js_name = currentSeed)]
+    pub fn current_seed() -> String {
+        CURRENT_SEED
+            .lock()
+            .map(|seed| seed.clone())
+            .unwrap_or_default()
+    }
+
+    fn push(command: JsCommand) {
+        if let Ok(mut commands) = COMMANDS.lock() {
+            commands.push(command);
+        }
+    }
+
+    pub(super) fn run_js_commands(
+        mut bird_inputs: ResMut<BirdGenInputs>,
+        mut rebuild_writer: MessageWriter<RebuildBird>,
+        mut log_writer: MessageWriter<NewLog>,
+    ) {
+        let commands = match COMMANDS.lock() {
+            Ok(mut commands) => std::mem::take(&mut *commands),
+            Err(_) => return,
+        };
+        for command in commands {
+            match command {
+                JsCommand::LoadSeed(seed) => {
+                    if bird_inputs.update_from_seed_string(seed.clone()).is_ok() {
+                        log_writer.write(NewLog {
+                            text: format!("loaded bird seed from the page\n{seed}"),
+                        });
+                        rebuild_writer.write(RebuildBird);
+                    }
+                }
+                JsCommand::Randomize => {
+                    bird_inputs.randomize_values();
+                    rebuild_writer.write(RebuildBird);
+                }
+            }
+        }
+    }
+
+    // Whenever the seed bird changes, for `currentSeed()` and anyone listening for the event
+    pub(super) fn publish_current_seed(bird_inputs: Res<BirdGenInputs>) {
+        if !bird_inputs.is_changed() {
+            return;
+        }
+        let seed = bird_inputs.get_bird_seed_string();
+        if let Ok(mut current) = CURRENT_SEED.lock() {
+            current.clone_from(&seed);
+        }
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(&JsValue::from_str(&seed));
+        if let Ok(event) = web_sys::CustomEvent::new_with_event_init_dict(SEED_EVENT, &init) {
+            let _ = window.dispatch_event(&event);
+        }
+    }
+}
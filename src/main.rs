@@ -1,8 +1,10 @@
 use crate::clipboard::ClipboardPlugin;
 use crate::{
+    js_api::JsApiPlugin,
     log_text::{LogTextPlugin, NewLog},
     random_words::get_random_exaclamatory,
     ui::BirdUIPlugin,
+    yield_now::yield_to_browser,
 };
 use bevy::{
     camera::Viewport,
@@ -14,16 +16,23 @@ use bevy::{
     input_focus::{InputDispatchPlugin, tab_navigation::TabNavigationPlugin},
     picking::hover::HoverMap,
     prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future},
 };
 use bevy_file_dialog::FileDialogPlugin;
-use birdgen::{BirdGenInputs, RecentBirds, generate_bird_body_mesh, generate_bird_head_mesh};
+use birdgen::{
+    AttachedBird, BirdCSGMeshes, BirdGenInputs, BirdMeshSettings, BirdMeshes, BirdMetrics,
+    MeshCache, PrintabilityOptions, RecentBirds, generate_bird_body_csg_mesh_with,
+    generate_bird_head_csg_mesh_with,
+};
 use rand::{Rng, seq::IndexedRandom};
 
 mod clipboard;
+mod js_api;
 mod log_text;
 mod open_link;
 mod random_words;
 mod ui;
+mod yield_now;
 
 pub const BG_COLOR: Color = Color::srgb(0.47, 0.49, 0.68);
 
@@ -38,6 +47,17 @@ enum BirdState {
 
 pub struct BirdSTLContents;
 
+pub struct Bird3MFContents;
+
+// Size and filament numbers for the seed bird, for the stats line under it
+// (None while it's still being built)
+#[derive(Resource, Default)]
+struct SeedBirdMetrics(Option<BirdMetrics>);
+
+// When set, children that won't print get re-bred (or nudged) before we show them
+#[derive(Resource, Default)]
+struct PrintabilityFilter(Option<PrintabilityOptions>);
+
 fn main() {
     App::new()
         .add_plugins((
@@ -51,7 +71,9 @@ fn main() {
                 ..default()
             }),
             ClipboardPlugin,
-            FileDialogPlugin::new().with_save_file::<BirdSTLContents>(),
+            FileDialogPlugin::new()
+                .with_save_file::<BirdSTLContents>()
+                .with_save_file::<Bird3MFContents>(),
         ))
         .add_plugins(LogTextPlugin)
         .add_plugins(InputDispatchPlugin)
@@ -60,11 +82,17 @@ fn main() {
         .insert_state(BirdState::BirdVisible)
         .insert_resource(ClearColor(BG_COLOR))
         .insert_resource(BirdGenInputs::get_a_good_bird())
+        .insert_resource(BirdMeshSettings::default())
+        .init_resource::<BirdMeshTasks>()
+        .init_resource::<MeshCache>()
+        .init_resource::<SeedBirdMetrics>()
+        .init_resource::<PrintabilityFilter>()
         .insert_resource(RecentBirds {
             left: BirdGenInputs::default(),
             right: BirdGenInputs::default(),
         })
         .add_plugins(BirdUIPlugin)
+        .add_plugins(JsApiPlugin)
         .add_systems(Startup, (spawn_camera_and_light, kick_off_bird_load))
         .add_systems(
             Update,
@@ -75,7 +103,13 @@ fn main() {
                 zoom_system,
             ),
         )
-        .add_systems(OnEnter(BirdState::Loading), spawn_bird_mesh)
+        .add_systems(OnEnter(BirdState::Loading), start_bird_mesh_tasks)
+        .add_systems(
+            Update,
+            poll_bird_mesh_tasks
+                .after(handle_bird_rebuild)
+                .run_if(in_state(BirdState::Loading)),
+        )
         .run();
 }
 
@@ -86,6 +120,7 @@ fn handle_bird_rebuild(
     mut bird_rebuild_reader: MessageReader<RebuildBird>,
     bird_mesh_query: Query<Entity, With<BirdMesh>>,
     mut commands: Commands,
+    mut mesh_tasks: ResMut<BirdMeshTasks>,
     mut next_bird_state: ResMut<NextState<BirdState>>,
     mut log_writer: MessageWriter<NewLog>,
 ) {
@@ -96,6 +131,8 @@ fn handle_bird_rebuild(
         for bird_mesh_entity in bird_mesh_query.iter() {
             commands.entity(bird_mesh_entity).despawn();
         }
+        // drop any half built birds, re-entering Loading starts fresh ones
+        mesh_tasks.0.clear();
         next_bird_state.set(BirdState::Loading);
     }
 }
@@ -146,13 +183,31 @@ fn get_bird_transform_offset(focus: BirdCamFocus) -> Vec3 {
     }
 }
 
-fn spawn_bird_mesh(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
+// One bird's meshes, either straight out of the cache or being built off the main thread,
+// and where they go once they're done
+// (the task hands back the inputs too, since a printable child might not be the one we started with)
+struct PendingBird {
+    inputs: BirdGenInputs,
+    task: Option<Task<(BirdGenInputs, BirdMeshes)>>,
+    meshes: Option<BirdMeshes>,
+    material: Handle<StandardMaterial>,
+    offset: Vec3,
+    focus: BirdCamFocus,
+}
+
+// Everything the current `Loading` state is waiting on
+// Clearing this drops the tasks, which cancels them
+#[derive(Resource, Default)]
+struct BirdMeshTasks(Vec<PendingBird>);
+
+fn start_bird_mesh_tasks(
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut next_bird_state: ResMut<NextState<BirdState>>,
-    mut recent_birds: ResMut<RecentBirds>,
+    mut mesh_tasks: ResMut<BirdMeshTasks>,
+    mut mesh_cache: ResMut<MeshCache>,
+    mut seed_metrics: ResMut<SeedBirdMetrics>,
     bird_inputs: Res<BirdGenInputs>,
+    mesh_settings: Res<BirdMeshSettings>,
+    printability_filter: Res<PrintabilityFilter>,
 ) {
     let colors = get_colors(3);
     let left_bird_mat = materials.add(StandardMaterial {
@@ -168,10 +223,7 @@ fn spawn_bird_mesh(
         ..default()
     });
 
-    let current_bird_inputs = bird_inputs.into_inner();
-    // create mesh of current bird for display
-    let seed_head_mesh = generate_bird_head_mesh(&current_bird_inputs);
-    let seed_body_mesh = generate_bird_body_mesh(&current_bird_inputs);
+    let current_bird_inputs = *bird_inputs;
 
     // deviate bird inputs for left and right bird separately
     let mut rng = rand::rng();
@@ -182,50 +234,149 @@ fn spawn_bird_mesh(
     let left_bird_inputs = current_bird_inputs.get_child_with(&left_bird_source);
     let right_bird_inputs = current_bird_inputs.get_child_with(&right_bird_source);
 
-    // update RecentBirds
-    recent_birds.left = left_bird_inputs;
-    recent_birds.right = right_bird_inputs;
+    // reuse meshes we've built before, otherwise kick off a task for the bird
+    // (the CSG work is what used to freeze the window)
+    let task_pool = AsyncComputeTaskPool::get();
+    let settings = *mesh_settings;
+    let printability = printability_filter.0;
+    mesh_tasks.0.clear();
+    seed_metrics.0 = None;
+    for (inputs, mate, material, focus) in [
+        (
+            current_bird_inputs,
+            None,
+            seed_bird_mat,
+            BirdCamFocus::SeedBird,
+        ),
+        (
+            left_bird_inputs,
+            Some(left_bird_source),
+            left_bird_mat,
+            BirdCamFocus::LeftBird,
+        ),
+        (
+            right_bird_inputs,
+            Some(right_bird_source),
+            right_bird_mat,
+            BirdCamFocus::RightBird,
+        ),
+    ] {
+        // printable children get bred inside the task, checking them means building them anyway
+        let breed = mate.zip(printability);
+        let meshes = match breed {
+            Some(_) => None,
+            None => mesh_cache.get(&inputs, &settings),
+        };
+        let task = meshes.is_none().then(|| {
+            task_pool.spawn(async move {
+                // on wasm all three birds' tasks start back to back on the main thread,
+                // so let a frame through before each big chunk of CSG work
+                yield_to_browser().await;
+                let Some((mate, options)) = breed else {
+                    return build_bird_meshes(inputs, settings).await;
+                };
+                // (the printable child's retries still run in one go, it only stops between birds)
+                let child =
+                    current_bird_inputs.get_printable_child_with(&mate, &settings, &options);
+                if !child.report.is_printable() {
+                    let problems: Vec<String> = child
+                        .report
+                        .problems
+                        .iter()
+                        .map(|problem| problem.describe())
+                        .collect();
+                    info!(
+                        "no printable child after {} tries ({})",
+                        child.attempts,
+                        problems.join(", ")
+                    );
+                }
+                (child.inputs, BirdMeshes::from_csg(&child.meshes))
+            })
+        });
+        mesh_tasks.0.push(PendingBird {
+            inputs,
+            task,
+            meshes,
+            material,
+            offset: get_bird_transform_offset(focus),
+            focus,
+        });
+    }
+    let stats = mesh_cache.stats();
+    info!(
+        "mesh cache: {} hits, {} misses ({}/{} birds stored)",
+        stats.hits, stats.misses, stats.entries, stats.capacity
+    );
+}
 
-    let left_head_mesh = generate_bird_head_mesh(&left_bird_inputs);
-    let left_body_mesh = generate_bird_body_mesh(&left_bird_inputs);
-    let right_head_mesh = generate_bird_head_mesh(&right_bird_inputs);
-    let right_body_mesh = generate_bird_body_mesh(&right_bird_inputs);
-    commands.spawn((
-        Mesh3d(meshes.add(seed_head_mesh)),
-        MeshMaterial3d(seed_bird_mat.clone()),
-        Transform::from_xyz(0.0, -100000.0, 0.0),
-        BirdMesh,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(seed_body_mesh)),
-        MeshMaterial3d(seed_bird_mat),
-        Transform::from_xyz(0.0, -100000.0, 0.0),
-        BirdMesh,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(left_head_mesh)),
-        MeshMaterial3d(left_bird_mat.clone()),
-        Transform::from_xyz(0.0, 0.0, 0.0),
-        BirdMesh,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(left_body_mesh)),
-        MeshMaterial3d(left_bird_mat),
-        Transform::from_xyz(0.0, 0.0, 0.0),
-        BirdMesh,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(right_head_mesh)),
-        MeshMaterial3d(right_bird_mat.clone()),
-        Transform::from_xyz(0.0, 100000.0, 0.0),
-        BirdMesh,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(right_body_mesh)),
-        MeshMaterial3d(right_bird_mat),
-        Transform::from_xyz(0.0, 100000.0, 0.0),
-        BirdMesh,
-    ));
+// On wasm the head and body separately with a frame in between (unless the neck fix needs them
+// together), native builds them side by side on other threads anyway
+// Hands back the inputs too, a clamped neck means a slightly different bird than was asked for
+async fn build_bird_meshes(
+    inputs: BirdGenInputs,
+    settings: BirdMeshSettings,
+) -> (BirdGenInputs, BirdMeshes) {
+    if !cfg!(target_arch = "wasm32") || settings.neck_fix.is_some() {
+        let bird = AttachedBird::generate(&inputs, &settings);
+        return (bird.inputs, BirdMeshes::from_csg(&bird.meshes));
+    }
+    let head = generate_bird_head_csg_mesh_with(&inputs, &settings);
+    yield_to_browser().await;
+    let body = generate_bird_body_csg_mesh_with(&inputs, &settings);
+    yield_to_browser().await;
+    (inputs, BirdMeshes::from_csg(&BirdCSGMeshes { head, body }))
+}
+
+// Check in on the mesh tasks once a frame, and only show the birds once they're all done
+fn poll_bird_mesh_tasks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh_tasks: ResMut<BirdMeshTasks>,
+    mut mesh_cache: ResMut<MeshCache>,
+    mesh_settings: Res<BirdMeshSettings>,
+    mut seed_metrics: ResMut<SeedBirdMetrics>,
+    mut recent_birds: ResMut<RecentBirds>,
+    mut next_bird_state: ResMut<NextState<BirdState>>,
+) {
+    // nothing in flight (e.g. a re-roll just cancelled everything, new tasks start next frame)
+    if mesh_tasks.0.is_empty() {
+        return;
+    }
+    for pending in mesh_tasks.0.iter_mut() {
+        if pending.meshes.is_some() {
+            continue;
+        }
+        let Some(task) = &mut pending.task else {
+            continue;
+        };
+        if let Some((inputs, built)) = block_on(future::poll_once(task)) {
+            mesh_cache.insert(&inputs, &mesh_settings, built.clone());
+            pending.inputs = inputs;
+            pending.meshes = Some(built);
+        }
+    }
+    if mesh_tasks.0.iter().any(|pending| pending.meshes.is_none()) {
+        return;
+    }
+    for pending in mesh_tasks.0.drain(..) {
+        let Some(bird_meshes) = pending.meshes else {
+            continue;
+        };
+        match pending.focus {
+            BirdCamFocus::SeedBird => seed_metrics.0 = Some(bird_meshes.metrics),
+            BirdCamFocus::LeftBird => recent_birds.left = pending.inputs,
+            BirdCamFocus::RightBird => recent_birds.right = pending.inputs,
+        }
+        for mesh in [bird_meshes.head, bird_meshes.body] {
+            commands.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(pending.material.clone()),
+                Transform::from_translation(pending.offset),
+                BirdMesh,
+            ));
+        }
+    }
     next_bird_state.set(BirdState::BirdVisible);
 }
 
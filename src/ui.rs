@@ -1,7 +1,7 @@
 use crate::clipboard::{Clipboard, ClipboardRead};
 use crate::{
-    BG_COLOR, BirdSTLContents, BirdState, RebuildBird, log_text::NewLog, open_link,
-    random_words::get_bird_description,
+    BG_COLOR, Bird3MFContents, BirdSTLContents, BirdState, PrintabilityFilter, RebuildBird,
+    SeedBirdMetrics, log_text::NewLog, open_link, random_words::get_bird_description,
 };
 use bevy::{
     picking::hover::Hovered,
@@ -10,7 +10,9 @@ use bevy::{
     ui_widgets::{Activate, Button, UiWidgetsPlugins, observe},
 };
 use bevy_file_dialog::FileDialogExt;
-use birdgen::{BirdGenInputs, RecentBirds};
+use birdgen::{
+    BirdGenInputs, BirdMeshSettings, BodyMode, ExportOptions, PrintabilityOptions, RecentBirds,
+};
 
 const NORMAL_BUTTON: Color = Color::srgba(0., 0., 0., 0.00);
 const HOVERED_BUTTON: Color = Color::srgba(1.0, 1.0, 1.0, 0.95);
@@ -44,6 +46,8 @@ impl Plugin for BirdUIPlugin {
                     update_button_text_style,
                     listen_for_pasted_values,
                     handle_bird_rebuild,
+                    update_loading_indicator,
+                    update_seed_bird_stats,
                 ),
             );
     }
@@ -63,6 +67,45 @@ fn handle_bird_rebuild(
     }
 }
 
+#[derive(Component)]
+struct LoadingIndicator;
+
+// little animated "hatching birds..." while the meshes are being built
+fn update_loading_indicator(
+    bird_state: Res<State<BirdState>>,
+    time: Res<Time>,
+    mut indicators: Query<(&mut Text, &mut Visibility), With<LoadingIndicator>>,
+) {
+    let loading = *bird_state.get() == BirdState::Loading;
+    for (mut text, mut visibility) in &mut indicators {
+        if loading {
+            *visibility = Visibility::Visible;
+            let dots = (time.elapsed_secs() * 3.0) as usize % 4;
+            text.0 = format!("hatching birds{}", ".".repeat(dots));
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+#[derive(Component)]
+struct SeedBirdStats;
+
+fn update_seed_bird_stats(
+    seed_metrics: Res<SeedBirdMetrics>,
+    mut stats_text: Query<&mut Text, With<SeedBirdStats>>,
+) {
+    if !seed_metrics.is_changed() {
+        return;
+    }
+    for mut text in &mut stats_text {
+        text.0 = match &seed_metrics.0 {
+            Some(metrics) => metrics.summary(),
+            None => String::new(),
+        };
+    }
+}
+
 #[derive(Resource)]
 struct PasteWatcher(Option<ClipboardRead>);
 
@@ -109,6 +152,29 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         IsDefaultUiCamera,
     ));
 
+    // loading indicator, shown while birds are being generated
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: vw(100),
+            top: px(24),
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        children![(
+            LoadingIndicator,
+            Text::new("hatching birds"),
+            TextFont {
+                font: asset_server.load(FONT_PATH_MONTREAL),
+                font_size: INSTRUCTIONS_LABEL_FONT_SIZE,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Visibility::Hidden,
+        )],
+    ));
+
     // app label
     commands.spawn((
         Node {
@@ -213,6 +279,26 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 ..default()
             },
             TextColor(TEXT_COLOR),
+        ), (
+            // size + filament estimate (PLA, 20% infill)
+            Node {
+                margin: UiRect {
+                    left: px(376),
+                    right: px(0),
+                    top: px(4),
+                    bottom: px(0)
+                },
+                max_width: px(540),
+                ..default()
+            },
+            SeedBirdStats,
+            Text::new(""),
+            TextFont {
+                font: asset_server.load(FONT_PATH_OT_BRUT_REGULAR),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(FADED_TEXT_COLOR),
         )],
     ));
 
@@ -281,6 +367,7 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 observe(
                     |_activate: On<Activate>,
                      bird_inputs: Res<BirdGenInputs>,
+                     mesh_settings: Res<BirdMeshSettings>,
                      mut commands: Commands,
                      bird_state: Res<State<BirdState>>,
                      mut log_writer: MessageWriter<NewLog>| {
@@ -289,7 +376,10 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 text: "creating bird STL...".to_string(),
                             });
                             // make bird stl
-                            let stl_bytes_result = bird_inputs.get_stl();
+                            let stl_bytes_result = bird_inputs.get_stl_with(&ExportOptions {
+                                mesh: *mesh_settings,
+                                ..default()
+                            });
                             match stl_bytes_result {
                                 Ok(stl_binary) => {
                                     // pop a file dialog for them to save the file
@@ -309,6 +399,90 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     }
                 )
             ),
+            (
+                bird_action_button(&asset_server, "save parts".to_string()),
+                observe(
+                    |_activate: On<Activate>,
+                     bird_inputs: Res<BirdGenInputs>,
+                     mesh_settings: Res<BirdMeshSettings>,
+                     mut commands: Commands,
+                     bird_state: Res<State<BirdState>>,
+                     mut log_writer: MessageWriter<NewLog>| {
+                        if *bird_state.get() == BirdState::BirdVisible {
+                            log_writer.write(NewLog {
+                                text: "splitting bird into parts...".to_string(),
+                            });
+                            // beak, skull, eyes, body & tail as separate objects for multi colour printing
+                            match bird_inputs.get_3mf_parts(&mesh_settings) {
+                                Ok(model_bytes) => {
+                                    commands
+                                        .dialog()
+                                        .add_filter("3MF", &["3mf"])
+                                        .set_file_name("coolbird.3mf")
+                                        .save_file::<Bird3MFContents>(model_bytes);
+                                }
+                                _ => {
+                                    log_writer.write(NewLog {
+                                        text: "yikes couldn't split the bird up oops".to_string(),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                )
+            ),
+            (
+                bird_action_button(&asset_server, "smooth".to_string()),
+                observe(
+                    |_activate: On<Activate>,
+                     mut mesh_settings: ResMut<BirdMeshSettings>,
+                     mut rebuild_writer: MessageWriter<RebuildBird>,
+                     mut log_writer: MessageWriter<NewLog>,
+                     bird_state: Res<State<BirdState>>| {
+                        if *bird_state.get() == BirdState::BirdVisible {
+                            // flip between hulled and blended bodies
+                            mesh_settings.body_mode = match mesh_settings.body_mode {
+                                BodyMode::Hull => BodyMode::smooth(),
+                                BodyMode::Smooth { .. } => BodyMode::Hull,
+                            };
+                            let text = match mesh_settings.body_mode {
+                                BodyMode::Hull => "back to hulled bodies",
+                                BodyMode::Smooth { .. } => "smooth bodies engaged",
+                            };
+                            log_writer.write(NewLog {
+                                text: text.to_string(),
+                            });
+                            rebuild_writer.write(RebuildBird);
+                        }
+                    }
+                )
+            ),
+            (
+                bird_action_button(&asset_server, "printable".to_string()),
+                observe(
+                    |_activate: On<Activate>,
+                     mut printability_filter: ResMut<PrintabilityFilter>,
+                     mut rebuild_writer: MessageWriter<RebuildBird>,
+                     mut log_writer: MessageWriter<NewLog>,
+                     bird_state: Res<State<BirdState>>| {
+                        if *bird_state.get() == BirdState::BirdVisible {
+                            // only offer children that'll survive the printer
+                            printability_filter.0 = match printability_filter.0 {
+                                Some(_) => None,
+                                None => Some(PrintabilityOptions::default()),
+                            };
+                            let text = match printability_filter.0 {
+                                Some(_) => "only breeding printable birds",
+                                None => "breeding birds of any shape",
+                            };
+                            log_writer.write(NewLog {
+                                text: text.to_string(),
+                            });
+                            rebuild_writer.write(RebuildBird);
+                        }
+                    }
+                )
+            ),
             (
                 bird_action_button(&asset_server, "randomize".to_string()),
                 observe(
@@ -515,12 +689,10 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                         bird_selection_button(&asset_server, "re-roll".to_string()),
                         observe(
                             |_activate: On<Activate>,
-                             mut rebuild_writer: MessageWriter<RebuildBird>,
-                             bird_state: Res<State<BirdState>>| {
-                                if *bird_state.get() == BirdState::BirdVisible {
-                                    // Just re-run without setting new inputs, bye left & right
-                                    rebuild_writer.write(RebuildBird);
-                                }
+                             mut rebuild_writer: MessageWriter<RebuildBird>| {
+                                // Just re-run without setting new inputs, bye left & right
+                                // (works while loading too, the birds being built get thrown out)
+                                rebuild_writer.write(RebuildBird);
                             }
                         ),
                     ),
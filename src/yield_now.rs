@@ -0,0 +1,21 @@
+// helper for letting the browser draw a frame in the middle of building birds
+// on wasm the compute task pool runs everything on the main thread, so a task only gives the
+// page a chance to redraw when it awaits something that goes back through the event loop
+// native tasks have threads of their own, nothing to do there
+pub async fn yield_to_browser() {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let timeout = js_sys::Promise::new(&mut |resolve, _reject| {
+            let queued = web_sys::window().is_some_and(|window| {
+                window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 0)
+                    .is_ok()
+            });
+            // no window to wait on, carry on straight away rather than never waking up
+            if !queued {
+                let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+            }
+        });
+        let _ = wasm_bindgen_futures::JsFuture::from(timeout).await;
+    }
+}